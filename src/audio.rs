@@ -66,6 +66,7 @@ impl SoundData {
     /// Load the file at the given path and create a new `SoundData` from it.
     pub fn new<P: AsRef<path::Path>>(path: P) -> GameResult<Self> {
         let path = path.as_ref();
+        trace_scope!("SoundData::new", path = %path.display());
         let file = &mut File::open(path)?;
         SoundData::from_read(file)
     }
@@ -292,6 +293,7 @@ impl Source {
     /// Creates a new `Source` using the given `SoundData` object.
     pub fn from_data(audio: &impl Has<AudioContext>, data: SoundData) -> GameResult<Self> {
         let audio = audio.retrieve();
+        trace_scope!("Source::from_data", bytes = data.0.len());
         if !data.can_play() {
             return Err(GameError::AudioError(
                 "Could not decode the given audio data".to_string(),
@@ -458,6 +460,7 @@ impl SpatialSource {
     /// Creates a new `SpatialSource` using the given `SoundData` object.
     pub fn from_data(audio: &impl Has<AudioContext>, data: SoundData) -> GameResult<Self> {
         let audio = audio.retrieve();
+        trace_scope!("SpatialSource::from_data", bytes = data.0.len());
         if !data.can_play() {
             return Err(GameError::AudioError(
                 "Could not decode the given audio data".to_string(),
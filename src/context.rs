@@ -49,6 +49,8 @@ pub struct Context {
     /// Gamepad input context.
     #[cfg(feature = "gamepad")]
     pub gamepad: input::gamepad::GamepadContext,
+    /// Built-in debug overlay, drawn on top of every frame when visible.
+    pub debug_overlay: graphics::DebugOverlay,
 
     /// The Conf object the Context was created with.
     /// It's here just so that we can see the original settings,
@@ -176,6 +178,7 @@ impl Context {
             mouse: input::mouse::MouseContext::new(),
             #[cfg(feature = "gamepad")]
             gamepad: input::gamepad::GamepadContext::new()?,
+            debug_overlay: graphics::DebugOverlay::new(),
         };
 
         Ok((ctx, events_loop))
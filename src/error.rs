@@ -132,6 +132,12 @@ impl From<image::ImageError> for GameError {
         GameError::ResourceLoadError(errstr)
     }
 }
+impl From<serde_json::Error> for GameError {
+    fn from(e: serde_json::Error) -> GameError {
+        let errstr = format!("JSON parse error: {e}");
+        GameError::ResourceLoadError(errstr)
+    }
+}
 impl From<winit::error::OsError> for GameError {
     fn from(s: winit::error::OsError) -> GameError {
         GameError::WindowCreationError(Arc::new(s))
@@ -490,52 +490,60 @@ where
                 // `ctx.process_event()` calls.  These update ggez's
                 // internal state however necessary.
                 ctx.time.tick();
+                trace_scope!("frame", frame = ctx.time.ticks());
 
                 // Handle gamepad events if necessary.
                 #[cfg(feature = "gamepad")]
-                while let Some(gilrs::Event { id, event, .. }) = ctx.gamepad.next_event() {
-                    match event {
-                        gilrs::EventType::ButtonPressed(button, _) => {
-                            let res = state.gamepad_button_down_event(ctx, button, GamepadId(id));
-                            if catch_error(
-                                ctx,
-                                res,
-                                state,
-                                control_flow,
-                                ErrorOrigin::GamepadButtonDownEvent,
-                            ) {
-                                return;
-                            };
-                        }
-                        gilrs::EventType::ButtonReleased(button, _) => {
-                            let res = state.gamepad_button_up_event(ctx, button, GamepadId(id));
-                            if catch_error(
-                                ctx,
-                                res,
-                                state,
-                                control_flow,
-                                ErrorOrigin::GamepadButtonUpEvent,
-                            ) {
-                                return;
-                            };
+                {
+                    trace_scope!("gamepad_events");
+                    while let Some(gilrs::Event { id, event, .. }) = ctx.gamepad.next_event() {
+                        match event {
+                            gilrs::EventType::ButtonPressed(button, _) => {
+                                let res =
+                                    state.gamepad_button_down_event(ctx, button, GamepadId(id));
+                                if catch_error(
+                                    ctx,
+                                    res,
+                                    state,
+                                    control_flow,
+                                    ErrorOrigin::GamepadButtonDownEvent,
+                                ) {
+                                    return;
+                                };
+                            }
+                            gilrs::EventType::ButtonReleased(button, _) => {
+                                let res = state.gamepad_button_up_event(ctx, button, GamepadId(id));
+                                if catch_error(
+                                    ctx,
+                                    res,
+                                    state,
+                                    control_flow,
+                                    ErrorOrigin::GamepadButtonUpEvent,
+                                ) {
+                                    return;
+                                };
+                            }
+                            gilrs::EventType::AxisChanged(axis, value, _) => {
+                                let res = state.gamepad_axis_event(ctx, axis, value, GamepadId(id));
+                                if catch_error(
+                                    ctx,
+                                    res,
+                                    state,
+                                    control_flow,
+                                    ErrorOrigin::GamepadAxisEvent,
+                                ) {
+                                    return;
+                                };
+                            }
+                            _ => {}
                         }
-                        gilrs::EventType::AxisChanged(axis, value, _) => {
-                            let res = state.gamepad_axis_event(ctx, axis, value, GamepadId(id));
-                            if catch_error(
-                                ctx,
-                                res,
-                                state,
-                                control_flow,
-                                ErrorOrigin::GamepadAxisEvent,
-                            ) {
-                                return;
-                            };
-                        }
-                        _ => {}
                     }
                 }
 
-                let res = state.update(ctx);
+                let res = {
+                    trace_scope!("update");
+                    state.update(ctx)
+                };
                 if catch_error(ctx, res, state, control_flow, ErrorOrigin::Update) {
                     return;
                 };
@@ -546,7 +554,11 @@ where
                     *control_flow = ControlFlow::Exit;
                 }
 
-                if let Err(e) = state.draw(ctx) {
+                let res = {
+                    trace_scope!("draw");
+                    state.draw(ctx)
+                };
+                if let Err(e) = res {
                     error!("Error on EventHandler::draw(): {e:?}");
                     eprintln!("Error on EventHandler::draw(): {e:?}");
                     if state.on_error(ctx, ErrorOrigin::Draw, e) {
@@ -555,6 +567,11 @@ where
                     }
                 }
 
+                if let Err(e) = ctx.debug_overlay.draw(&mut ctx.gfx, &ctx.time) {
+                    error!("Error on DebugOverlay::draw(): {e:?}");
+                    eprintln!("Error on DebugOverlay::draw(): {e:?}");
+                }
+
                 if let Err(e) = ctx.gfx.end_frame() {
                     error!("Error on GraphicsContext::end_frame(): {e:?}");
                     eprintln!("Error on GraphicsContext::end_frame(): {e:?}");
@@ -572,7 +589,13 @@ where
             }
             Event::RedrawRequested(_) => (),
             Event::RedrawEventsCleared => (),
-            Event::LoopDestroyed => (),
+            Event::LoopDestroyed => {
+                // make sure a recording in progress gets its last frames written
+                if let Err(e) = ctx.gfx.stop_recording() {
+                    error!("Error on GraphicsContext::stop_recording(): {e:?}");
+                    eprintln!("Error on GraphicsContext::stop_recording(): {e:?}");
+                }
+            }
         }
     })
 }
@@ -642,6 +665,12 @@ pub fn process_event(ctx: &mut Context, event: &mut winit::event::Event<()>) {
                 };
                 ctx.keyboard.set_scancode(*scancode, pressed);
                 if let Some(key) = keycode {
+                    if pressed
+                        && !ctx.keyboard.is_key_pressed(*key)
+                        && ctx.debug_overlay.toggle_key() == Some(*key)
+                    {
+                        ctx.debug_overlay.toggle();
+                    }
                     ctx.keyboard.set_key(*key, pressed);
                 }
             }
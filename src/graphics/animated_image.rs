@@ -0,0 +1,235 @@
+use super::{
+    context::GraphicsContext, image::Image, Animation, Canvas, DrawParam, Drawable, ImageFormat,
+    Rect, SpriteFrame, SpriteSheet,
+};
+use crate::{context::Has, timer::TimeContext, GameError, GameResult};
+use ::image::{
+    codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+    AnimationDecoder, Frame, ImageFormat as EncodedFormat, RgbaImage,
+};
+use std::{fs::File, io::Read, path::Path, time::Duration};
+
+/// Browsers show frames with a delay this short for 100 milliseconds instead,
+/// and animations are authored with that in mind.
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+
+/// An animated GIF, APNG or WebP image.
+///
+/// Every frame is decoded up front and laid out in a grid on a single [`Image`], which is
+/// played back by an [`Animation`] using the frame delays stored in the file. Files with a
+/// single frame, or formats that can't be animated, load as one frame that never changes.
+///
+/// ```rust,no_run
+/// # use ggez::graphics::{self, AnimatedImage};
+/// # fn t(ctx: &mut ggez::Context) -> ggez::GameResult {
+/// let mut spinner = AnimatedImage::from_path(ctx, "/spinner.gif")?;
+///
+/// // once per frame
+/// spinner.update(&ctx.time);
+/// let mut canvas = graphics::Canvas::from_frame(ctx, None);
+/// canvas.draw(&spinner, graphics::DrawParam::default().dest([16., 16.]));
+/// canvas.finish(ctx)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AnimatedImage {
+    animation: Animation,
+    width: u32,
+    height: u32,
+}
+
+impl AnimatedImage {
+    /// Loads an animated image from a file.
+    pub fn from_path(gfx: &impl Has<GraphicsContext>, path: impl AsRef<Path>) -> GameResult<Self> {
+        let mut encoded = Vec::new();
+        let _ = File::open(path)?.read_to_end(&mut encoded)?;
+        Self::from_bytes(gfx, &encoded)
+    }
+
+    /// Loads an animated image from encoded bytes.
+    pub fn from_bytes(gfx: &impl Has<GraphicsContext>, encoded: &[u8]) -> GameResult<Self> {
+        let gfx = gfx.retrieve();
+        let frames = decode(encoded)?;
+        let (width, height) = frames[0].0.dimensions();
+        let max = gfx.wgpu.device.limits().max_texture_dimension_2d;
+        let (sheet, columns) = pack(&frames, max).ok_or_else(|| {
+            GameError::ResourceLoadError(format!(
+                "{} frames of {width}x{height} don't fit into a {max}x{max} texture",
+                frames.len()
+            ))
+        })?;
+
+        let image = Image::from_pixels(
+            gfx,
+            &sheet,
+            ImageFormat::Rgba8UnormSrgb,
+            sheet.width(),
+            sheet.height(),
+        );
+        let sheet = SpriteSheet::from_frames(
+            image,
+            frames.iter().enumerate().map(|(i, (_, delay))| {
+                let (x, y) = (i as u32 % columns, i as u32 / columns);
+                SpriteFrame {
+                    duration: *delay,
+                    ..SpriteFrame::new(Rect::new(
+                        (x * width) as f32,
+                        (y * height) as f32,
+                        width as f32,
+                        height as f32,
+                    ))
+                }
+            }),
+        );
+
+        Ok(AnimatedImage {
+            animation: Animation::new(sheet, 0..frames.len()),
+            width,
+            height,
+        })
+    }
+
+    /// Advances the animation by the duration of the last frame.
+    pub fn update(&mut self, time: &TimeContext) {
+        self.animation.update(time.delta());
+    }
+
+    /// Returns the animation playing the frames, e.g. to check its position.
+    #[inline]
+    pub fn animation(&self) -> &Animation {
+        &self.animation
+    }
+
+    /// Returns the animation playing the frames, to pause it or change its speed or mode.
+    #[inline]
+    pub fn animation_mut(&mut self) -> &mut Animation {
+        &mut self.animation
+    }
+
+    /// Returns the number of frames.
+    #[inline]
+    pub fn frame_count(&self) -> usize {
+        self.animation.len()
+    }
+
+    /// Returns the width of a frame.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of a frame.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl Drawable for AnimatedImage {
+    fn draw(&self, canvas: &mut Canvas, param: impl Into<DrawParam>) {
+        self.animation.draw(canvas, param)
+    }
+
+    fn dimensions(&self, _gfx: &impl Has<GraphicsContext>) -> Option<Rect> {
+        Some(Rect::new(0., 0., self.width as f32, self.height as f32))
+    }
+}
+
+/// Decodes all frames with their delays; never returns an empty list.
+fn decode(encoded: &[u8]) -> GameResult<Vec<(RgbaImage, Duration)>> {
+    let frames = match ::image::guess_format(encoded)? {
+        EncodedFormat::Gif => collect(GifDecoder::new(encoded)?)?,
+        EncodedFormat::Png => {
+            let decoder = PngDecoder::new(encoded)?;
+            if decoder.is_apng() {
+                collect(decoder.apng())?
+            } else {
+                Vec::new()
+            }
+        }
+        // still WebP images decode to no frames at all
+        EncodedFormat::WebP => collect(WebPDecoder::new(encoded)?)?,
+        _ => Vec::new(),
+    };
+
+    if frames.is_empty() {
+        let still = ::image::load_from_memory(encoded)?.into_rgba8();
+        return Ok(vec![(still, Duration::MAX)]);
+    }
+    Ok(frames)
+}
+
+fn collect<'a>(decoder: impl AnimationDecoder<'a>) -> GameResult<Vec<(RgbaImage, Duration)>> {
+    decoder
+        .into_frames()
+        .map(|frame| {
+            let frame: Frame = frame?;
+            let delay = Duration::from(frame.delay());
+            let delay = if delay < MIN_FRAME_DELAY {
+                Duration::from_millis(100)
+            } else {
+                delay
+            };
+            Ok((frame.into_buffer(), delay))
+        })
+        .collect()
+}
+
+/// Lays the frames out in a roughly square grid no larger than `max` on either side,
+/// returning the grid image and its number of columns.
+fn pack(frames: &[(RgbaImage, Duration)], max: u32) -> Option<(RgbaImage, u32)> {
+    let (width, height) = frames[0].0.dimensions();
+    let count = frames.len() as u32;
+    let columns = ((count as f32).sqrt().ceil() as u32)
+        .min(max / width.max(1))
+        .max(1);
+    let rows = count.div_ceil(columns);
+    if width * columns > max || height * rows > max {
+        return None;
+    }
+
+    let mut sheet = RgbaImage::new(width * columns, height * rows);
+    for (i, (frame, _)) in frames.iter().enumerate() {
+        let (x, y) = (i as u32 % columns, i as u32 / columns);
+        ::image::imageops::replace(&mut sheet, frame, (x * width) as i64, (y * height) as i64);
+    }
+    Some((sheet, columns))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::{codecs::gif::GifEncoder, Delay, Rgba};
+
+    #[test]
+    fn gif_frames_and_delays() {
+        let mut encoded = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut encoded);
+            for (shade, ms) in [(0, 50), (128, 0), (255, 200)] {
+                let buffer = RgbaImage::from_pixel(3, 2, Rgba([shade, shade, shade, 255]));
+                encoder
+                    .encode_frame(Frame::from_parts(
+                        buffer,
+                        0,
+                        0,
+                        Delay::from_numer_denom_ms(ms, 1),
+                    ))
+                    .unwrap();
+            }
+        }
+
+        let frames = decode(&encoded).unwrap();
+        let delays: Vec<_> = frames.iter().map(|(_, d)| d.as_millis()).collect();
+        assert_eq!(delays, [50, 100, 200]);
+
+        let (sheet, columns) = pack(&frames, 64).unwrap();
+        assert_eq!((columns, sheet.dimensions()), (2, (6, 4)));
+        assert_eq!(sheet.get_pixel(0, 2)[0], 255);
+        assert_eq!(sheet.get_pixel(5, 3)[3], 0);
+
+        // one frame per row is all that fits into 5 pixels, and three rows don't
+        assert_eq!(pack(&frames, 5).map(|(_, c)| c), None);
+    }
+}
@@ -0,0 +1,444 @@
+use super::{context::GraphicsContext, image::Image, BlendMode, ImageData, ImageFormat, Rect};
+use crate::{context::Has, GameError, GameResult};
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+/// A named image packed into a [`TextureAtlas`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRegion {
+    /// Index of the page the image was packed into, see [`TextureAtlas::page`].
+    pub page: usize,
+    /// Source rectangle in UV coordinates, for [`DrawParam::src`](super::DrawParam::src).
+    pub uv: Rect,
+    /// Position and size of the image on its page, in pixels.
+    pub rect: Rect,
+}
+
+/// Collects images to pack into a [`TextureAtlas`].
+///
+/// ```rust,no_run
+/// # use ggez::graphics::{self, TextureAtlasBuilder, DrawParam};
+/// # fn t(ctx: &mut ggez::Context) -> ggez::GameResult {
+/// let atlas = TextureAtlasBuilder::new()
+///     .padding(1)
+///     .extrude(1)
+///     .add_path("player", "/player.png")?
+///     .add_path("enemy", "/enemy.png")?
+///     .build(ctx)?;
+///
+/// let player = atlas.region("player").unwrap();
+/// let mut canvas = graphics::Canvas::from_frame(ctx, None);
+/// canvas.draw(atlas.page(player.page), DrawParam::default().src(player.uv));
+/// canvas.finish(ctx)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextureAtlasBuilder {
+    settings: Settings,
+    images: Vec<(String, ImageData)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Settings {
+    page_width: u32,
+    page_height: u32,
+    padding: u32,
+    extrude: u32,
+}
+
+impl TextureAtlasBuilder {
+    /// Creates a builder for 2048x2048 pages, without padding or extrusion.
+    pub fn new() -> Self {
+        TextureAtlasBuilder {
+            settings: Settings {
+                page_width: 2048,
+                page_height: 2048,
+                padding: 0,
+                extrude: 0,
+            },
+            images: Vec::new(),
+        }
+    }
+
+    /// Sets the size of each page in pixels. No single image may be larger than a page.
+    pub fn page_size(&mut self, width: u32, height: u32) -> &mut Self {
+        self.settings.page_width = width;
+        self.settings.page_height = height;
+        self
+    }
+
+    /// Sets the number of transparent pixels left between packed images.
+    pub fn padding(&mut self, pixels: u32) -> &mut Self {
+        self.settings.padding = pixels;
+        self
+    }
+
+    /// Sets how many pixels each image's edges are repeated outwards by.
+    ///
+    /// With linear filtering, sampling near the edge of a region blends in the neighbouring pixels;
+    /// extruding the edges makes those the image's own border, instead of another image or padding.
+    pub fn extrude(&mut self, pixels: u32) -> &mut Self {
+        self.settings.extrude = pixels;
+        self
+    }
+
+    /// Adds an image from tightly packed RGBA8 pixels (sRGB encoded, like [`Image::from_pixels`] with
+    /// `Rgba8UnormSrgb`).
+    pub fn add_pixels(
+        &mut self,
+        name: impl Into<String>,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> GameResult<&mut Self> {
+        let data =
+            ImageData::from_pixels(width, height, ImageFormat::Rgba8UnormSrgb, pixels.to_vec())?;
+        Ok(self.add_image_data(name, data))
+    }
+
+    /// Adds an image prepared with [`ImageData`].
+    pub fn add_image_data(&mut self, name: impl Into<String>, data: ImageData) -> &mut Self {
+        self.images.push((name.into(), data));
+        self
+    }
+
+    /// Adds an image decoded from an encoded image (e.g. PNG or JPEG).
+    pub fn add_bytes(&mut self, name: impl Into<String>, encoded: &[u8]) -> GameResult<&mut Self> {
+        Ok(self.add_image_data(name, ImageData::from_bytes(encoded)?))
+    }
+
+    /// Adds an image loaded from a given path.
+    pub fn add_path(
+        &mut self,
+        name: impl Into<String>,
+        path: impl AsRef<Path>,
+    ) -> GameResult<&mut Self> {
+        self.add_bytes(name, &read_file(path)?)
+    }
+
+    /// Packs all added images and uploads the pages.
+    ///
+    /// Images are packed tallest first, which fits them more tightly than packing them in the order
+    /// they were added.
+    pub fn build(&self, gfx: &impl Has<GraphicsContext>) -> GameResult<TextureAtlas> {
+        let settings = self.settings;
+        let mut order: Vec<_> = self.images.iter().collect();
+        order.sort_by_key(|(_, data)| std::cmp::Reverse(data.height()));
+
+        let mut packers = Vec::new();
+        let mut pages: Vec<ImageData> = Vec::new();
+        let mut regions = HashMap::new();
+        for (name, data) in order {
+            let tile = extruded(data, settings.extrude);
+            let (page, x, y) = settings.place(&mut packers, &tile)?;
+            if page == pages.len() {
+                pages.push(ImageData::new(settings.page_width, settings.page_height));
+            }
+
+            pages[page].blit(&tile, x as i32, y as i32, BlendMode::REPLACE);
+            let _ = regions.insert(name.clone(), settings.region(page, x, y, data));
+        }
+
+        let pages = pages
+            .iter()
+            .map(|data| Image::from_image_data(gfx, data))
+            .collect();
+        Ok(TextureAtlas {
+            settings,
+            packers,
+            pages,
+            regions,
+        })
+    }
+}
+
+impl Default for TextureAtlasBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Settings {
+    /// Finds a place for `tile` on the first page with room, adding a page to `packers` if none has.
+    ///
+    /// Tiles take up their own size plus padding to the right and bottom, where padding
+    /// may be cut off at the page edges.
+    fn place(&self, packers: &mut Vec<Skyline>, tile: &ImageData) -> GameResult<(usize, u32, u32)> {
+        if tile.width() > self.page_width || tile.height() > self.page_height {
+            return Err(GameError::RenderError(format!(
+                "a {}x{} image doesn't fit into a {}x{} atlas page",
+                tile.width(),
+                tile.height(),
+                self.page_width,
+                self.page_height
+            )));
+        }
+        let w = (tile.width() + self.padding).min(self.page_width);
+        let h = (tile.height() + self.padding).min(self.page_height);
+
+        let placed = packers
+            .iter_mut()
+            .enumerate()
+            .find_map(|(i, packer)| Some((i, packer.insert(w, h)?)));
+        Ok(match placed {
+            Some((page, (x, y))) => (page, x, y),
+            None => {
+                let mut packer = Skyline::new(self.page_width, self.page_height);
+                let (x, y) = packer.insert(w, h).unwrap(/* the tile fits into an empty page */);
+                packers.push(packer);
+                (packers.len() - 1, x, y)
+            }
+        })
+    }
+
+    fn region(&self, page: usize, x: u32, y: u32, data: &ImageData) -> AtlasRegion {
+        let (x, y) = (x + self.extrude, y + self.extrude);
+        let (w, h) = (data.width(), data.height());
+        AtlasRegion {
+            page,
+            uv: Rect::new(
+                x as f32 / self.page_width as f32,
+                y as f32 / self.page_height as f32,
+                w as f32 / self.page_width as f32,
+                h as f32 / self.page_height as f32,
+            ),
+            rect: Rect::new(x as f32, y as f32, w as f32, h as f32),
+        }
+    }
+}
+
+/// Many images packed into a few larger pages, so that they can be drawn without switching images,
+/// e.g. in a single [`InstanceArray`](super::InstanceArray) per page.
+///
+/// Created with [`TextureAtlasBuilder`]. More images can be added later; they are packed into the
+/// free space of the existing pages, or into new pages if there is none.
+#[derive(Debug)]
+pub struct TextureAtlas {
+    settings: Settings,
+    packers: Vec<Skyline>,
+    pages: Vec<Image>,
+    regions: HashMap<String, AtlasRegion>,
+}
+
+impl TextureAtlas {
+    /// Returns the region of the image added with the given name.
+    pub fn region(&self, name: &str) -> Option<AtlasRegion> {
+        self.regions.get(name).copied()
+    }
+
+    /// Iterates over the names and regions of all packed images.
+    pub fn regions(&self) -> impl Iterator<Item = (&str, AtlasRegion)> {
+        self.regions
+            .iter()
+            .map(|(name, region)| (name.as_str(), *region))
+    }
+
+    /// Returns the image of the page with the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such page.
+    pub fn page(&self, index: usize) -> &Image {
+        &self.pages[index]
+    }
+
+    /// Returns the images of all pages.
+    pub fn pages(&self) -> &[Image] {
+        &self.pages
+    }
+
+    /// Packs another image into the atlas and uploads it. An image previously added under
+    /// the same name is no longer found by name, but keeps taking up space.
+    pub fn add_image_data(
+        &mut self,
+        gfx: &impl Has<GraphicsContext>,
+        name: impl Into<String>,
+        data: &ImageData,
+    ) -> GameResult<AtlasRegion> {
+        let tile = extruded(data, self.settings.extrude);
+        let (page, x, y) = self.settings.place(&mut self.packers, &tile)?;
+        if page == self.pages.len() {
+            let (width, height) = (self.settings.page_width, self.settings.page_height);
+            self.pages
+                .push(Image::from_image_data(gfx, &ImageData::new(width, height)));
+        }
+
+        self.pages[page].write_pixels(
+            gfx,
+            Rect::new(
+                x as f32,
+                y as f32,
+                tile.width() as f32,
+                tile.height() as f32,
+            ),
+            tile.pixels(),
+        )?;
+
+        let region = self.settings.region(page, x, y, data);
+        let _ = self.regions.insert(name.into(), region);
+        Ok(region)
+    }
+
+    /// Packs another image from tightly packed RGBA8 pixels, see [`TextureAtlas::add_image_data`].
+    pub fn add_pixels(
+        &mut self,
+        gfx: &impl Has<GraphicsContext>,
+        name: impl Into<String>,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> GameResult<AtlasRegion> {
+        let data =
+            ImageData::from_pixels(width, height, ImageFormat::Rgba8UnormSrgb, pixels.to_vec())?;
+        self.add_image_data(gfx, name, &data)
+    }
+
+    /// Packs another image loaded from a given path, see [`TextureAtlas::add_image_data`].
+    pub fn add_path(
+        &mut self,
+        gfx: &impl Has<GraphicsContext>,
+        name: impl Into<String>,
+        path: impl AsRef<Path>,
+    ) -> GameResult<AtlasRegion> {
+        let data = ImageData::from_bytes(&read_file(path)?)?;
+        self.add_image_data(gfx, name, &data)
+    }
+}
+
+fn read_file(path: impl AsRef<Path>) -> GameResult<Vec<u8>> {
+    let mut encoded = Vec::new();
+    let _ = File::open(path)?.read_to_end(&mut encoded)?;
+    Ok(encoded)
+}
+
+/// Returns an `Rgba8UnormSrgb` copy of `data` with its outermost pixels repeated `extrude` times.
+fn extruded(data: &ImageData, extrude: u32) -> ImageData {
+    let src = ImageData::from(data.to_rgba_image());
+    if extrude == 0 {
+        return src;
+    }
+
+    let (w, h) = (src.width(), src.height());
+    let mut tile = ImageData::new(w + 2 * extrude, h + 2 * extrude);
+    let src_row = w as usize * 4;
+    let dst_row = tile.width() as usize * 4;
+    let pixels = tile.pixels_mut();
+    for y in 0..h + 2 * extrude {
+        let sy = y.saturating_sub(extrude).min(h.saturating_sub(1)) as usize;
+        for x in 0..w + 2 * extrude {
+            let sx = x.saturating_sub(extrude).min(w.saturating_sub(1)) as usize;
+            let s = sy * src_row + sx * 4;
+            let d = y as usize * dst_row + x as usize * 4;
+            pixels[d..d + 4].copy_from_slice(&src.pixels()[s..s + 4]);
+        }
+    }
+    tile
+}
+
+/// Skyline bottom-left rectangle packer: tracks the height of the packed area along the page's width
+/// and places each rectangle where its top ends up lowest.
+#[derive(Debug, Clone)]
+struct Skyline {
+    width: u32,
+    height: u32,
+    // (x, y, width) of each horizontal segment, left to right
+    segments: Vec<(u32, u32, u32)>,
+}
+
+impl Skyline {
+    fn new(width: u32, height: u32) -> Self {
+        Skyline {
+            width,
+            height,
+            segments: vec![(0, 0, width)],
+        }
+    }
+
+    fn insert(&mut self, w: u32, h: u32) -> Option<(u32, u32)> {
+        let (index, x, y) = (0..self.segments.len())
+            .filter_map(|i| Some((i, self.segments[i].0, self.fit(i, w, h)?)))
+            .min_by_key(|&(_, x, y)| (y + h, x))?;
+
+        // raise the skyline under the new rectangle, cutting off the segments it covers
+        self.segments.insert(index, (x, y + h, w));
+        let end = x + w;
+        let i = index + 1;
+        while i < self.segments.len() {
+            let (sx, sy, sw) = self.segments[i];
+            if sx >= end {
+                break;
+            }
+            if sx + sw <= end {
+                let _ = self.segments.remove(i);
+            } else {
+                self.segments[i] = (end, sy, sx + sw - end);
+                break;
+            }
+        }
+
+        // merge neighbouring segments of the same height
+        self.segments.dedup_by(|next, prev| {
+            let merge = prev.1 == next.1;
+            if merge {
+                prev.2 += next.2;
+            }
+            merge
+        });
+
+        Some((x, y))
+    }
+
+    // returns the y at which a `w` x `h` rectangle fits with its left edge at segment `index`
+    fn fit(&self, index: usize, w: u32, h: u32) -> Option<u32> {
+        let x = self.segments[index].0;
+        if x + w > self.width {
+            return None;
+        }
+
+        let mut y = 0;
+        let mut covered = 0;
+        for &(_, sy, sw) in &self.segments[index..] {
+            y = y.max(sy);
+            if y + h > self.height {
+                return None;
+            }
+            covered += sw;
+            if covered >= w {
+                return Some(y);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Color;
+
+    #[test]
+    fn skyline_packs_without_overlap() {
+        let mut packer = Skyline::new(64, 64);
+        let mut placed = Vec::new();
+        for (w, h) in [(32, 32), (16, 40), (16, 8), (64, 16), (8, 8), (30, 8)] {
+            let (x, y) = packer.insert(w, h).unwrap();
+            assert!(x + w <= 64 && y + h <= 64);
+            for &(px, py, pw, ph) in &placed {
+                assert!(x >= px + pw || px >= x + w || y >= py + ph || py >= y + h);
+            }
+            placed.push((x, y, w, h));
+        }
+        assert!(packer.insert(64, 64).is_none());
+    }
+
+    #[test]
+    fn extrusion_repeats_edges() {
+        let mut data = ImageData::new(2, 1);
+        data.set_pixel(0, 0, Color::RED);
+        let tile = extruded(&data, 1);
+        assert_eq!((tile.width(), tile.height()), (4, 3));
+        assert_eq!(tile.get_pixel(0, 0), Some(Color::RED));
+        assert_eq!(tile.get_pixel(1, 2), Some(Color::RED));
+        assert_eq!(tile.get_pixel(3, 1), data.get_pixel(1, 0));
+    }
+}
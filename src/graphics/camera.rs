@@ -0,0 +1,340 @@
+use super::{internal_canvas::screen_to_mat, Canvas, Rect};
+use crate::{timer::TimeContext, GameResult};
+use glam::{Mat4, Vec2, Vec3, Vec4Swizzles};
+use std::time::Duration;
+
+/// A 2D camera looking at the world through a viewport of a canvas.
+///
+/// The camera's position is the point of the world shown in the middle of the viewport.
+/// Zooming in makes the world appear larger, and rotating the camera turns the world the
+/// other way on screen. [`Camera2D::apply`] sets up a canvas to draw in world coordinates,
+/// and [`Camera2D::screen_to_world`] maps pixels back to the world, e.g. for mouse picking.
+///
+/// Besides being moved directly, the camera can follow a target with smoothing, be kept
+/// inside the bounds of a level, and shake based on "trauma": adding trauma shakes the view,
+/// and the trauma wears off over time.
+///
+/// ```rust,no_run
+/// # use ggez::graphics::{self, Camera2D, Rect};
+/// # fn t(ctx: &mut ggez::Context, player: ggez::glam::Vec2) -> ggez::GameResult {
+/// let (w, h) = ctx.gfx.drawable_size();
+/// let mut camera = Camera2D::new(Rect::new(0., 0., w, h));
+/// camera.set_follow_speed(5.);
+/// camera.set_bounds(Some(Rect::new(0., 0., 4096., 1024.)));
+///
+/// // once per frame
+/// camera.follow(player);
+/// camera.update(&ctx.time);
+/// let cursor = camera.screen_to_world(ctx.mouse.position());
+///
+/// let mut canvas = graphics::Canvas::from_frame(ctx, None);
+/// camera.apply(&mut canvas)?;
+/// // ... draw the world ...
+/// canvas.finish(ctx)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera2D {
+    position: Vec2,
+    zoom: f32,
+    rotation: f32,
+    viewport: Rect,
+    target: Option<Vec2>,
+    follow_speed: f32,
+    bounds: Option<Rect>,
+    trauma: f32,
+    trauma_decay: f32,
+    max_shake_offset: f32,
+    max_shake_angle: f32,
+    time: f32,
+}
+
+impl Camera2D {
+    /// Creates a camera drawing into `viewport`, in pixels of the canvas, and looking at
+    /// the world origin.
+    pub fn new(viewport: Rect) -> Self {
+        Camera2D {
+            position: Vec2::ZERO,
+            zoom: 1.,
+            rotation: 0.,
+            viewport,
+            target: None,
+            follow_speed: f32::INFINITY,
+            bounds: None,
+            trauma: 0.,
+            trauma_decay: 1.,
+            max_shake_offset: 16.,
+            max_shake_angle: 0.1,
+            time: 0.,
+        }
+    }
+
+    /// Returns the point of the world in the middle of the viewport.
+    pub fn position(&self) -> mint::Point2<f32> {
+        self.position.into()
+    }
+
+    /// Moves the camera to look at `position`, within the bounds if there are any.
+    pub fn set_position(&mut self, position: impl Into<mint::Point2<f32>>) {
+        let position: mint::Point2<f32> = position.into();
+        self.position = Vec2::new(position.x, position.y);
+        self.clamp();
+    }
+
+    /// Returns the zoom factor.
+    #[inline]
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Sets the zoom factor; `2.0` shows the world at twice its size.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.max(f32::EPSILON);
+        self.clamp();
+    }
+
+    /// Returns the rotation of the camera, in radians.
+    #[inline]
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    /// Sets the rotation of the camera, in radians.
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+        self.clamp();
+    }
+
+    /// Returns the viewport, in pixels of the canvas.
+    #[inline]
+    pub fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
+    /// Changes the part of the canvas the camera draws into, e.g. when the window is resized
+    /// or for split screen.
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+        self.clamp();
+    }
+
+    /// Makes the camera move towards `target` on every update.
+    pub fn follow(&mut self, target: impl Into<mint::Point2<f32>>) {
+        let target: mint::Point2<f32> = target.into();
+        self.target = Some(Vec2::new(target.x, target.y));
+    }
+
+    /// Stops following the target.
+    pub fn unfollow(&mut self) {
+        self.target = None;
+    }
+
+    /// Sets how quickly the camera catches up with its target. At a speed of `n`, about
+    /// `1 - e^-n` of the remaining distance is covered each second; infinity (the default)
+    /// keeps the target centered at all times.
+    pub fn set_follow_speed(&mut self, speed: f32) {
+        self.follow_speed = speed.max(0.);
+    }
+
+    /// Keeps the view inside `bounds`, in world coordinates. If the view is larger than the
+    /// bounds, it's centered on them.
+    pub fn set_bounds(&mut self, bounds: Option<Rect>) {
+        self.bounds = bounds;
+        self.clamp();
+    }
+
+    /// Adds trauma between `0.0` and `1.0`, shaking the view. The shake grows with the
+    /// square of the trauma, so small hits barely shake while big ones do.
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0., 1.);
+    }
+
+    /// Returns the current trauma.
+    #[inline]
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// Sets how much trauma wears off per second. Defaults to `1.0`.
+    pub fn set_trauma_decay(&mut self, per_second: f32) {
+        self.trauma_decay = per_second.max(0.);
+    }
+
+    /// Sets how far the view moves, in pixels, and turns, in radians, at full trauma.
+    /// Defaults to 16 pixels and 0.1 radians.
+    pub fn set_shake(&mut self, max_offset: f32, max_angle: f32) {
+        self.max_shake_offset = max_offset;
+        self.max_shake_angle = max_angle;
+    }
+
+    /// Advances following and shaking by the duration of the last frame.
+    pub fn update(&mut self, time: &TimeContext) {
+        self.advance(time.delta());
+    }
+
+    /// Advances following and shaking by `dt`.
+    pub fn advance(&mut self, dt: Duration) {
+        let dt = dt.as_secs_f32();
+        self.time += dt;
+        self.trauma = (self.trauma - self.trauma_decay * dt).max(0.);
+
+        if let Some(target) = self.target {
+            // framerate independent exponential smoothing
+            let t = 1. - (-self.follow_speed * dt).exp();
+            self.position += (target - self.position) * if t.is_nan() { 1. } else { t };
+            self.clamp();
+        }
+    }
+
+    fn clamp(&mut self) {
+        let Some(bounds) = self.bounds else {
+            return;
+        };
+        let view = self.visible_rect();
+        let axis = |position: f32, half: f32, min: f32, max: f32| {
+            if max - min <= 2. * half {
+                (min + max) / 2.
+            } else {
+                position.clamp(min + half, max - half)
+            }
+        };
+        self.position = Vec2::new(
+            axis(self.position.x, view.w / 2., bounds.left(), bounds.right()),
+            axis(self.position.y, view.h / 2., bounds.top(), bounds.bottom()),
+        );
+    }
+
+    /// Returns the current shake offset and angle, which only affect drawing.
+    fn shake(&self) -> (Vec2, f32) {
+        let shake = self.trauma * self.trauma;
+        if shake == 0. {
+            return (Vec2::ZERO, 0.);
+        }
+        // smooth noise from sines with unrelated frequencies, a different phase per channel
+        let noise = |phase: f32| {
+            let t = self.time * 25. + phase;
+            ((t * 1.13).sin() + (t * 2.37 + 1.7).sin() * 0.5 + (t * 4.71 + 4.2).sin() * 0.25) / 1.75
+        };
+        (
+            Vec2::new(noise(0.), noise(10.)) * self.max_shake_offset * shake,
+            noise(20.) * self.max_shake_angle * shake,
+        )
+    }
+
+    /// Returns the matrix taking world coordinates to pixels of the canvas.
+    pub fn view_matrix(&self) -> mint::ColumnMatrix4<f32> {
+        self.view().into()
+    }
+
+    fn view(&self) -> Mat4 {
+        let (offset, angle) = self.shake();
+        let center = Vec2::new(
+            self.viewport.x + self.viewport.w / 2.,
+            self.viewport.y + self.viewport.h / 2.,
+        );
+        Mat4::from_translation((center + offset).extend(0.))
+            * Mat4::from_scale(Vec3::new(self.zoom, self.zoom, 1.))
+            * Mat4::from_rotation_z(-(self.rotation + angle))
+            * Mat4::from_translation((-self.position).extend(0.))
+    }
+
+    /// Returns the projection [`Camera2D::apply`] sets on a canvas of the given size.
+    pub fn projection(&self, canvas_width: f32, canvas_height: f32) -> mint::ColumnMatrix4<f32> {
+        (screen_to_mat(Rect::new(0., 0., canvas_width, canvas_height)) * self.view()).into()
+    }
+
+    /// Sets the canvas' projection to draw in world coordinates, and limits drawing to the
+    /// viewport if it doesn't cover the whole canvas.
+    pub fn apply(&self, canvas: &mut Canvas) -> GameResult {
+        let (w, h) = canvas.target_size();
+        let (w, h) = (w as f32, h as f32);
+        canvas.set_projection(self.projection(w, h));
+        if self.viewport == Rect::new(0., 0., w, h) {
+            canvas.set_default_scissor_rect();
+            Ok(())
+        } else {
+            canvas.set_scissor_rect(self.viewport)
+        }
+    }
+
+    /// Converts a point in the world to pixels of the canvas.
+    pub fn world_to_screen(&self, point: impl Into<mint::Point2<f32>>) -> mint::Point2<f32> {
+        let point: mint::Point2<f32> = point.into();
+        (self.view() * glam::vec4(point.x, point.y, 0., 1.))
+            .xy()
+            .into()
+    }
+
+    /// Converts pixels of the canvas to a point in the world.
+    pub fn screen_to_world(&self, point: impl Into<mint::Point2<f32>>) -> mint::Point2<f32> {
+        let point: mint::Point2<f32> = point.into();
+        (self.view().inverse() * glam::vec4(point.x, point.y, 0., 1.))
+            .xy()
+            .into()
+    }
+
+    /// Returns the part of the world that's visible, as an axis aligned rectangle that
+    /// contains the whole viewport even when the camera is rotated. Shake isn't included.
+    pub fn visible_rect(&self) -> Rect {
+        let half = Vec2::new(self.viewport.w, self.viewport.h) / (2. * self.zoom);
+        let (sin, cos) = self.rotation.sin_cos();
+        let extent = Vec2::new(
+            half.x * cos.abs() + half.y * sin.abs(),
+            half.x * sin.abs() + half.y * cos.abs(),
+        );
+        Rect::new(
+            self.position.x - extent.x,
+            self.position.y - extent.y,
+            extent.x * 2.,
+            extent.y * 2.,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: mint::Point2<f32>, b: [f32; 2]) -> bool {
+        (a.x - b[0]).abs() < 1e-3 && (a.y - b[1]).abs() < 1e-3
+    }
+
+    #[test]
+    fn conversions_match_the_projection() {
+        let mut camera = Camera2D::new(Rect::new(100., 0., 200., 100.));
+        camera.set_position([50., 50.]);
+        camera.set_zoom(2.);
+        camera.set_rotation(std::f32::consts::FRAC_PI_2);
+
+        // the camera's position is drawn in the middle of the viewport
+        assert!(close(camera.world_to_screen([50., 50.]), [200., 50.]));
+        // turning the camera clockwise turns the world counterclockwise
+        assert!(close(camera.world_to_screen([60., 50.]), [200., 30.]));
+        let world = camera.screen_to_world([123., 45.]);
+        assert!(close(camera.world_to_screen(world), [123., 45.]));
+
+        // the canvas maps normalized device coordinates back to pixels
+        let ndc = Mat4::from(camera.projection(400., 100.)) * glam::vec4(60., 50., 0., 1.);
+        let pixels = [(ndc.x + 1.) / 2. * 400., (1. - ndc.y) / 2. * 100.];
+        assert!(close(camera.world_to_screen([60., 50.]), pixels));
+    }
+
+    #[test]
+    fn following_and_bounds() {
+        let mut camera = Camera2D::new(Rect::new(0., 0., 100., 100.));
+        camera.follow([1000., 40.]);
+        camera.advance(Duration::from_millis(16));
+        assert!(close(camera.position(), [1000., 40.]));
+
+        camera.set_bounds(Some(Rect::new(0., 0., 500., 80.)));
+        // too narrow to scroll vertically, so it's centered
+        assert!(close(camera.position(), [450., 40.]));
+
+        camera.set_follow_speed(1.);
+        camera.follow([0., 0.]);
+        camera.advance(Duration::from_secs(1));
+        let expected = 450. * (-1f32).exp();
+        assert!(close(camera.position(), [expected, 40.]));
+    }
+}
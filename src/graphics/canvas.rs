@@ -6,12 +6,15 @@ use crate::{
 };
 
 use super::{
+    culling::Viewport,
+    draw::DrawUniforms,
     gpu::arc::{ArcBindGroup, ArcBindGroupLayout},
-    internal_canvas::{screen_to_mat, InstanceArrayView, InternalCanvas},
-    BlendMode, Color, DrawParam, Drawable, GraphicsContext, Image, InstanceArray, Mesh, Rect,
-    Sampler, ScreenImage, Shader, ShaderParams, Text, WgpuContext, ZIndex,
+    internal_canvas::{screen_to_mat, InstanceArrayView, InternalCanvas, BATCH_CAPACITY},
+    BlendMode, Color, DepthMode, DrawParam, Drawable, GraphicsContext, Image, InstanceArray, Mesh,
+    Mesh3d, Rect, Sampler, ScreenImage, Shader, ShaderParams, StencilMode, Text, Transform,
+    WgpuContext, ZIndex,
 };
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, ops::Range, sync::Arc};
 
 /// Canvases are the main method of drawing meshes and text to images in ggez.
 ///
@@ -19,7 +22,11 @@ use std::{collections::BTreeMap, sync::Arc};
 /// or they can draw directly to the screen.
 ///
 /// Canvases are also where you can bind your own custom shaders and samplers to use while drawing.
-/// Canvases *do not* automatically batch draws. To used batched (instanced) drawing, refer to [`InstanceArray`].
+/// Consecutive draws of the same mesh and image with the same canvas state (e.g. a run of sprites
+/// from one texture) are automatically batched into a single instanced draw call when the canvas
+/// is finished, keeping their order. Draws with a custom vertex shader are never batched, since
+/// batches are drawn with the instancing vertex shader. For more control over instanced drawing,
+/// refer to [`InstanceArray`].
 // note:
 //   Canvas does not draw anything itself. It is merely a state-tracking and draw-reordering wrapper around InternalCanvas, which does the actual
 // drawing.
@@ -35,6 +42,10 @@ pub struct Canvas {
     target: Image,
     resolve: Option<Image>,
     clear: Option<Color>,
+    // whether the draws are recorded into a draw list rather than drawn
+    recording: bool,
+    // instance array uploads, added to the frame stats when finished
+    bytes_written: u64,
 }
 
 impl Canvas {
@@ -108,6 +119,15 @@ impl Canvas {
         Canvas::new(gfx, target, resolve, clear.into())
     }
 
+    /// Creates a canvas that is never finished, only used to record draws into a
+    /// [`DrawList`](super::DrawList).
+    pub(crate) fn for_recording(gfx: &impl Has<GraphicsContext>) -> Self {
+        let gfx = gfx.retrieve();
+        let mut canvas = Canvas::new(gfx, gfx.white_image.clone(), None, None);
+        canvas.recording = true;
+        canvas
+    }
+
     fn new(
         gfx: &impl Has<GraphicsContext>,
         target: Image,
@@ -126,6 +146,9 @@ impl Canvas {
             sampler: Sampler::default(),
             blend_mode: BlendMode::ALPHA,
             premul_text: true,
+            depth: DepthMode::DISABLED,
+            stencil: StencilMode::DISABLED,
+            culling: false,
             projection: glam::Mat4::IDENTITY.into(),
             scissor_rect: (0, 0, target.width(), target.height()),
         };
@@ -148,6 +171,8 @@ impl Canvas {
             target,
             resolve,
             clear,
+            recording: false,
+            bytes_written: 0,
         };
 
         this.set_screen_coordinates(screen);
@@ -178,6 +203,14 @@ impl Canvas {
         ));
     }
 
+    /// Sets type-erased shader parameters, or resets them with `None`.
+    pub(crate) fn set_raw_shader_params(
+        &mut self,
+        params: Option<(ArcBindGroup, ArcBindGroupLayout, u32)>,
+    ) {
+        self.state.params = params;
+    }
+
     /// Sets the shader to use when drawing text.
     #[inline]
     pub fn set_text_shader(&mut self, shader: Shader) {
@@ -259,6 +292,53 @@ impl Canvas {
         self.state.premul_text = premultiplied_text;
     }
 
+    /// Sets how the following draws test and write the depth buffer.
+    ///
+    /// See [`DepthMode`] for how depth is compared.
+    #[inline]
+    pub fn set_depth_mode(&mut self, mode: DepthMode) {
+        self.state.depth = mode;
+    }
+
+    /// Returns the current depth mode.
+    #[inline]
+    pub fn depth_mode(&self) -> DepthMode {
+        self.state.depth
+    }
+
+    /// Sets how the following draws test and change the stencil buffer.
+    ///
+    /// See [`StencilMode`] for how masks work.
+    #[inline]
+    pub fn set_stencil_mode(&mut self, mode: StencilMode) {
+        self.state.stencil = mode;
+    }
+
+    /// Returns the current stencil mode.
+    #[inline]
+    pub fn stencil_mode(&self) -> StencilMode {
+        self.state.stencil
+    }
+
+    /// Sets whether the following draws are skipped when they can't be seen.
+    ///
+    /// With culling, [`Canvas::finish`] leaves out draws whose bounds (see
+    /// [`Drawable::dimensions`]), moved by their `DrawParam` and the projection, lie entirely
+    /// outside the image or scissor rect. The instances of an [`InstanceArray`] are culled one by
+    /// one, and only the visible ones are drawn. Anything the bounds don't account for, like
+    /// custom vertex shaders moving vertices around, can be wrongly culled. This is `false` by
+    /// default.
+    #[inline]
+    pub fn set_culling(&mut self, culling: bool) {
+        self.state.culling = culling;
+    }
+
+    /// Returns whether culling is enabled for the following draws.
+    #[inline]
+    pub fn culling(&self) -> bool {
+        self.state.culling
+    }
+
     /// Sets the raw projection matrix to the given homogeneous
     /// transformation matrix.  For an introduction to graphics matrices,
     /// a good source is this: <http://ncase.me/matrix/>
@@ -359,6 +439,27 @@ impl Canvas {
         drawable.draw(self, param)
     }
 
+    /// Draws the given `Drawable` as a stencil mask with [`StencilMode::mask`]`(level)`, without
+    /// changing the current stencil mode.
+    ///
+    /// ```rust,no_run
+    /// # use ggez::graphics::{self, Color, DrawMode, Mesh, StencilMode};
+    /// # fn t(ctx: &mut ggez::Context, map: &graphics::Image) -> ggez::GameResult {
+    /// let circle = Mesh::new_circle(ctx, DrawMode::fill(), [100., 100.], 80., 0.5, Color::WHITE)?;
+    ///
+    /// let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
+    /// canvas.draw_mask(&circle, graphics::DrawParam::new(), 0);
+    /// canvas.set_stencil_mode(StencilMode::inside(1));
+    /// canvas.draw(map, [20., 20.]);
+    /// canvas.finish(ctx)
+    /// # }
+    /// ```
+    pub fn draw_mask(&mut self, drawable: &impl Drawable, param: impl Into<DrawParam>, level: u32) {
+        let mode = std::mem::replace(&mut self.state.stencil, StencilMode::mask(level));
+        drawable.draw(self, param);
+        self.state.stencil = mode;
+    }
+
     /// Draws a `Mesh` textured with an `Image`.
     ///
     /// This differs from `canvas.draw(mesh, param)` as in that case, the mesh is untextured.
@@ -383,15 +484,7 @@ impl Canvas {
         instances: &InstanceArray,
         param: impl Into<DrawParam>,
     ) {
-        instances.flush_wgpu(&self.wgpu).unwrap(); // Will only fail if you can't lock the buffers shouldn't happen
-        self.push_draw(
-            Draw::MeshInstances {
-                mesh,
-                instances: InstanceArrayView::from_instances(instances).unwrap(),
-                scale: false,
-            },
-            param.into(),
-        );
+        self.push_instances(mesh, instances, param.into(), false);
     }
 
     /// Finish drawing with this canvas and submit all the draw calls.
@@ -401,25 +494,173 @@ impl Canvas {
         self.finalize(gfx)
     }
 
+    /// Returns the size of the image being drawn to.
+    pub(crate) fn target_size(&self) -> (u32, u32) {
+        (self.target.width(), self.target.height())
+    }
+
     #[inline]
     pub(crate) fn default_resources(&self) -> &DefaultResources {
         &self.defaults
     }
 
+    pub(crate) fn push_instances(
+        &mut self,
+        mesh: Mesh,
+        instances: &InstanceArray,
+        param: DrawParam,
+        scale: bool,
+    ) {
+        // Will only fail if you can't lock the buffers shouldn't happen
+        self.bytes_written += instances.flush_wgpu(&self.wgpu).unwrap();
+        let mut view = InstanceArrayView::from_instances(instances).unwrap();
+        if self.state.culling {
+            // culled now rather than in `finish`, as the array can change before then
+            let viewport = self.viewport(&self.state);
+            view.visible = Some(instances.visible(&viewport, mesh.bounds, &param, scale));
+        }
+        if self.recording {
+            // the instances are culled when the draw list is drawn, by which time the array
+            // may have changed
+            view.recorded = Some(Arc::new(instances.record()));
+        }
+        self.push_draw(
+            Draw::MeshInstances {
+                mesh,
+                instances: view,
+                scale,
+            },
+            param,
+        );
+    }
+
+    pub(crate) fn viewport(&self, state: &DrawState) -> Viewport {
+        Viewport::new(state.projection, state.scissor_rect, self.target_size())
+    }
+
+    /// Whether a command with culling enabled may cover any pixel.
+    fn is_visible(&self, gfx: &GraphicsContext, draw: &DrawCommand) -> bool {
+        let viewport = self.viewport(&draw.state);
+        match &draw.draw {
+            Draw::Mesh { mesh, image, scale } => {
+                let image_scale =
+                    scale.then(|| glam::vec2(image.width() as f32, image.height() as f32).into());
+                let transform = DrawUniforms::from_param(&draw.param, image_scale).transform;
+                viewport.overlaps(transform.into(), mesh.bounds)
+            }
+            // without 2D bounds, 3D meshes are left to the GPU's clipping
+            Draw::Mesh3d { .. } => true,
+            // the instances were culled one by one when drawn
+            Draw::MeshInstances { instances, .. } => instances
+                .visible
+                .as_ref()
+                .is_none_or(|visible| !visible.is_empty()),
+            Draw::BoundedText { text } => match text.bounds_raw(&gfx.text, &gfx.fonts) {
+                Ok(Some(bounds)) => {
+                    // the offset is resolved against the measured size, as when drawing
+                    let mut param = draw.param;
+                    if let Transform::Values { dest, offset, .. } = &mut param.transform {
+                        dest.x -= offset.x * bounds.w;
+                        dest.y -= offset.y * bounds.h;
+                        *offset = mint::Point2 { x: 0., y: 0. };
+                    }
+                    let transform = param.transform.to_matrix_at_depth(param.depth);
+                    viewport.overlaps(transform.into(), bounds)
+                }
+                Ok(None) => false,
+                // left to fail when drawn
+                Err(_) => true,
+            },
+        }
+    }
+
     #[inline]
     pub(crate) fn push_draw(&mut self, draw: Draw, param: DrawParam) {
-        self.draws.entry(param.z).or_default().push(DrawCommand {
+        self.push_command(DrawCommand {
             state: self.state.clone(),
             draw,
             param,
         });
     }
 
+    #[inline]
+    pub(crate) fn push_command(&mut self, command: DrawCommand) {
+        self.draws.entry(command.param.z).or_default().push(command);
+    }
+
+    /// Removes the queued draw commands, in drawing order.
+    pub(crate) fn take_commands(&mut self) -> impl Iterator<Item = DrawCommand> {
+        std::mem::take(&mut self.draws).into_values().flatten()
+    }
+
+    #[inline]
+    pub(crate) fn state(&self) -> &DrawState {
+        &self.state
+    }
+
+    /// Clamps a scissor rect to the image like [`Canvas::set_scissor_rect`], returning `None`
+    /// where that would fail.
+    pub(crate) fn clamp_scissor_rect(&self, rect: Rect) -> Option<(u32, u32, u32, u32)> {
+        let (width, height) = self.target_size();
+        let (x, y) = (rect.x as u32, rect.y as u32);
+        if rect.w as u32 == 0 || rect.h as u32 == 0 || x >= width || y >= height {
+            return None;
+        }
+        Some((
+            x,
+            y,
+            u32::min(width - x, rect.w as u32),
+            u32::min(height - y, rect.h as u32),
+        ))
+    }
+
     fn finalize(&mut self, gfx: &mut GraphicsContext) -> GameResult {
+        trace_scope!(
+            "Canvas::finish",
+            width = self.target.width(),
+            height = self.target.height(),
+            commands = self.draws.values().map(Vec::len).sum::<usize>()
+        );
+
+        // the depth-stencil buffer is only attached when something uses it
+        let depth_stencil = self
+            .draws
+            .values()
+            .flatten()
+            .any(|draw| {
+                draw.state.depth != DepthMode::DISABLED
+                    || draw.state.stencil != StencilMode::DISABLED
+            })
+            .then(|| {
+                gfx.depth_stencil_view(
+                    self.target.width(),
+                    self.target.height(),
+                    self.target.samples(),
+                )
+            });
+        let depth_stencil = depth_stencil.as_ref().map(|view| view.as_ref());
+
+        let mut commands = Vec::new();
+        for draw in self.draws.values().flatten() {
+            let visible = !draw.state.culling || self.is_visible(gfx, draw);
+            match &draw.draw {
+                Draw::MeshInstances { instances, .. } => {
+                    if let Some(visible) = &instances.visible {
+                        gfx.stats.culled += instances.len as usize - visible.len();
+                    }
+                }
+                _ if !visible => gfx.stats.culled += 1,
+                _ => {}
+            }
+            if visible {
+                commands.push(draw);
+            }
+        }
+
         let mut canvas = if let Some(resolve) = &self.resolve {
-            InternalCanvas::from_msaa(gfx, self.clear, &self.target, resolve)?
+            InternalCanvas::from_msaa(gfx, self.clear, &self.target, resolve, depth_stencil)?
         } else {
-            InternalCanvas::from_image(gfx, self.clear, &self.target)?
+            InternalCanvas::from_image(gfx, self.clear, &self.target, depth_stencil)?
         };
 
         let mut state = self.state.clone();
@@ -437,80 +678,102 @@ impl Canvas {
 
         canvas.set_sampler(state.sampler);
         canvas.set_blend_mode(state.blend_mode);
+        canvas.set_depth_mode(state.depth);
+        canvas.set_stencil_mode(state.stencil);
         canvas.set_projection(state.projection);
 
         if state.scissor_rect.2 > 0 && state.scissor_rect.3 > 0 {
             canvas.set_scissor_rect(state.scissor_rect);
         }
 
-        for draws in self.draws.values() {
-            for draw in draws {
-                // track state and apply to InternalCanvas if changed
+        // consecutive draws that only differ by their params are merged into batches
+        let runs = batch_runs(commands.iter().map(|draw| draw.batch_key()), BATCH_CAPACITY);
+        for run in runs {
+            let batched = &commands[run];
+            let draw = batched[0];
 
-                if draw.state.shader != state.shader {
-                    canvas.set_shader(draw.state.shader.clone());
-                }
+            // track state and apply to InternalCanvas if changed
 
-                if draw.state.params != state.params {
-                    if let Some((bind_group, layout, offset)) = &draw.state.params {
-                        canvas.set_shader_params(bind_group.clone(), layout.clone(), *offset);
-                    }
-                }
+            if draw.state.shader != state.shader {
+                canvas.set_shader(draw.state.shader.clone());
+            }
 
-                if draw.state.text_shader != state.text_shader {
-                    canvas.set_text_shader(draw.state.text_shader.clone());
+            if draw.state.params != state.params {
+                if let Some((bind_group, layout, offset)) = &draw.state.params {
+                    canvas.set_shader_params(bind_group.clone(), layout.clone(), *offset);
                 }
+            }
 
-                if draw.state.text_params != state.text_params {
-                    if let Some((bind_group, layout, offset)) = &draw.state.text_params {
-                        canvas.set_text_shader_params(bind_group.clone(), layout.clone(), *offset);
-                    }
-                }
+            if draw.state.text_shader != state.text_shader {
+                canvas.set_text_shader(draw.state.text_shader.clone());
+            }
 
-                if draw.state.sampler != state.sampler {
-                    canvas.set_sampler(draw.state.sampler);
+            if draw.state.text_params != state.text_params {
+                if let Some((bind_group, layout, offset)) = &draw.state.text_params {
+                    canvas.set_text_shader_params(bind_group.clone(), layout.clone(), *offset);
                 }
+            }
 
-                if draw.state.blend_mode != state.blend_mode {
-                    canvas.set_blend_mode(draw.state.blend_mode);
-                }
+            if draw.state.sampler != state.sampler {
+                canvas.set_sampler(draw.state.sampler);
+            }
 
-                if draw.state.premul_text != state.premul_text {
-                    canvas.set_premultiplied_text(draw.state.premul_text);
-                }
+            if draw.state.blend_mode != state.blend_mode {
+                canvas.set_blend_mode(draw.state.blend_mode);
+            }
 
-                if draw.state.projection != state.projection {
-                    canvas.set_projection(draw.state.projection);
-                }
+            if draw.state.premul_text != state.premul_text {
+                canvas.set_premultiplied_text(draw.state.premul_text);
+            }
 
-                if draw.state.scissor_rect != state.scissor_rect {
-                    canvas.set_scissor_rect(draw.state.scissor_rect);
-                }
+            if draw.state.depth != state.depth {
+                canvas.set_depth_mode(draw.state.depth);
+            }
 
-                state = draw.state.clone();
+            if draw.state.stencil != state.stencil {
+                canvas.set_stencil_mode(draw.state.stencil);
+            }
 
-                match &draw.draw {
-                    Draw::Mesh { mesh, image, scale } => {
-                        canvas.draw_mesh(mesh, image, draw.param, *scale)
-                    }
-                    Draw::MeshInstances {
-                        mesh,
-                        instances,
-                        scale,
-                    } => canvas.draw_mesh_instances(mesh, instances, draw.param, *scale)?,
-                    Draw::BoundedText { text } => canvas.draw_bounded_text(text, draw.param)?,
+            if draw.state.projection != state.projection {
+                canvas.set_projection(draw.state.projection);
+            }
+
+            if draw.state.scissor_rect != state.scissor_rect {
+                canvas.set_scissor_rect(draw.state.scissor_rect);
+            }
+
+            state = draw.state.clone();
+
+            match &draw.draw {
+                Draw::Mesh { mesh, image, scale } if batched.len() > 1 => {
+                    let params = batched.iter().map(|draw| draw.param).collect::<Vec<_>>();
+                    canvas.draw_mesh_batch(mesh, image, &params, *scale)
                 }
+                Draw::Mesh { mesh, image, scale } => {
+                    canvas.draw_mesh(mesh, image, draw.param, *scale)
+                }
+                Draw::Mesh3d { mesh, image } => canvas.draw_mesh3d(mesh, image, draw.param),
+                Draw::MeshInstances {
+                    mesh,
+                    instances,
+                    scale,
+                } => canvas.draw_mesh_instances(mesh, instances, draw.param, *scale)?,
+                Draw::BoundedText { text } => canvas.draw_bounded_text(text, draw.param)?,
             }
         }
 
         canvas.finish();
 
+        gfx.canvas_draws = self.draws.values().map(Vec::len).sum();
+        gfx.stats.canvases += 1;
+        gfx.stats.bytes_written += std::mem::take(&mut self.bytes_written);
+
         Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
-struct DrawState {
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DrawState {
     shader: Shader,
     params: Option<(ArcBindGroup, ArcBindGroupLayout, u32)>,
     text_shader: Shader,
@@ -518,17 +781,24 @@ struct DrawState {
     sampler: Sampler,
     blend_mode: BlendMode,
     premul_text: bool,
-    projection: mint::ColumnMatrix4<f32>,
-    scissor_rect: (u32, u32, u32, u32),
+    depth: DepthMode,
+    stencil: StencilMode,
+    pub culling: bool,
+    pub projection: mint::ColumnMatrix4<f32>,
+    pub scissor_rect: (u32, u32, u32, u32),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) enum Draw {
     Mesh {
         mesh: Mesh,
         image: Image,
         scale: bool,
     },
+    Mesh3d {
+        mesh: Mesh3d,
+        image: Image,
+    },
     MeshInstances {
         mesh: Mesh,
         instances: InstanceArrayView,
@@ -540,11 +810,60 @@ pub(crate) enum Draw {
 }
 
 // Stores *everything* you need to know to draw something.
-#[derive(Debug)]
-struct DrawCommand {
-    state: DrawState,
-    param: DrawParam,
-    draw: Draw,
+#[derive(Debug, Clone)]
+pub(crate) struct DrawCommand {
+    pub state: DrawState,
+    pub param: DrawParam,
+    pub draw: Draw,
+}
+
+impl DrawCommand {
+    /// What this command must share with its neighbours to be drawn in the same instanced draw
+    /// call, or `None` if it can't be batched.
+    fn batch_key(&self) -> Option<BatchKey<'_>> {
+        match &self.draw {
+            Draw::Mesh { mesh, image, scale } if self.state.shader.vs_module.is_none() => {
+                Some(BatchKey {
+                    mesh: (mesh.verts.id(), mesh.inds.id()),
+                    image: image.view.id(),
+                    scale: *scale,
+                    z: self.param.z,
+                    state: &self.state,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct BatchKey<'a> {
+    mesh: (u64, u64),
+    image: u64,
+    scale: bool,
+    z: ZIndex,
+    state: &'a DrawState,
+}
+
+/// Splits commands into runs of consecutive equal batch keys, each at most `capacity` long.
+///
+/// Commands without a key are runs of their own, and order is always kept.
+fn batch_runs<K: PartialEq>(
+    keys: impl IntoIterator<Item = Option<K>>,
+    capacity: usize,
+) -> Vec<Range<usize>> {
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    let mut last = None;
+    for (i, key) in keys.into_iter().enumerate() {
+        match (runs.last_mut(), &last, &key) {
+            (Some(run), Some(last), Some(key)) if last == key && run.len() < capacity => {
+                run.end = i + 1
+            }
+            _ => runs.push(i..i + 1),
+        }
+        last = key;
+    }
+    runs
 }
 
 #[derive(Debug)]
@@ -577,3 +896,48 @@ pub fn default_text_shader() -> Shader {
         vs_module: None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // keys stand in for (image, state, z)
+    fn runs(keys: &[Option<(u8, u8, ZIndex)>], capacity: usize) -> Vec<Range<usize>> {
+        batch_runs(keys.iter().copied(), capacity)
+    }
+
+    #[test]
+    fn batch_runs_break_on_any_difference() {
+        let a = Some((0, 0, 0));
+        assert_eq!(runs(&[a, a, a], 8), vec![0..3]);
+        assert_eq!(runs(&[a, a, Some((1, 0, 0))], 8), vec![0..2, 2..3]);
+        assert_eq!(runs(&[a, Some((0, 1, 0)), a], 8), vec![0..1, 1..2, 2..3]);
+        assert_eq!(
+            runs(&[a, Some((0, 0, 1)), Some((0, 0, 1))], 8),
+            vec![0..1, 1..3]
+        );
+        assert_eq!(runs(&[a, None, None, a], 8), vec![0..1, 1..2, 2..3, 3..4]);
+        assert_eq!(runs(&[], 8), vec![]);
+    }
+
+    #[test]
+    fn batch_runs_keep_interleaved_draws_apart() {
+        let (a, b) = (Some((0, 0, 0)), Some((1, 0, 0)));
+        assert_eq!(runs(&[a, b, a], 8), vec![0..1, 1..2, 2..3]);
+        assert_eq!(runs(&[a, a, b, b, a], 8), vec![0..2, 2..4, 4..5]);
+    }
+
+    #[test]
+    fn batch_runs_split_at_capacity_in_order() {
+        let a = Some((0, 0, 0));
+        let keys = vec![a; BATCH_CAPACITY * 2 + 1];
+        assert_eq!(
+            runs(&keys, BATCH_CAPACITY),
+            vec![
+                0..BATCH_CAPACITY,
+                BATCH_CAPACITY..BATCH_CAPACITY * 2,
+                BATCH_CAPACITY * 2..BATCH_CAPACITY * 2 + 1,
+            ]
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use super::{
+    gpu::{arc::ArcBuffer, readback::ReadbackPool},
+    image::Image,
+    ImageFormat,
+};
+use crate::{GameError, GameResult};
+use ::image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, RgbaImage,
+};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Output {
+    PngSequence(PathBuf),
+    Gif(PathBuf),
+}
+
+/// Describes a recording of the presented frames, to be started with
+/// [`GraphicsContext::start_recording`](super::GraphicsContext::start_recording).
+///
+/// Frames can be written either as a numbered PNG sequence (`000000.png`, `000001.png`, ...)
+/// or as an animated GIF. The GIF frame delays follow the real time between recorded frames.
+///
+/// Recording does not stall the render loop: each recorded frame is copied into a buffer as part of
+/// that frame's commands, read back once the GPU is done with it, and encoded on a background thread.
+/// [`event::run`](crate::event::run) stops the recording when the game quits; with a custom event loop,
+/// call [`GraphicsContext::stop_recording`](super::GraphicsContext::stop_recording) yourself, or the
+/// last frames may be lost.
+///
+/// ```rust,no_run
+/// # use ggez::graphics::FrameRecorder;
+/// # fn t(ctx: &mut ggez::Context) -> ggez::GameResult {
+/// // record every 2nd frame
+/// ctx.gfx.start_recording(FrameRecorder::gif("/tmp/recording.gif").every_nth_frame(2))?;
+/// // ... later
+/// ctx.gfx.stop_recording()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameRecorder {
+    output: Output,
+    every: u32,
+}
+
+impl FrameRecorder {
+    /// Records frames as PNG files numbered from zero into the directory `dir`,
+    /// which is created if it doesn't exist.
+    pub fn png_sequence(dir: impl AsRef<Path>) -> Self {
+        FrameRecorder {
+            output: Output::PngSequence(dir.as_ref().to_path_buf()),
+            every: 1,
+        }
+    }
+
+    /// Records frames into an endlessly looping animated GIF at `path`.
+    ///
+    /// GIF encoding is slow; consider only recording every few frames with [`FrameRecorder::every_nth_frame`].
+    pub fn gif(path: impl AsRef<Path>) -> Self {
+        FrameRecorder {
+            output: Output::Gif(path.as_ref().to_path_buf()),
+            every: 1,
+        }
+    }
+
+    /// Only records every `n`th presented frame. Defaults to 1, i.e. every frame.
+    pub fn every_nth_frame(mut self, n: u32) -> Self {
+        self.every = n.max(1);
+        self
+    }
+}
+
+struct CapturedFrame {
+    pixels: RgbaImage,
+    time: Instant,
+}
+
+#[derive(Debug)]
+struct PendingFrame {
+    buffer: ArcBuffer,
+    mapped: Option<mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>>,
+    width: u32,
+    height: u32,
+    padded_row: u32,
+    bgra: bool,
+    time: Instant,
+}
+
+/// A running [`FrameRecorder`].
+#[derive(Debug)]
+pub(crate) struct Recording {
+    every: u32,
+    frames: u64,
+    pool: ReadbackPool,
+    pending: VecDeque<PendingFrame>,
+    sender: Option<mpsc::Sender<CapturedFrame>>,
+    worker: Option<JoinHandle<GameResult>>,
+    error: Option<GameError>,
+}
+
+impl Recording {
+    pub fn start(recorder: FrameRecorder, pool: ReadbackPool) -> GameResult<Self> {
+        // open the output right away so that bad paths are reported by `start_recording`
+        let writer = match recorder.output {
+            Output::PngSequence(dir) => {
+                std::fs::create_dir_all(&dir)?;
+                Writer::PngSequence(dir, 0)
+            }
+            Output::Gif(path) => {
+                let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+                encoder.set_repeat(Repeat::Infinite)?;
+                Writer::Gif(encoder, None)
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let worker = std::thread::Builder::new()
+            .name(String::from("ggez frame recorder"))
+            .spawn(move || writer.write_all(receiver))?;
+
+        Ok(Recording {
+            every: recorder.every,
+            frames: 0,
+            pool,
+            pending: VecDeque::new(),
+            sender: Some(sender),
+            worker: Some(worker),
+            error: None,
+        })
+    }
+
+    /// Records the readback of `image` into `cmd`, if this frame is to be recorded.
+    pub fn capture(
+        &mut self,
+        device: &wgpu::Device,
+        cmd: &mut wgpu::CommandEncoder,
+        image: &Image,
+    ) {
+        let frame = self.frames;
+        self.frames += 1;
+        if !frame.is_multiple_of(u64::from(self.every)) || self.error.is_some() {
+            return;
+        }
+
+        let bgra = match image.format() {
+            ImageFormat::Rgba8Unorm | ImageFormat::Rgba8UnormSrgb => false,
+            ImageFormat::Bgra8Unorm | ImageFormat::Bgra8UnormSrgb => true,
+            format => {
+                self.error = Some(GameError::RenderError(format!(
+                    "cannot record frames presented in the {format:?} image format"
+                )));
+                return;
+            }
+        };
+        if image.samples() > 1 {
+            self.error = Some(GameError::RenderError(String::from(
+                "cannot record a multisampled image",
+            )));
+            return;
+        }
+
+        let (buffer, padded_row) = image.copy_to_readback_buffer(device, &self.pool, cmd);
+        self.pending.push_back(PendingFrame {
+            buffer,
+            mapped: None,
+            width: image.width(),
+            height: image.height(),
+            padded_row,
+            bgra,
+            time: Instant::now(),
+        });
+    }
+
+    /// Requests the mapping of newly submitted readbacks and hands finished ones to the writer thread.
+    ///
+    /// Must be called after the commands passed to [`Recording::capture`] have been submitted.
+    pub fn after_submit(&mut self, device: &wgpu::Device) {
+        for frame in self.pending.iter_mut().filter(|f| f.mapped.is_none()) {
+            let (tx, rx) = mpsc::sync_channel(1);
+            frame
+                .buffer
+                .slice(..frame.size())
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let _ = tx.send(result);
+                });
+            frame.mapped = Some(rx);
+        }
+
+        let _ = device.poll(wgpu::Maintain::Poll);
+        self.flush(false);
+    }
+
+    /// Waits for all outstanding readbacks, then finishes writing the output.
+    pub fn finish(mut self, device: &wgpu::Device) -> GameResult {
+        let _ = device.poll(wgpu::Maintain::Wait);
+        self.flush(true);
+
+        drop(self.sender.take());
+        let result = match self.worker.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(GameError::RenderError(String::from(
+                "the frame recorder thread panicked",
+            ))),
+            None => Ok(()),
+        };
+
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => result,
+        }
+    }
+
+    // frames are sent in order, so this stops at the first one that isn't mapped yet
+    fn flush(&mut self, wait: bool) {
+        while let Some(frame) = self.pending.front() {
+            let received = match &frame.mapped {
+                Some(rx) if wait => rx.recv().map_err(|_| mpsc::TryRecvError::Disconnected),
+                Some(rx) => rx.try_recv(),
+                None => return,
+            };
+            let result = match received {
+                Ok(result) => result,
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    let _ = self.pending.pop_front();
+                    continue;
+                }
+            };
+
+            let frame = self.pending.pop_front().unwrap(/* checked above */);
+            if let Err(e) = result {
+                self.error = Some(e.into());
+                continue;
+            }
+
+            let pixels = frame.read();
+            self.pool.put(frame.buffer);
+            if let Some(sender) = &self.sender {
+                // the worker only hangs up after failing, which `finish` reports
+                let _ = sender.send(CapturedFrame {
+                    pixels,
+                    time: frame.time,
+                });
+            }
+        }
+    }
+}
+
+impl PendingFrame {
+    fn size(&self) -> u64 {
+        u64::from(self.padded_row) * u64::from(self.height)
+    }
+
+    fn read(&self) -> RgbaImage {
+        let row = self.width as usize * 4;
+        let mut pixels = Vec::with_capacity(row * self.height as usize);
+        {
+            let data = self.buffer.slice(..self.size()).get_mapped_range();
+            for padded in data.chunks_exact(self.padded_row as usize) {
+                pixels.extend_from_slice(&padded[..row]);
+            }
+        }
+        self.buffer.unmap();
+
+        if self.bgra {
+            for px in pixels.chunks_exact_mut(4) {
+                px.swap(0, 2);
+            }
+        }
+
+        RgbaImage::from_raw(self.width, self.height, pixels).unwrap(/* sized above */)
+    }
+}
+
+enum Writer {
+    PngSequence(PathBuf, usize),
+    // the previous frame is held back until the next one arrives so its delay is known
+    Gif(GifEncoder<BufWriter<File>>, Option<CapturedFrame>),
+}
+
+impl Writer {
+    fn write_all(mut self, frames: mpsc::Receiver<CapturedFrame>) -> GameResult {
+        let mut last_delay = Duration::from_millis(100);
+        for frame in frames {
+            match &mut self {
+                Writer::PngSequence(dir, index) => {
+                    let path = dir.join(format!("{index:06}.png"));
+                    frame
+                        .pixels
+                        .save_with_format(path, ::image::ImageFormat::Png)?;
+                    *index += 1;
+                }
+                Writer::Gif(encoder, prev) => {
+                    let time = frame.time;
+                    if let Some(prev) = prev.replace(frame) {
+                        last_delay = time - prev.time;
+                        encoder.encode_frame(Frame::from_parts(
+                            prev.pixels,
+                            0,
+                            0,
+                            Delay::from_saturating_duration(last_delay),
+                        ))?;
+                    }
+                }
+            }
+        }
+
+        if let Writer::Gif(mut encoder, Some(last)) = self {
+            encoder.encode_frame(Frame::from_parts(
+                last.pixels,
+                0,
+                0,
+                Delay::from_saturating_duration(last_delay),
+            ))?;
+        }
+        Ok(())
+    }
+}
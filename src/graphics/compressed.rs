@@ -0,0 +1,355 @@
+use super::image::{ImageFormat, ImageLoadOptions};
+use crate::{GameError, GameResult};
+use ddsfile::{Caps2, Dds, DxgiFormat};
+use wgpu::{AstcBlock, AstcChannel};
+
+const DDS_MAGIC: &[u8] = b"DDS ";
+const KTX2_MAGIC: &[u8] = &[
+    0xab, b'K', b'T', b'X', b' ', b'2', b'0', 0xbb, b'\r', b'\n', 0x1a, b'\n',
+];
+
+/// A 2D texture read from a DDS or KTX2 container, with the data of each mip level, largest first.
+///
+/// The (usually block compressed) pixels are uploaded to the GPU as they are stored.
+#[derive(Debug)]
+pub(crate) struct TextureContainer {
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl TextureContainer {
+    /// Returns `true` if `bytes` start like a DDS or KTX2 file.
+    pub fn is_container(bytes: &[u8]) -> bool {
+        bytes.starts_with(DDS_MAGIC) || bytes.starts_with(KTX2_MAGIC)
+    }
+
+    /// Parses a DDS or KTX2 file.
+    ///
+    /// Legacy DDS files don't say whether their colors are sRGB encoded; for those,
+    /// [`ImageLoadOptions::srgb`] decides. The other options don't apply to containers.
+    pub fn parse(bytes: &[u8], options: &ImageLoadOptions) -> GameResult<Self> {
+        let container = if bytes.starts_with(DDS_MAGIC) {
+            Self::parse_dds(bytes, options)?
+        } else {
+            Self::parse_ktx2(bytes)?
+        };
+        container.validate()?;
+        Ok(container)
+    }
+
+    /// Checks that a texture can be created for the container, which wgpu would otherwise panic
+    /// on.
+    fn validate(&self) -> GameResult {
+        if self.width == 0 || self.height == 0 {
+            return Err(GameError::ResourceLoadError(format!(
+                "texture size {}x{} is empty",
+                self.width, self.height
+            )));
+        }
+
+        let (block_width, block_height) = self.format.block_dimensions();
+        if !self.width.is_multiple_of(block_width) || !self.height.is_multiple_of(block_height) {
+            return Err(GameError::ResourceLoadError(format!(
+                "texture size {}x{} isn't a multiple of the {block_width}x{block_height} blocks of the {:?} format",
+                self.width, self.height, self.format
+            )));
+        }
+
+        let max_levels = u32::BITS - self.width.max(self.height).leading_zeros();
+        if self.levels.is_empty() || self.levels.len() > max_levels as usize {
+            return Err(GameError::ResourceLoadError(format!(
+                "{} mip levels given, a {}x{} texture has 1 to {max_levels}",
+                self.levels.len(),
+                self.width,
+                self.height
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn parse_dds(bytes: &[u8], options: &ImageLoadOptions) -> GameResult<Self> {
+        let dds = Dds::read(bytes)
+            .map_err(|e| GameError::ResourceLoadError(format!("failed to read DDS file: {e}")))?;
+
+        if dds.get_depth() > 1
+            || dds.get_num_array_layers() > 1
+            || dds.header.caps2.contains(Caps2::CUBEMAP)
+        {
+            return Err(unsupported("DDS volume, array and cube map textures"));
+        }
+
+        let dxgi = dds
+            .get_dxgi_format()
+            .ok_or_else(|| unsupported("this DDS pixel format"))?;
+        let mut format = dxgi_format(dxgi).ok_or_else(|| {
+            GameError::ResourceLoadError(format!("unsupported DDS format {dxgi:?}"))
+        })?;
+        if dds.header10.is_none() && !options.srgb {
+            format = format.remove_srgb_suffix();
+        }
+
+        let (width, height) = (dds.get_width(), dds.get_height());
+        let mut data = dds.data.as_slice();
+        let mut levels = Vec::new();
+        for level in 0..dds.get_num_mipmap_levels() {
+            let size = level_size(format, width, height, level);
+            if data.len() < size {
+                return Err(GameError::ResourceLoadError(String::from(
+                    "DDS file is truncated",
+                )));
+            }
+            let (level, rest) = data.split_at(size);
+            levels.push(level.to_vec());
+            data = rest;
+        }
+
+        Ok(TextureContainer {
+            format,
+            width,
+            height,
+            levels,
+        })
+    }
+
+    fn parse_ktx2(bytes: &[u8]) -> GameResult<Self> {
+        let reader = ktx2::Reader::new(bytes)
+            .map_err(|e| GameError::ResourceLoadError(format!("failed to read KTX2 file: {e}")))?;
+        let header = reader.header();
+
+        if header.supercompression_scheme.is_some() {
+            return Err(unsupported(
+                "supercompressed (Basis Universal or zstd) KTX2 files",
+            ));
+        }
+        if header.pixel_depth > 1 || header.layer_count > 1 || header.face_count > 1 {
+            return Err(unsupported("KTX2 volume, array and cube map textures"));
+        }
+
+        let vk_format = header
+            .format
+            .ok_or_else(|| unsupported("KTX2 files without a Vulkan format"))?;
+        let format = vk_format_to_wgpu(vk_format).ok_or_else(|| {
+            GameError::ResourceLoadError(format!("unsupported KTX2 format {vk_format:?}"))
+        })?;
+
+        let (width, height) = (header.pixel_width, header.pixel_height.max(1));
+        let levels = reader
+            .levels()
+            .enumerate()
+            .map(|(level, data)| {
+                if data.len() < level_size(format, width, height, level as u32) {
+                    Err(GameError::ResourceLoadError(String::from(
+                        "KTX2 file is truncated",
+                    )))
+                } else {
+                    Ok(data.to_vec())
+                }
+            })
+            .collect::<GameResult<Vec<_>>>()?;
+
+        Ok(TextureContainer {
+            format,
+            width,
+            height,
+            levels,
+        })
+    }
+}
+
+fn unsupported(what: &str) -> GameError {
+    GameError::ResourceLoadError(format!("{what} are not supported"))
+}
+
+/// Returns the size in bytes of mip level `level` of a `width` x `height` texture.
+fn level_size(format: ImageFormat, width: u32, height: u32, level: u32) -> usize {
+    let (bw, bh) = format.block_dimensions();
+    let block_size = format.block_size(None).unwrap_or(0);
+    let width = (width >> level).max(1).div_ceil(bw);
+    let height = (height >> level).max(1).div_ceil(bh);
+    width as usize * height as usize * block_size as usize
+}
+
+fn dxgi_format(format: DxgiFormat) -> Option<ImageFormat> {
+    Some(match format {
+        DxgiFormat::R8G8B8A8_UNorm => ImageFormat::Rgba8Unorm,
+        DxgiFormat::R8G8B8A8_UNorm_sRGB => ImageFormat::Rgba8UnormSrgb,
+        DxgiFormat::B8G8R8A8_UNorm => ImageFormat::Bgra8Unorm,
+        DxgiFormat::B8G8R8A8_UNorm_sRGB => ImageFormat::Bgra8UnormSrgb,
+        DxgiFormat::R8_UNorm => ImageFormat::R8Unorm,
+        DxgiFormat::R16G16B16A16_Float => ImageFormat::Rgba16Float,
+        DxgiFormat::R32G32B32A32_Float => ImageFormat::Rgba32Float,
+        DxgiFormat::BC1_UNorm => ImageFormat::Bc1RgbaUnorm,
+        DxgiFormat::BC1_UNorm_sRGB => ImageFormat::Bc1RgbaUnormSrgb,
+        DxgiFormat::BC2_UNorm => ImageFormat::Bc2RgbaUnorm,
+        DxgiFormat::BC2_UNorm_sRGB => ImageFormat::Bc2RgbaUnormSrgb,
+        DxgiFormat::BC3_UNorm => ImageFormat::Bc3RgbaUnorm,
+        DxgiFormat::BC3_UNorm_sRGB => ImageFormat::Bc3RgbaUnormSrgb,
+        DxgiFormat::BC4_UNorm => ImageFormat::Bc4RUnorm,
+        DxgiFormat::BC4_SNorm => ImageFormat::Bc4RSnorm,
+        DxgiFormat::BC5_UNorm => ImageFormat::Bc5RgUnorm,
+        DxgiFormat::BC5_SNorm => ImageFormat::Bc5RgSnorm,
+        DxgiFormat::BC6H_UF16 => ImageFormat::Bc6hRgbUfloat,
+        DxgiFormat::BC6H_SF16 => ImageFormat::Bc6hRgbFloat,
+        DxgiFormat::BC7_UNorm => ImageFormat::Bc7RgbaUnorm,
+        DxgiFormat::BC7_UNorm_sRGB => ImageFormat::Bc7RgbaUnormSrgb,
+        _ => return None,
+    })
+}
+
+fn vk_format_to_wgpu(format: ktx2::Format) -> Option<ImageFormat> {
+    use ktx2::Format as Vk;
+
+    let astc = |block, srgb| ImageFormat::Astc {
+        block,
+        channel: if srgb {
+            AstcChannel::UnormSrgb
+        } else {
+            AstcChannel::Unorm
+        },
+    };
+
+    Some(match format {
+        Vk::R8_UNORM => ImageFormat::R8Unorm,
+        Vk::R8G8B8A8_UNORM => ImageFormat::Rgba8Unorm,
+        Vk::R8G8B8A8_SRGB => ImageFormat::Rgba8UnormSrgb,
+        Vk::B8G8R8A8_UNORM => ImageFormat::Bgra8Unorm,
+        Vk::B8G8R8A8_SRGB => ImageFormat::Bgra8UnormSrgb,
+        Vk::R16G16B16A16_SFLOAT => ImageFormat::Rgba16Float,
+        Vk::R32G32B32A32_SFLOAT => ImageFormat::Rgba32Float,
+        // wgpu has no opaque BC1 format; only blocks using BC1's 1-bit alpha mode decode differently
+        Vk::BC1_RGB_UNORM_BLOCK | Vk::BC1_RGBA_UNORM_BLOCK => ImageFormat::Bc1RgbaUnorm,
+        Vk::BC1_RGB_SRGB_BLOCK | Vk::BC1_RGBA_SRGB_BLOCK => ImageFormat::Bc1RgbaUnormSrgb,
+        Vk::BC2_UNORM_BLOCK => ImageFormat::Bc2RgbaUnorm,
+        Vk::BC2_SRGB_BLOCK => ImageFormat::Bc2RgbaUnormSrgb,
+        Vk::BC3_UNORM_BLOCK => ImageFormat::Bc3RgbaUnorm,
+        Vk::BC3_SRGB_BLOCK => ImageFormat::Bc3RgbaUnormSrgb,
+        Vk::BC4_UNORM_BLOCK => ImageFormat::Bc4RUnorm,
+        Vk::BC4_SNORM_BLOCK => ImageFormat::Bc4RSnorm,
+        Vk::BC5_UNORM_BLOCK => ImageFormat::Bc5RgUnorm,
+        Vk::BC5_SNORM_BLOCK => ImageFormat::Bc5RgSnorm,
+        Vk::BC6H_UFLOAT_BLOCK => ImageFormat::Bc6hRgbUfloat,
+        Vk::BC6H_SFLOAT_BLOCK => ImageFormat::Bc6hRgbFloat,
+        Vk::BC7_UNORM_BLOCK => ImageFormat::Bc7RgbaUnorm,
+        Vk::BC7_SRGB_BLOCK => ImageFormat::Bc7RgbaUnormSrgb,
+        Vk::ETC2_R8G8B8_UNORM_BLOCK => ImageFormat::Etc2Rgb8Unorm,
+        Vk::ETC2_R8G8B8_SRGB_BLOCK => ImageFormat::Etc2Rgb8UnormSrgb,
+        Vk::ETC2_R8G8B8A1_UNORM_BLOCK => ImageFormat::Etc2Rgb8A1Unorm,
+        Vk::ETC2_R8G8B8A1_SRGB_BLOCK => ImageFormat::Etc2Rgb8A1UnormSrgb,
+        Vk::ETC2_R8G8B8A8_UNORM_BLOCK => ImageFormat::Etc2Rgba8Unorm,
+        Vk::ETC2_R8G8B8A8_SRGB_BLOCK => ImageFormat::Etc2Rgba8UnormSrgb,
+        Vk::EAC_R11_UNORM_BLOCK => ImageFormat::EacR11Unorm,
+        Vk::EAC_R11_SNORM_BLOCK => ImageFormat::EacR11Snorm,
+        Vk::EAC_R11G11_UNORM_BLOCK => ImageFormat::EacRg11Unorm,
+        Vk::EAC_R11G11_SNORM_BLOCK => ImageFormat::EacRg11Snorm,
+        Vk::ASTC_4x4_UNORM_BLOCK => astc(AstcBlock::B4x4, false),
+        Vk::ASTC_4x4_SRGB_BLOCK => astc(AstcBlock::B4x4, true),
+        Vk::ASTC_5x4_UNORM_BLOCK => astc(AstcBlock::B5x4, false),
+        Vk::ASTC_5x4_SRGB_BLOCK => astc(AstcBlock::B5x4, true),
+        Vk::ASTC_5x5_UNORM_BLOCK => astc(AstcBlock::B5x5, false),
+        Vk::ASTC_5x5_SRGB_BLOCK => astc(AstcBlock::B5x5, true),
+        Vk::ASTC_6x5_UNORM_BLOCK => astc(AstcBlock::B6x5, false),
+        Vk::ASTC_6x5_SRGB_BLOCK => astc(AstcBlock::B6x5, true),
+        Vk::ASTC_6x6_UNORM_BLOCK => astc(AstcBlock::B6x6, false),
+        Vk::ASTC_6x6_SRGB_BLOCK => astc(AstcBlock::B6x6, true),
+        Vk::ASTC_8x5_UNORM_BLOCK => astc(AstcBlock::B8x5, false),
+        Vk::ASTC_8x5_SRGB_BLOCK => astc(AstcBlock::B8x5, true),
+        Vk::ASTC_8x6_UNORM_BLOCK => astc(AstcBlock::B8x6, false),
+        Vk::ASTC_8x6_SRGB_BLOCK => astc(AstcBlock::B8x6, true),
+        Vk::ASTC_8x8_UNORM_BLOCK => astc(AstcBlock::B8x8, false),
+        Vk::ASTC_8x8_SRGB_BLOCK => astc(AstcBlock::B8x8, true),
+        Vk::ASTC_10x5_UNORM_BLOCK => astc(AstcBlock::B10x5, false),
+        Vk::ASTC_10x5_SRGB_BLOCK => astc(AstcBlock::B10x5, true),
+        Vk::ASTC_10x6_UNORM_BLOCK => astc(AstcBlock::B10x6, false),
+        Vk::ASTC_10x6_SRGB_BLOCK => astc(AstcBlock::B10x6, true),
+        Vk::ASTC_10x8_UNORM_BLOCK => astc(AstcBlock::B10x8, false),
+        Vk::ASTC_10x8_SRGB_BLOCK => astc(AstcBlock::B10x8, true),
+        Vk::ASTC_10x10_UNORM_BLOCK => astc(AstcBlock::B10x10, false),
+        Vk::ASTC_10x10_SRGB_BLOCK => astc(AstcBlock::B10x10, true),
+        Vk::ASTC_12x10_UNORM_BLOCK => astc(AstcBlock::B12x10, false),
+        Vk::ASTC_12x10_SRGB_BLOCK => astc(AstcBlock::B12x10, true),
+        Vk::ASTC_12x12_UNORM_BLOCK => astc(AstcBlock::B12x12, false),
+        Vk::ASTC_12x12_SRGB_BLOCK => astc(AstcBlock::B12x12, true),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_sizes_round_up_to_blocks() {
+        assert_eq!(level_size(ImageFormat::Bc1RgbaUnorm, 16, 16, 0), 4 * 4 * 8);
+        assert_eq!(level_size(ImageFormat::Bc1RgbaUnorm, 16, 16, 3), 8);
+        assert_eq!(level_size(ImageFormat::Bc7RgbaUnorm, 6, 3, 0), 2 * 16);
+        assert_eq!(level_size(ImageFormat::Rgba8Unorm, 5, 3, 1), 2 * 4);
+    }
+
+    #[test]
+    fn invalid_containers_are_rejected() {
+        let container = |format, width, height, levels| TextureContainer {
+            format,
+            width,
+            height,
+            levels: vec![Vec::new(); levels],
+        };
+
+        assert!(container(ImageFormat::Bc1RgbaUnorm, 32, 8, 6)
+            .validate()
+            .is_ok());
+        assert!(container(ImageFormat::Rgba8Unorm, 30, 30, 5)
+            .validate()
+            .is_ok());
+
+        // not made of whole blocks
+        assert!(container(ImageFormat::Bc1RgbaUnorm, 30, 30, 1)
+            .validate()
+            .is_err());
+        assert!(container(ImageFormat::Bc7RgbaUnorm, 8, 6, 1)
+            .validate()
+            .is_err());
+        // empty
+        assert!(container(ImageFormat::Rgba8Unorm, 0, 1, 1)
+            .validate()
+            .is_err());
+        assert!(container(ImageFormat::Rgba8Unorm, 4, 0, 1)
+            .validate()
+            .is_err());
+        // no levels, or more than the full chain
+        assert!(container(ImageFormat::Rgba8Unorm, 4, 4, 0)
+            .validate()
+            .is_err());
+        assert!(container(ImageFormat::Rgba8Unorm, 32, 8, 7)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn dds_mip_levels() {
+        let dds = Dds::new_dxgi(ddsfile::NewDxgiParams {
+            height: 8,
+            width: 8,
+            depth: None,
+            format: DxgiFormat::BC1_UNorm_sRGB,
+            mipmap_levels: Some(4),
+            array_layers: None,
+            caps2: None,
+            is_cubemap: false,
+            resource_dimension: ddsfile::D3D10ResourceDimension::Texture2D,
+            alpha_mode: ddsfile::AlphaMode::Straight,
+        })
+        .unwrap();
+        let mut bytes = Vec::new();
+        dds.write(&mut bytes).unwrap();
+
+        assert!(TextureContainer::is_container(&bytes));
+        let container = TextureContainer::parse(&bytes, &ImageLoadOptions::default()).unwrap();
+        assert_eq!(container.format, ImageFormat::Bc1RgbaUnormSrgb);
+        assert_eq!((container.width, container.height), (8, 8));
+        let sizes: Vec<_> = container.levels.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [32, 8, 8, 8]);
+    }
+}
@@ -1,18 +1,23 @@
 use super::{
+    capture::{FrameRecorder, Recording},
     draw::DrawUniforms,
     gpu::{
         arc::{
-            ArcBindGroup, ArcBindGroupLayout, ArcBuffer, ArcRenderPipeline, ArcSampler,
-            ArcShaderModule, ArcTextureView,
+            live_buffers, live_textures, ArcBindGroup, ArcBindGroupLayout, ArcBuffer,
+            ArcRenderPipeline, ArcSampler, ArcShaderModule, ArcTextureView,
         },
         bind_group::{BindGroupCache, BindGroupEntryKey},
         growing::GrowingBufferArena,
-        pipeline::PipelineCache,
+        mipmap::MipmapGenerator,
+        pipeline::{PipelineCache, DEPTH_STENCIL_FORMAT},
+        readback::ReadbackPool,
         text::TextRenderer,
     },
-    image::{Image, ImageFormat},
+    image::{Image, ImageFormat, ImageLoadOptions},
+    internal_canvas::BATCH_CAPACITY,
     mesh::{Mesh, Vertex},
     sampler::{Sampler, SamplerCache},
+    stats::FrameStats,
     text::FontData,
     MeshData, ScreenImage,
 };
@@ -80,8 +85,16 @@ pub struct GraphicsContext {
     pub(crate) fonts: HashMap<String, FontId>,
     pub(crate) staging_belt: wgpu::util::StagingBelt,
     pub(crate) uniform_arena: GrowingBufferArena,
+    // per-instance draw params of the sprites batched by `Canvas::finish`
+    pub(crate) batch_arena: GrowingBufferArena,
+    pub(crate) readback_pool: ReadbackPool,
+    pub(crate) mipmaps: MipmapGenerator,
+    // depth-stencil attachments shared by canvases, by size and sample count, with whether they
+    // were used this frame; unused ones are dropped at the end of the frame
+    depth_stencil: HashMap<(u32, u32, u32), (ArcTextureView, bool)>,
 
     pub(crate) draw_shader: ArcShaderModule,
+    pub(crate) draw3d_shader: ArcShaderModule,
     pub(crate) instance_shader: ArcShaderModule,
     pub(crate) instance_unordered_shader: ArcShaderModule,
     pub(crate) text_shader: ArcShaderModule,
@@ -90,6 +103,13 @@ pub struct GraphicsContext {
     pub(crate) white_image: Image,
     pub(crate) instance_bind_layout: ArcBindGroupLayout,
 
+    // number of draw commands submitted by the last `Canvas::finish`
+    pub(crate) canvas_draws: usize,
+    pub(crate) stats: FrameStats,
+    last_stats: FrameStats,
+    last_present: Option<Image>,
+    recording: Option<Recording>,
+
     bind_group: Option<(Vec<BindGroupEntryKey>, ArcBindGroup)>,
 }
 
@@ -251,7 +271,7 @@ impl GraphicsContext {
         }
 
         window_builder = if !conf.window_setup.icon.is_empty() {
-            let icon = load_icon(conf.window_setup.icon.as_ref())?;
+            let icon = load_icon(conf.window_setup.icon.as_ref(), ImageLoadOptions::default())?;
             window_builder.with_window_icon(Some(icon))
         } else {
             window_builder
@@ -275,7 +295,11 @@ impl GraphicsContext {
         let (device, queue) = pollster::block_on(adapter.request_device(
             &wgpu::DeviceDescriptor {
                 label: None,
-                features: wgpu::Features::default(),
+                // compressed formats are enabled where available, loading such images fails otherwise
+                features: adapter.features()
+                    & (wgpu::Features::TEXTURE_COMPRESSION_BC
+                        | wgpu::Features::TEXTURE_COMPRESSION_ETC2
+                        | wgpu::Features::TEXTURE_COMPRESSION_ASTC),
                 limits: wgpu::Limits {
                     // 1st: DrawParams
                     // 2nd: Texture + Sampler
@@ -340,6 +364,17 @@ impl GraphicsContext {
                 mapped_at_creation: false,
             },
         );
+        // batches are bound by dynamic offset, which has to respect the storage alignment
+        let batch_arena = GrowingBufferArena::new(
+            &wgpu.device,
+            u64::from(wgpu.device.limits().min_storage_buffer_offset_alignment),
+            wgpu::BufferDescriptor {
+                label: None,
+                size: BATCH_CAPACITY as u64 * DrawUniforms::std140_size_static() as u64,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        );
 
         let draw_shader = ArcShaderModule::new(wgpu.device.create_shader_module(
             wgpu::ShaderModuleDescriptor {
@@ -348,6 +383,13 @@ impl GraphicsContext {
             },
         ));
 
+        let draw3d_shader = ArcShaderModule::new(wgpu.device.create_shader_module(
+            wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(include_str!("shader/draw3d.wgsl").into()),
+            },
+        ));
+
         let instance_shader = ArcShaderModule::new(wgpu.device.create_shader_module(
             wgpu::ShaderModuleDescriptor {
                 label: None,
@@ -377,6 +419,7 @@ impl GraphicsContext {
                 source: wgpu::ShaderSource::Wgsl(include_str!("shader/copy.wgsl").into()),
             },
         ));
+        let mipmaps = MipmapGenerator::new(&wgpu.device, copy_shader.clone());
 
         let rect_mesh = Mesh::from_data_wgpu(
             &wgpu,
@@ -444,7 +487,12 @@ impl GraphicsContext {
             fonts: HashMap::new(),
             staging_belt,
             uniform_arena,
+            batch_arena,
+            readback_pool: ReadbackPool::default(),
+            mipmaps,
+            depth_stencil: HashMap::new(),
             draw_shader,
+            draw3d_shader,
             instance_shader,
             instance_unordered_shader,
             text_shader,
@@ -453,6 +501,12 @@ impl GraphicsContext {
             white_image,
             instance_bind_layout,
 
+            canvas_draws: 0,
+            stats: FrameStats::default(),
+            last_stats: FrameStats::default(),
+            last_present: None,
+            recording: None,
+
             bind_group: None,
         };
 
@@ -558,8 +612,20 @@ impl GraphicsContext {
 
     /// Sets the window icon. `None` for path removes the icon.
     pub fn set_window_icon<P: AsRef<Path>>(&self, path: impl Into<Option<P>>) -> GameResult {
+        self.set_window_icon_with_options(path, ImageLoadOptions::default())
+    }
+
+    /// Like [`GraphicsContext::set_window_icon`], but decodes the icon as described by `options`.
+    ///
+    /// Icons are always 8-bit RGBA, so only [`ImageLoadOptions::srgb`] and
+    /// [`ImageLoadOptions::premultiply`] have an effect.
+    pub fn set_window_icon_with_options<P: AsRef<Path>>(
+        &self,
+        path: impl Into<Option<P>>,
+        options: ImageLoadOptions,
+    ) -> GameResult {
         let icon = match path.into() {
-            Some(p) => Some(load_icon(p.as_ref())?),
+            Some(p) => Some(load_icon(p.as_ref(), options)?),
             None => None,
         };
         self.window.set_window_icon(icon);
@@ -616,10 +682,60 @@ impl GraphicsContext {
         self.fcx.as_mut().map(|fcx| &mut fcx.cmd)
     }
 
+    /// Returns a copy of the image presented by the last call to [`GraphicsContext::end_frame`].
+    ///
+    /// The copy is unaffected by later frames, so it can be kept around, drawn,
+    /// or read back with [`Image::to_pixels`] and [`Image::encode`].
+    pub fn capture_frame(&self) -> GameResult<Image> {
+        match &self.last_present {
+            Some(image) => image.duplicate(&self.wgpu),
+            None => Err(GameError::RenderError(String::from(
+                "cannot capture a frame before one has been presented",
+            ))),
+        }
+    }
+
+    /// Starts recording the presented frames as described by `recorder`.
+    ///
+    /// Any recording already in progress is stopped first.
+    pub fn start_recording(&mut self, recorder: FrameRecorder) -> GameResult {
+        self.stop_recording()?;
+        self.recording = Some(Recording::start(recorder, self.readback_pool.clone())?);
+        Ok(())
+    }
+
+    /// Stops the current recording, waiting for the outstanding frames to be written.
+    ///
+    /// Returns the first error encountered while recording, if any.
+    /// Does nothing if there is no recording in progress.
+    pub fn stop_recording(&mut self) -> GameResult {
+        match self.recording.take() {
+            Some(recording) => recording.finish(&self.wgpu.device),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns whether frames are currently being recorded.
+    #[inline]
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Returns the rendering statistics of the last completed frame.
+    ///
+    /// The counters are reset by [`GraphicsContext::begin_frame`] and collected by
+    /// [`GraphicsContext::end_frame`], so values read while drawing describe the previous frame.
+    #[inline]
+    pub fn frame_stats(&self) -> &FrameStats {
+        &self.last_stats
+    }
+
     /// Begins a new frame.
     ///
     /// The only situation you need to call this in is when you are rolling your own event loop.
     pub fn begin_frame(&mut self) -> GameResult {
+        trace_scope!("begin_frame");
+
         if self.fcx.is_some() {
             return Err(GameError::RenderError(String::from(
                 "cannot begin a new frame while another frame is still in progress; call end_frame first",
@@ -657,9 +773,13 @@ impl GraphicsContext {
         });
 
         self.uniform_arena.free();
+        self.batch_arena.free();
 
         self.text.verts.free();
 
+        self.stats = FrameStats::default();
+        let _ = self.bind_group_cache.take_stats();
+
         Ok(())
     }
 
@@ -667,7 +787,23 @@ impl GraphicsContext {
     ///
     /// The only situation you need to call this in is when you are rolling your own event loop.
     pub fn end_frame(&mut self) -> GameResult {
+        trace_scope!(
+            "end_frame",
+            canvases = self.stats.canvases,
+            draw_calls = self.stats.draw_calls
+        );
+
         if let Some(mut fcx) = self.fcx.take() {
+            self.depth_stencil
+                .retain(|_, (_, used)| std::mem::take(used));
+
+            let (hits, misses) = self.bind_group_cache.take_stats();
+            self.stats.bind_group_hits = hits;
+            self.stats.bind_group_misses = misses;
+            self.stats.textures_alive = live_textures();
+            self.stats.buffers_alive = live_buffers();
+            self.last_stats = self.stats;
+
             let mut present_pass = fcx.cmd.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -685,7 +821,7 @@ impl GraphicsContext {
                 .sampler_cache
                 .get(&self.wgpu.device, Sampler::default());
 
-            let (bind, layout) = self.bind_group(fcx.present.view, sampler.clone());
+            let (bind, layout) = self.bind_group(fcx.present.view.clone(), sampler.clone());
 
             let layout = self.pipeline_cache.layout(&self.wgpu.device, &[layout]);
             let copy = self.pipeline_cache.render_pipeline(
@@ -699,7 +835,8 @@ impl GraphicsContext {
                     samples: 1,
                     format: self.surface_config.format,
                     blend: None,
-                    depth: false,
+                    depth_stencil: None,
+                    write_mask: wgpu::ColorWrites::ALL,
                     vertices: false,
                     topology: wgpu::PrimitiveTopology::TriangleList,
                     vertex_layout: Vertex::layout(),
@@ -715,12 +852,21 @@ impl GraphicsContext {
 
             std::mem::drop(present_pass);
 
+            if let Some(recording) = &mut self.recording {
+                recording.capture(&self.wgpu.device, &mut fcx.cmd, &fcx.present);
+            }
+
             self.staging_belt.finish();
             let _ = self.wgpu.queue.submit([fcx.cmd.finish()]);
             fcx.frame.present();
 
             self.staging_belt.recall();
 
+            if let Some(recording) = &mut self.recording {
+                recording.after_submit(&self.wgpu.device);
+            }
+            self.last_present = Some(fcx.present);
+
             Ok(())
         } else {
             Err(GameError::RenderError(String::from(
@@ -741,6 +887,8 @@ impl GraphicsContext {
     }
 
     pub(crate) fn update_frame_image(&mut self) {
+        self.depth_stencil.clear();
+
         // Internally, GraphicsContext stores an intermediate image that is rendered to. Then, that frame image is rendered to the actual swapchain image.
         // Moreover, one frame image is non-MSAA, whilst the other is MSAA.
         // Since they're stored as ScreenImage, all this function does is store the corresponding Image returned by `ScreenImage::image()`.
@@ -754,6 +902,42 @@ impl GraphicsContext {
         self.frame_msaa = Some(frame_msaa);
     }
 
+    /// Returns a depth-stencil attachment for canvases of the given size and sample count.
+    ///
+    /// Attachments are shared between canvases, which clear them when they start drawing, and
+    /// are dropped when the window is resized.
+    pub(crate) fn depth_stencil_view(
+        &mut self,
+        width: u32,
+        height: u32,
+        samples: u32,
+    ) -> ArcTextureView {
+        let device = &self.wgpu.device;
+        let (view, used) = self
+            .depth_stencil
+            .entry((width, height, samples))
+            .or_insert_with(|| {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: None,
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: samples,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: DEPTH_STENCIL_FORMAT,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                });
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                (ArcTextureView::new(view), false)
+            });
+        *used = true;
+        view.clone()
+    }
+
     pub(crate) fn set_window_mode(&mut self, mode: &WindowMode) -> GameResult {
         let window = &mut self.window;
 
@@ -839,7 +1023,10 @@ impl GraphicsContext {
 // but still better than
 // having `winit` try to do the image loading for us.
 // see https://github.com/tomaka/winit/issues/661
-pub(crate) fn load_icon(icon_file: &Path) -> GameResult<winit::window::Icon> {
+pub(crate) fn load_icon(
+    icon_file: &Path,
+    options: ImageLoadOptions,
+) -> GameResult<winit::window::Icon> {
     use std::io::Read;
     use winit::window::Icon;
 
@@ -847,8 +1034,15 @@ pub(crate) fn load_icon(icon_file: &Path) -> GameResult<winit::window::Icon> {
     let mut reader = File::open(icon_file)?;
     let _ = reader.read_to_end(&mut buf)?;
     let i = imgcrate::load_from_memory(&buf)?;
-    let image_data = i.to_rgba8();
-    Icon::from_rgba(image_data.to_vec(), i.width(), i.height()).map_err(|e| {
+    let options = ImageLoadOptions {
+        single_channel: false,
+        high_precision: false,
+        mipmaps: false,
+        ..options
+    };
+    let (width, height) = (i.width(), i.height());
+    let (image_data, _) = options.convert(i)?;
+    Icon::from_rgba(image_data, width, height).map_err(|e| {
         let msg = format!("Could not load icon: {e:?}");
         GameError::ResourceLoadError(msg)
     })
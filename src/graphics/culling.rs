@@ -0,0 +1,85 @@
+use super::Rect;
+
+/// The part of a canvas' target that draws can reach: the scissor rect, seen through the
+/// projection.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Viewport {
+    projection: glam::Mat4,
+    size: glam::Vec2,
+    scissor: Rect,
+}
+
+impl Viewport {
+    pub fn new(
+        projection: mint::ColumnMatrix4<f32>,
+        (x, y, w, h): (u32, u32, u32, u32),
+        (width, height): (u32, u32),
+    ) -> Self {
+        Viewport {
+            projection: projection.into(),
+            size: glam::vec2(width as f32, height as f32),
+            scissor: Rect::new(x as f32, y as f32, w as f32, h as f32),
+        }
+    }
+
+    /// Whether `bounds`, moved by `transform` before the projection, can cover any pixel of the
+    /// viewport.
+    ///
+    /// This is conservative: bounds reaching behind a perspective camera are always visible.
+    pub fn overlaps(&self, transform: glam::Mat4, bounds: Rect) -> bool {
+        let transform = self.projection * transform;
+
+        let mut min = glam::Vec2::splat(f32::INFINITY);
+        let mut max = glam::Vec2::splat(f32::NEG_INFINITY);
+        for (x, y) in [
+            (bounds.left(), bounds.top()),
+            (bounds.right(), bounds.top()),
+            (bounds.right(), bounds.bottom()),
+            (bounds.left(), bounds.bottom()),
+        ] {
+            let clip = transform * glam::vec4(x, y, 0., 1.);
+            if clip.w <= 0. {
+                return true;
+            }
+
+            // from normalized device coordinates to pixels, with y pointing down
+            let ndc = clip.truncate().truncate() / clip.w;
+            let pixel = (glam::vec2(ndc.x, -ndc.y) + 1.) / 2. * self.size;
+            min = min.min(pixel);
+            max = max.max(pixel);
+        }
+
+        max.x > self.scissor.left()
+            && min.x < self.scissor.right()
+            && max.y > self.scissor.top()
+            && min.y < self.scissor.bottom()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::internal_canvas::screen_to_mat;
+
+    #[test]
+    fn overlaps() {
+        let projection = screen_to_mat(Rect::new(0., 0., 800., 600.));
+        let viewport = Viewport::new(projection.into(), (0, 0, 800, 600), (800, 600));
+        let quad = Rect::new(0., 0., 10., 10.);
+        let at = |x, y| glam::Mat4::from_translation(glam::vec3(x, y, 0.));
+
+        assert!(viewport.overlaps(at(0., 0.), quad));
+        assert!(viewport.overlaps(at(-5., 595.), quad));
+        assert!(!viewport.overlaps(at(-10., 0.), quad));
+        assert!(!viewport.overlaps(at(0., 600.), quad));
+        assert!(!viewport.overlaps(at(1000., 300.), quad));
+
+        // a rotated quad whose corner pokes into the top-left
+        let rotated = at(-7., -7.) * glam::Mat4::from_rotation_z(std::f32::consts::FRAC_PI_4);
+        assert!(viewport.overlaps(rotated, quad));
+
+        let scissored = Viewport::new(projection.into(), (100, 100, 50, 50), (800, 600));
+        assert!(!scissored.overlaps(at(0., 0.), quad));
+        assert!(scissored.overlaps(at(95., 95.), quad));
+    }
+}
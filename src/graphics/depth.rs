@@ -0,0 +1,54 @@
+use super::CompareFunction;
+
+/// Describes how a draw tests and writes the depth buffer of a [`Canvas`](super::Canvas).
+///
+/// Depth goes from `0.0`, nearest, to `1.0`, farthest, and every canvas starts out at `1.0`.
+/// 2D draws are placed with [`DrawParam::depth`](super::DrawParam::depth), which lets the GPU
+/// sort overlapping sprites (e.g. by their feet in an isometric game) regardless of the
+/// order they're drawn in. [`Mesh3d`](super::Mesh3d)es take their depth from their vertices
+/// and the projection.
+///
+/// Every pixel of the drawn geometry writes depth, including transparent ones, so
+/// translucent draws should come last, with [`DepthMode::TEST`].
+///
+/// The canvas only gets a depth buffer if one of its draws uses a mode other than
+/// [`DepthMode::DISABLED`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DepthMode {
+    /// Comparison between the depth of a pixel and the stored depth, as
+    /// `depth <compare> stored`, that must pass for the pixel to be drawn.
+    pub compare: CompareFunction,
+    /// Whether passing pixels replace the stored depth.
+    pub write: bool,
+}
+
+impl DepthMode {
+    /// Ignores the depth buffer; this is the default.
+    pub const DISABLED: Self = DepthMode {
+        compare: CompareFunction::Always,
+        write: false,
+    };
+
+    /// Draws pixels at least as near as what's already there and stores their depth.
+    pub const LESS_EQUAL: Self = DepthMode {
+        compare: CompareFunction::LessEqual,
+        write: true,
+    };
+
+    /// Draws pixels at least as near as what's already there, without storing their depth.
+    pub const TEST: Self = DepthMode {
+        compare: CompareFunction::LessEqual,
+        write: false,
+    };
+
+    /// Creates a depth mode with the given comparison.
+    pub const fn new(compare: CompareFunction, write: bool) -> Self {
+        DepthMode { compare, write }
+    }
+}
+
+impl Default for DepthMode {
+    fn default() -> Self {
+        Self::DISABLED
+    }
+}
@@ -97,6 +97,17 @@ impl Transform {
             }
         }
     }
+
+    /// Same as `to_bare_matrix()`, moved away from the viewer by `depth`.
+    pub(crate) fn to_matrix_at_depth(self, depth: f32) -> mint::ColumnMatrix4<f32> {
+        let matrix = self.to_bare_matrix();
+        if depth == 0. {
+            matrix
+        } else {
+            (glam::Mat4::from_translation(glam::vec3(0., 0., -depth)) * glam::Mat4::from(matrix))
+                .into()
+        }
+    }
 }
 
 /// Value describing the Z "coordinate" of a draw.
@@ -134,6 +145,8 @@ pub struct DrawParam {
     pub transform: Transform,
     /// The Z coordinate of the draw.
     pub z: ZIndex,
+    /// The depth of the draw, tested against the depth buffer of the canvas.
+    pub depth: f32,
 }
 
 impl Default for DrawParam {
@@ -143,6 +156,7 @@ impl Default for DrawParam {
             color: Color::WHITE,
             transform: Transform::default(),
             z: 0,
+            depth: 0.,
         }
     }
 }
@@ -266,6 +280,16 @@ impl DrawParam {
         self.z = z;
         self
     }
+
+    /// Set the depth, from `0.0` (nearest) to `1.0` (farthest) with the default projection.
+    ///
+    /// Unlike [`DrawParam::z`], which orders draws as they're submitted, depth is only
+    /// compared per pixel when a [`DepthMode`](crate::graphics::DepthMode) is set on the canvas.
+    #[must_use]
+    pub fn depth(mut self, depth: f32) -> Self {
+        self.depth = depth;
+        self
+    }
 }
 
 /// Create a `DrawParam` from a location, like this:
@@ -339,7 +363,7 @@ impl DrawUniforms {
                 z: param.src.x + param.src.w,
                 w: param.src.y + param.src.h,
             },
-            transform: param.transform.to_bare_matrix(),
+            transform: param.transform.to_matrix_at_depth(param.depth),
         }
     }
 }
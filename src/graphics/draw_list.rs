@@ -0,0 +1,290 @@
+use crevice::std140::AsStd140;
+
+use super::{
+    canvas::{Draw, DrawCommand},
+    BlendMode, Canvas, Color, DepthMode, DrawParam, Drawable, GraphicsContext, Image,
+    InstanceArray, Mesh, Rect, Sampler, Shader, ShaderParams, StencilMode,
+};
+use crate::{context::Has, GameResult};
+
+#[derive(Debug, Clone)]
+struct Recorded {
+    command: DrawCommand,
+    // `None` to use the projection and scissor rect of the canvas the list is drawn to
+    projection: Option<mint::ColumnMatrix4<f32>>,
+    scissor_rect: Option<Rect>,
+}
+
+/// A recorded list of draws, with the canvas state they were made in, that can be drawn to a
+/// [`Canvas`] any number of times.
+///
+/// Draws are recorded with the same methods as on a canvas, and the list is then drawn like any
+/// other [`Drawable`]. This saves rebuilding things that rarely change, like a static background
+/// or UI chrome, every frame:
+///
+/// ```rust,no_run
+/// # use ggez::graphics::{self, Color, DrawList, DrawParam};
+/// # fn t(ctx: &mut ggez::Context, tiles: &graphics::Image) -> ggez::GameResult {
+/// let mut background = DrawList::new(ctx);
+/// for x in 0..100 {
+///     for y in 0..100 {
+///         background.draw(tiles, [x as f32 * 32., y as f32 * 32.]);
+///     }
+/// }
+///
+/// // every frame, scrolled by the camera
+/// let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
+/// canvas.draw(&background, DrawParam::new().dest([-120., -64.]));
+/// canvas.finish(ctx)
+/// # }
+/// ```
+///
+/// The `DrawParam` the list is drawn with transforms every draw in it after their own
+/// `DrawParam`s, before the projection. Its color tints every draw, and its z value is added to
+/// theirs; its source rect is ignored.
+///
+/// Shaders, samplers, blend, depth and stencil modes start at their defaults and are recorded.
+/// The projection and scissor rect are those of the canvas at the time the list is drawn, unless
+/// set on the list. Whether draws are culled is always up to the canvas (see
+/// [`Canvas::set_culling`]), and instance arrays are then culled instance by instance.
+///
+/// A `DrawList` is `Send`, so it can be created on the main thread, filled on a worker thread and
+/// sent back. Images, meshes and instance arrays are captured as they are when drawn into the list;
+/// later changes to an [`InstanceArray`] aren't picked up until it's drawn into the list again.
+#[derive(Debug)]
+pub struct DrawList {
+    recorder: Canvas,
+    commands: Vec<Recorded>,
+    projection: Option<mint::ColumnMatrix4<f32>>,
+    scissor_rect: Option<Rect>,
+}
+
+impl DrawList {
+    /// Creates an empty draw list.
+    pub fn new(gfx: &impl Has<GraphicsContext>) -> Self {
+        DrawList {
+            recorder: Canvas::for_recording(gfx),
+            commands: Vec::new(),
+            projection: None,
+            scissor_rect: None,
+        }
+    }
+
+    /// Records a drawable, as with [`Canvas::draw`].
+    pub fn draw(&mut self, drawable: &impl Drawable, param: impl Into<DrawParam>) {
+        drawable.draw(&mut self.recorder, param);
+        self.record();
+    }
+
+    /// Records a textured mesh, as with [`Canvas::draw_textured_mesh`].
+    pub fn draw_textured_mesh(&mut self, mesh: Mesh, image: Image, param: impl Into<DrawParam>) {
+        self.recorder.draw_textured_mesh(mesh, image, param);
+        self.record();
+    }
+
+    /// Records an instance array drawn with a mesh, as with [`Canvas::draw_instanced_mesh`].
+    pub fn draw_instanced_mesh(
+        &mut self,
+        mesh: Mesh,
+        instances: &InstanceArray,
+        param: impl Into<DrawParam>,
+    ) {
+        self.recorder.draw_instanced_mesh(mesh, instances, param);
+        self.record();
+    }
+
+    fn record(&mut self) {
+        let (projection, scissor_rect) = (self.projection, self.scissor_rect);
+        self.commands
+            .extend(self.recorder.take_commands().map(|command| Recorded {
+                command,
+                projection,
+                scissor_rect,
+            }));
+    }
+
+    /// Sets the shader of the following draws, see [`Canvas::set_shader`].
+    #[inline]
+    pub fn set_shader(&mut self, shader: &Shader) {
+        self.recorder.set_shader(shader);
+    }
+
+    /// Sets the shader parameters of the following draws, see [`Canvas::set_shader_params`].
+    #[inline]
+    pub fn set_shader_params<Uniforms: AsStd140>(&mut self, params: &ShaderParams<Uniforms>) {
+        self.recorder.set_shader_params(params);
+    }
+
+    /// Resets the shader of the following draws to the default shader.
+    #[inline]
+    pub fn set_default_shader(&mut self) {
+        self.recorder.set_default_shader();
+    }
+
+    /// Sets the text shader of the following draws, see [`Canvas::set_text_shader`].
+    #[inline]
+    pub fn set_text_shader(&mut self, shader: Shader) {
+        self.recorder.set_text_shader(shader);
+    }
+
+    /// Sets the text shader parameters of the following draws, see
+    /// [`Canvas::set_text_shader_params`].
+    #[inline]
+    pub fn set_text_shader_params<Uniforms: AsStd140>(
+        &mut self,
+        params: &ShaderParams<Uniforms>,
+    ) -> GameResult {
+        self.recorder.set_text_shader_params(params)
+    }
+
+    /// Resets the text shader of the following draws to the default text shader.
+    #[inline]
+    pub fn set_default_text_shader(&mut self) {
+        self.recorder.set_default_text_shader();
+    }
+
+    /// Sets the sampler of the following draws.
+    #[inline]
+    pub fn set_sampler(&mut self, sampler: impl Into<Sampler>) {
+        self.recorder.set_sampler(sampler);
+    }
+
+    /// Sets the blend mode of the following draws.
+    #[inline]
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.recorder.set_blend_mode(blend_mode);
+    }
+
+    /// Sets the depth mode of the following draws, see [`Canvas::set_depth_mode`].
+    #[inline]
+    pub fn set_depth_mode(&mut self, mode: DepthMode) {
+        self.recorder.set_depth_mode(mode);
+    }
+
+    /// Sets the stencil mode of the following draws, see [`Canvas::set_stencil_mode`].
+    #[inline]
+    pub fn set_stencil_mode(&mut self, mode: StencilMode) {
+        self.recorder.set_stencil_mode(mode);
+    }
+
+    /// Sets the projection of the following draws, replacing the one of the canvas the list is
+    /// drawn to.
+    #[inline]
+    pub fn set_projection(&mut self, proj: impl Into<mint::ColumnMatrix4<f32>>) {
+        self.projection = Some(proj.into());
+    }
+
+    /// Sets the projection of the following draws to show `rect`, see
+    /// [`Canvas::set_screen_coordinates`].
+    #[inline]
+    pub fn set_screen_coordinates(&mut self, rect: Rect) {
+        self.set_projection(super::internal_canvas::screen_to_mat(rect));
+    }
+
+    /// Makes the following draws use the projection of the canvas the list is drawn to.
+    #[inline]
+    pub fn set_default_projection(&mut self) {
+        self.projection = None;
+    }
+
+    /// Sets the scissor rect of the following draws, in pixels of the canvas the list is drawn to.
+    ///
+    /// The rect is clamped to the canvas image when drawn, and the draws are skipped if it
+    /// doesn't overlap the image at all.
+    #[inline]
+    pub fn set_scissor_rect(&mut self, rect: Rect) {
+        self.scissor_rect = Some(rect);
+    }
+
+    /// Makes the following draws use the scissor rect of the canvas the list is drawn to.
+    #[inline]
+    pub fn set_default_scissor_rect(&mut self) {
+        self.scissor_rect = None;
+    }
+
+    /// Removes every recorded draw. The state for the following draws is kept.
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Returns the number of recorded draw commands.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns `true` if nothing has been recorded.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+impl Drawable for DrawList {
+    fn draw(&self, canvas: &mut Canvas, param: impl Into<DrawParam>) {
+        let param = param.into();
+        let transform = glam::Mat4::from(param.transform.to_matrix_at_depth(param.depth));
+
+        for recorded in &self.commands {
+            let mut command = recorded.command.clone();
+
+            let scissor_rect = match recorded.scissor_rect {
+                Some(rect) => match canvas.clamp_scissor_rect(rect) {
+                    Some(scissor_rect) => scissor_rect,
+                    None => continue,
+                },
+                None => canvas.state().scissor_rect,
+            };
+            let projection = recorded
+                .projection
+                .unwrap_or_else(|| canvas.state().projection);
+
+            command.state.projection = (glam::Mat4::from(projection) * transform).into();
+            command.state.scissor_rect = scissor_rect;
+            command.state.culling = canvas.state().culling;
+
+            // instances are culled one by one, as when drawn to the canvas directly
+            if let (
+                true,
+                Draw::MeshInstances {
+                    mesh,
+                    instances,
+                    scale,
+                },
+            ) = (command.state.culling, &mut command.draw)
+            {
+                if let Some(recorded) = &instances.recorded {
+                    let viewport = canvas.viewport(&command.state);
+                    instances.visible =
+                        Some(recorded.visible(&viewport, mesh.bounds, &command.param, *scale));
+                }
+            }
+
+            let color = command.param.color;
+            command.param.color = Color::new(
+                color.r * param.color.r,
+                color.g * param.color.g,
+                color.b * param.color.b,
+                color.a * param.color.a,
+            );
+            command.param.z = command.param.z.saturating_add(param.z);
+
+            canvas.push_command(command);
+        }
+    }
+
+    fn dimensions(&self, _gfx: &impl Has<GraphicsContext>) -> Option<Rect> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<DrawList>();
+    }
+}
@@ -0,0 +1,226 @@
+use super::{
+    context::GraphicsContext,
+    gpu::arc::ArcBuffer,
+    image::{align_row, Image, ImageFormat},
+    Canvas, DrawParam, Drawable, Rect,
+};
+use crate::{
+    context::{Has, HasMut},
+    GameError, GameResult,
+};
+use std::num::NonZeroU64;
+
+#[derive(Debug)]
+struct PendingWrite {
+    offset: u64,
+    padded_row: u32,
+    origin: wgpu::Origin3d,
+    size: wgpu::Extent3d,
+}
+
+/// An [`Image`] that is updated often, one region at a time.
+///
+/// Region writes are collected on the CPU and uploaded together by [`DynamicTexture::flush`],
+/// through the same staging buffers the graphics context uses for its own per-frame uploads.
+/// This suits things like fog of war or a minimap, where a few tiles change every frame.
+///
+/// ```rust,no_run
+/// # use ggez::graphics::{self, DynamicTexture, Rect};
+/// # fn t(ctx: &mut ggez::Context, texture: &mut DynamicTexture) -> ggez::GameResult {
+/// // reveal a 16x16 tile
+/// texture.write(Rect::new(32., 48., 16., 16.), &[0; 16 * 16 * 4])?;
+/// texture.flush(ctx)?;
+///
+/// let mut canvas = graphics::Canvas::from_frame(ctx, None);
+/// canvas.draw(texture, graphics::DrawParam::default());
+/// canvas.finish(ctx)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DynamicTexture {
+    image: Image,
+    staged: Vec<u8>,
+    writes: Vec<PendingWrite>,
+    upload: Option<ArcBuffer>,
+}
+
+impl DynamicTexture {
+    /// Creates a new dynamic texture with undefined contents.
+    pub fn new(
+        gfx: &impl Has<GraphicsContext>,
+        format: ImageFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let gfx = gfx.retrieve();
+        let image = Image::new(
+            &gfx.wgpu,
+            format,
+            width,
+            height,
+            1,
+            wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        );
+        Self::wrap(image)
+    }
+
+    /// Turns an existing image into a dynamic texture, keeping its contents.
+    ///
+    /// Fails if the image can't be written to, or is multisampled.
+    pub fn from_image(image: Image) -> GameResult<Self> {
+        if !image
+            .texture
+            .usage()
+            .contains(wgpu::TextureUsages::COPY_DST)
+            || image.samples() > 1
+        {
+            return Err(GameError::RenderError(String::from(
+                "a dynamic texture needs an image that can be copied to and isn't multisampled",
+            )));
+        }
+        Ok(Self::wrap(image))
+    }
+
+    fn wrap(image: Image) -> Self {
+        DynamicTexture {
+            image,
+            staged: Vec::new(),
+            writes: Vec::new(),
+            upload: None,
+        }
+    }
+
+    /// Returns the image the writes end up in, e.g. for use in a shader.
+    #[inline]
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// Queues replacing the pixels inside `rect` with `pixels`, tightly packed rows in the image's format.
+    ///
+    /// Writes apply in the order they were made, so later writes win where regions overlap.
+    pub fn write(&mut self, rect: Rect, pixels: &[u8]) -> GameResult {
+        let (origin, size, row) = self.image.region(rect)?;
+        if pixels.len() != row as usize * size.height as usize {
+            return Err(GameError::RenderError(String::from(
+                "pixel data doesn't match the size of the region",
+            )));
+        }
+        if row == 0 || size.height == 0 {
+            return Ok(());
+        }
+
+        let padded_row = align_row(row);
+        let offset = self.staged.len() as u64;
+        for src in pixels.chunks_exact(row as usize) {
+            self.staged.extend_from_slice(src);
+            self.staged
+                .resize(self.staged.len() + (padded_row - row) as usize, 0);
+        }
+        self.writes.push(PendingWrite {
+            offset,
+            padded_row,
+            origin,
+            size,
+        });
+
+        Ok(())
+    }
+
+    /// Returns `true` if there are writes waiting for [`DynamicTexture::flush`].
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        !self.writes.is_empty()
+    }
+
+    /// Uploads all queued writes.
+    ///
+    /// During a frame the upload is recorded in order with canvases, so canvases finished
+    /// before this call still see the old pixels. Outside of a frame it's submitted right away.
+    pub fn flush(&mut self, gfx: &mut impl HasMut<GraphicsContext>) -> GameResult {
+        if self.writes.is_empty() {
+            return Ok(());
+        }
+        let gfx = gfx.retrieve_mut();
+        let size = self.staged.len() as u64;
+
+        let upload = match &self.upload {
+            Some(upload) if upload.size() >= size => upload.clone(),
+            _ => {
+                let upload =
+                    ArcBuffer::new(gfx.wgpu.device.create_buffer(&wgpu::BufferDescriptor {
+                        label: None,
+                        size: size.next_power_of_two(),
+                        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+                        mapped_at_creation: false,
+                    }));
+                self.upload = Some(upload.clone());
+                upload
+            }
+        };
+
+        let mut own_cmd = None;
+        let cmd = match &mut gfx.fcx {
+            Some(fcx) => &mut fcx.cmd,
+            None => own_cmd.insert(
+                gfx.wgpu
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor::default()),
+            ),
+        };
+
+        gfx.staging_belt
+            .write_buffer(
+                cmd,
+                &upload,
+                0,
+                NonZeroU64::new(size).unwrap(/* writes aren't empty */),
+                &gfx.wgpu.device,
+            )
+            .copy_from_slice(&self.staged);
+
+        for write in self.writes.drain(..) {
+            cmd.copy_buffer_to_texture(
+                wgpu::ImageCopyBuffer {
+                    buffer: &upload,
+                    layout: wgpu::ImageDataLayout {
+                        offset: write.offset,
+                        bytes_per_row: Some(write.padded_row),
+                        rows_per_image: None,
+                    },
+                },
+                wgpu::ImageCopyTexture {
+                    texture: &self.image.texture,
+                    mip_level: 0,
+                    origin: write.origin,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                write.size,
+            );
+        }
+
+        // the frame's staging belt is finished and recalled in `end_frame`
+        if let Some(cmd) = own_cmd {
+            gfx.staging_belt.finish();
+            let _ = gfx.wgpu.queue.submit([cmd.finish()]);
+            gfx.staging_belt.recall();
+        }
+
+        gfx.stats.bytes_written += size;
+        self.staged.clear();
+        Ok(())
+    }
+}
+
+impl Drawable for DynamicTexture {
+    fn draw(&self, canvas: &mut Canvas, param: impl Into<DrawParam>) {
+        self.image.draw(canvas, param)
+    }
+
+    fn dimensions(&self, gfx: &impl Has<GraphicsContext>) -> Option<Rect> {
+        self.image.dimensions(gfx)
+    }
+}
@@ -0,0 +1,73 @@
+//! Ready-made fragment shaders for common effects.
+//!
+//! Every effect is a parameter struct implementing [`Effect`], which knows the WGSL source
+//! of its shader. Compile the shader once with [`Effect::shader`] and pass the parameters
+//! through a [`ShaderParams`](super::ShaderParams), then either draw with them on a
+//! [`Canvas`](super::Canvas) or add them to a [`PostProcess`](super::PostProcess) chain:
+//!
+//! ```rust,no_run
+//! # use ggez::graphics::{self, effects::{Effect, Outline}, Color, ShaderParamsBuilder};
+//! # fn t(ctx: &mut ggez::Context, sprite: &graphics::Image) -> ggez::GameResult {
+//! let shader = Outline::shader(ctx)?;
+//! let params = ShaderParamsBuilder::new(&Outline::new(Color::YELLOW, 2.)).build(ctx);
+//!
+//! let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
+//! canvas.set_shader(&shader);
+//! canvas.set_shader_params(&params);
+//! canvas.draw(sprite, [100., 100.]);
+//! canvas.finish(ctx)
+//! # }
+//! ```
+//!
+//! The sprite effects ([`DropShadow`], [`Outline`] and [`Glow`]) can only draw inside the
+//! quad of the sprite, so the image needs transparent padding around it for the effect to
+//! show. With a sprite sheet they also sample neighbouring frames, which should be kept
+//! apart by at least the size of the effect.
+//!
+//! The screen effects ([`Crt`] and [`Vignette`]) and the [`ChromaticAberration`] center
+//! work in texture coordinates, so they're meant for whole images such as a frame being
+//! post-processed.
+//!
+//! Bloom is a combination of passes: [`Bloom`] keeps only the bright parts of the image,
+//! two [`GaussianBlur`] passes spread them out, and the result is drawn over the original
+//! image with [`BlendMode::ADD`](super::BlendMode::ADD).
+
+use super::{Color, GraphicsContext, Shader, ShaderBuilder};
+use crate::{context::Has, GameResult};
+use crevice::std140::AsStd140;
+
+/// A parameter struct with a matching fragment shader.
+pub trait Effect: AsStd140 {
+    /// The WGSL source of the fragment shader, with the parameters bound to group 3.
+    const SOURCE: &'static str;
+
+    /// Compiles the fragment shader of the effect.
+    ///
+    /// This compiles a new shader every time, so it should be called once and the result
+    /// kept around.
+    fn shader(gfx: &impl Has<GraphicsContext>) -> GameResult<Shader> {
+        ShaderBuilder::new().fragment_code(Self::SOURCE).build(gfx)
+    }
+}
+
+macro_rules! effect {
+    ($name:ty, $file:literal) => {
+        impl Effect for $name {
+            const SOURCE: &'static str = include_str!(concat!("../shader/effects/", $file));
+        }
+    };
+}
+
+// the parameter structs are re-exported one by one, so that the undocumented `Std140*`
+// counterparts generated by the `AsStd140` derive stay private
+mod params;
+
+pub use params::{
+    Bloom, ChromaticAberration, ColorMatrix, Crt, Dissolve, DropShadow, GaussianBlur, Glow,
+    Grayscale, LutGrading, Outline, Pixelate, Sepia, Vignette,
+};
+
+fn color(color: Color) -> mint::Vector4<f32> {
+    let [x, y, z, w]: [f32; 4] = color.into();
+    mint::Vector4 { x, y, z, w }
+}
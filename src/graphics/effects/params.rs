@@ -0,0 +1,480 @@
+use super::{color, Effect};
+use crate::graphics::Color;
+use crevice::std140::AsStd140;
+
+/// One direction of a separable Gaussian blur.
+///
+/// A full blur takes two passes, one [`GaussianBlur::horizontal`] and one
+/// [`GaussianBlur::vertical`].
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct GaussianBlur {
+    /// Direction of the blur, in texels per sample.
+    pub direction: mint::Vector2<f32>,
+    /// Standard deviation of the blur, in samples. At most `64 / 3` is used.
+    pub sigma: f32,
+}
+
+impl GaussianBlur {
+    /// Blurs along the x axis.
+    pub fn horizontal(sigma: f32) -> Self {
+        GaussianBlur {
+            direction: mint::Vector2 { x: 1., y: 0. },
+            sigma,
+        }
+    }
+
+    /// Blurs along the y axis.
+    pub fn vertical(sigma: f32) -> Self {
+        GaussianBlur {
+            direction: mint::Vector2 { x: 0., y: 1. },
+            sigma,
+        }
+    }
+}
+
+impl Default for GaussianBlur {
+    fn default() -> Self {
+        Self::horizontal(2.)
+    }
+}
+
+effect!(GaussianBlur, "gaussian_blur.wgsl");
+
+/// Keeps the parts of the image brighter than a threshold, as the first step of bloom.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct Bloom {
+    /// Brightness, from the brightest color channel, above which pixels bloom.
+    pub threshold: f32,
+    /// Width of the soft transition around the threshold.
+    pub knee: f32,
+    /// Multiplier of the kept colors.
+    pub intensity: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            threshold: 0.8,
+            knee: 0.2,
+            intensity: 1.,
+        }
+    }
+}
+
+effect!(Bloom, "bloom.wgsl");
+
+/// Draws a sprite over a softened, offset silhouette of itself.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct DropShadow {
+    /// Color of the shadow.
+    pub color: mint::Vector4<f32>,
+    /// Offset of the shadow, in texels.
+    pub offset: mint::Vector2<f32>,
+    /// Radius of the box blur of the shadow, in texels. At most 8 is used.
+    pub softness: f32,
+}
+
+impl DropShadow {
+    /// Creates a drop shadow.
+    pub fn new(color: Color, offset: impl Into<mint::Vector2<f32>>, softness: f32) -> Self {
+        DropShadow {
+            color: self::color(color),
+            offset: offset.into(),
+            softness,
+        }
+    }
+}
+
+impl Default for DropShadow {
+    fn default() -> Self {
+        Self::new(Color::new(0., 0., 0., 0.5), [4., 4.], 2.)
+    }
+}
+
+effect!(DropShadow, "drop_shadow.wgsl");
+
+/// Draws a solid outline around the opaque parts of a sprite.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct Outline {
+    /// Color of the outline.
+    pub color: mint::Vector4<f32>,
+    /// Thickness of the outline, in texels.
+    pub thickness: f32,
+}
+
+impl Outline {
+    /// Creates an outline.
+    pub fn new(color: Color, thickness: f32) -> Self {
+        Outline {
+            color: self::color(color),
+            thickness,
+        }
+    }
+}
+
+impl Default for Outline {
+    fn default() -> Self {
+        Self::new(Color::WHITE, 1.)
+    }
+}
+
+effect!(Outline, "outline.wgsl");
+
+/// Draws a soft halo around the opaque parts of a sprite.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct Glow {
+    /// Color of the glow.
+    pub color: mint::Vector4<f32>,
+    /// How far the glow reaches, in texels.
+    pub radius: f32,
+    /// Multiplier of the glow's opacity.
+    pub intensity: f32,
+}
+
+impl Glow {
+    /// Creates a glow.
+    pub fn new(color: Color, radius: f32, intensity: f32) -> Self {
+        Glow {
+            color: self::color(color),
+            radius,
+            intensity,
+        }
+    }
+}
+
+impl Default for Glow {
+    fn default() -> Self {
+        Self::new(Color::WHITE, 8., 2.)
+    }
+}
+
+effect!(Glow, "glow.wgsl");
+
+/// Desaturates colors by their luminance.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct Grayscale {
+    /// How much to desaturate, from `0.0` for the original colors to `1.0` for gray.
+    pub amount: f32,
+}
+
+impl Default for Grayscale {
+    fn default() -> Self {
+        Grayscale { amount: 1. }
+    }
+}
+
+effect!(Grayscale, "grayscale.wgsl");
+
+/// Tints colors like an old photograph.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct Sepia {
+    /// How much to tint, from `0.0` for the original colors to `1.0` for full sepia.
+    pub amount: f32,
+}
+
+impl Default for Sepia {
+    fn default() -> Self {
+        Sepia { amount: 1. }
+    }
+}
+
+effect!(Sepia, "sepia.wgsl");
+
+/// Transforms colors by a matrix, as `matrix * rgba + offset`.
+///
+/// The constructors make the common adjustments, and [`ColorMatrix::then`] chains them
+/// into a single pass.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct ColorMatrix {
+    /// The matrix multiplying the color.
+    pub matrix: mint::ColumnMatrix4<f32>,
+    /// The color added afterwards.
+    pub offset: mint::Vector4<f32>,
+}
+
+impl ColorMatrix {
+    /// Leaves colors unchanged.
+    pub fn identity() -> Self {
+        Self::from_glam(glam::Mat4::IDENTITY, glam::Vec4::ZERO)
+    }
+
+    /// Adds `amount` to the color channels.
+    pub fn brightness(amount: f32) -> Self {
+        Self::from_glam(glam::Mat4::IDENTITY, glam::vec4(amount, amount, amount, 0.))
+    }
+
+    /// Scales the color channels away from mid-gray; `1.0` leaves them unchanged.
+    pub fn contrast(amount: f32) -> Self {
+        let shift = 0.5 * (1. - amount);
+        Self::from_glam(
+            glam::Mat4::from_diagonal(glam::vec4(amount, amount, amount, 1.)),
+            glam::vec4(shift, shift, shift, 0.),
+        )
+    }
+
+    /// Scales the saturation; `0.0` is grayscale and `1.0` leaves colors unchanged.
+    pub fn saturation(amount: f32) -> Self {
+        let gray = glam::vec3(0.2126, 0.7152, 0.0722) * (1. - amount);
+        Self::from_glam(
+            glam::Mat4::from_cols(
+                glam::vec4(gray.x + amount, gray.x, gray.x, 0.),
+                glam::vec4(gray.y, gray.y + amount, gray.y, 0.),
+                glam::vec4(gray.z, gray.z, gray.z + amount, 0.),
+                glam::Vec4::W,
+            ),
+            glam::Vec4::ZERO,
+        )
+    }
+
+    /// Rotates the hue by `angle` radians, keeping the luminance.
+    pub fn hue_rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::from_glam(
+            glam::Mat4::from_cols(
+                glam::vec4(
+                    0.213 + cos * 0.787 - sin * 0.213,
+                    0.213 - cos * 0.213 + sin * 0.143,
+                    0.213 - cos * 0.213 - sin * 0.787,
+                    0.,
+                ),
+                glam::vec4(
+                    0.715 - cos * 0.715 - sin * 0.715,
+                    0.715 + cos * 0.285 + sin * 0.140,
+                    0.715 - cos * 0.715 + sin * 0.715,
+                    0.,
+                ),
+                glam::vec4(
+                    0.072 - cos * 0.072 + sin * 0.928,
+                    0.072 - cos * 0.072 - sin * 0.283,
+                    0.072 + cos * 0.928 + sin * 0.072,
+                    0.,
+                ),
+                glam::Vec4::W,
+            ),
+            glam::Vec4::ZERO,
+        )
+    }
+
+    /// Returns the transform applying `self` and then `next`.
+    #[must_use]
+    pub fn then(self, next: ColorMatrix) -> Self {
+        let (m, o) = self.to_glam();
+        let (n, p) = next.to_glam();
+        Self::from_glam(n * m, n * o + p)
+    }
+
+    /// Applies the transform to a color, like the shader does.
+    pub fn apply(&self, color: Color) -> Color {
+        let (m, o) = self.to_glam();
+        let c: [f32; 4] = color.into();
+        let out = (m * glam::Vec4::from(c) + o).clamp(glam::Vec4::ZERO, glam::Vec4::ONE);
+        Color::new(out.x, out.y, out.z, out.w)
+    }
+
+    fn from_glam(matrix: glam::Mat4, offset: glam::Vec4) -> Self {
+        ColorMatrix {
+            matrix: matrix.into(),
+            offset: offset.into(),
+        }
+    }
+
+    fn to_glam(self) -> (glam::Mat4, glam::Vec4) {
+        (self.matrix.into(), self.offset.into())
+    }
+}
+
+impl Default for ColorMatrix {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+effect!(ColorMatrix, "color_matrix.wgsl");
+
+/// Grades colors through a lookup table image.
+///
+/// The table is passed as the first image of the shader parameters, with a sampler, i.e.
+/// `ShaderParamsBuilder::new(&grading).images(&[&lut], &[Sampler::linear_clamp()], false)`.
+/// It's a strip of `size` square tiles of `size` by `size` texels, red increasing to the
+/// right and green downwards inside each tile, and blue increasing from tile to tile. An
+/// unmodified table leaves colors unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct LutGrading {
+    /// Number of entries per color channel, e.g. `16.0` for a 256x16 image.
+    pub size: f32,
+    /// How much to grade, from `0.0` for the original colors to `1.0` for the table's.
+    pub amount: f32,
+}
+
+impl LutGrading {
+    /// Creates a full grading through a table with `size` entries per channel.
+    pub fn new(size: u32) -> Self {
+        LutGrading {
+            size: size as f32,
+            amount: 1.,
+        }
+    }
+}
+
+impl Default for LutGrading {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
+
+effect!(LutGrading, "lut.wgsl");
+
+/// Imitates an old CRT screen with a curved picture, scanlines and dark edges.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct Crt {
+    /// How much the picture bulges outwards.
+    pub curvature: f32,
+    /// Darkness of the gaps between scanlines, from `0.0` to `1.0`.
+    pub scanline_intensity: f32,
+    /// Number of scanlines over the height of the image.
+    pub scanline_count: f32,
+    /// Darkness of the edges, from `0.0` to `1.0`.
+    pub vignette: f32,
+}
+
+impl Default for Crt {
+    fn default() -> Self {
+        Crt {
+            curvature: 0.1,
+            scanline_intensity: 0.25,
+            scanline_count: 240.,
+            vignette: 0.5,
+        }
+    }
+}
+
+effect!(Crt, "crt.wgsl");
+
+/// Shows the image as large blocks of color.
+///
+/// Combine with [`Sampler::nearest_clamp`](super::Sampler::nearest_clamp) for hard edges.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct Pixelate {
+    /// Size of the blocks, in texels.
+    pub size: f32,
+}
+
+impl Default for Pixelate {
+    fn default() -> Self {
+        Pixelate { size: 4. }
+    }
+}
+
+effect!(Pixelate, "pixelate.wgsl");
+
+/// Splits the red and blue channels apart towards the edges, like a cheap lens.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct ChromaticAberration {
+    /// Point without aberration, in texture coordinates.
+    pub center: mint::Vector2<f32>,
+    /// Distance between the channels at the edges, in texels.
+    pub amount: f32,
+}
+
+impl Default for ChromaticAberration {
+    fn default() -> Self {
+        ChromaticAberration {
+            center: mint::Vector2 { x: 0.5, y: 0.5 },
+            amount: 3.,
+        }
+    }
+}
+
+effect!(ChromaticAberration, "chromatic_aberration.wgsl");
+
+/// Fades the image towards a color away from its center.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct Vignette {
+    /// Color at the corners.
+    pub color: mint::Vector4<f32>,
+    /// Distance from the center where the fade starts, where `1.0` is a corner.
+    pub radius: f32,
+    /// Length of the fade.
+    pub softness: f32,
+}
+
+impl Vignette {
+    /// Creates a vignette.
+    pub fn new(color: Color, radius: f32, softness: f32) -> Self {
+        Vignette {
+            color: self::color(color),
+            radius,
+            softness,
+        }
+    }
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Self::new(Color::BLACK, 0.5, 0.5)
+    }
+}
+
+effect!(Vignette, "vignette.wgsl");
+
+/// Burns a sprite away following a noise texture, with a glowing edge.
+///
+/// The noise is passed as the first image of the shader parameters, with a sampler.
+/// Pixels whose noise, in the red channel, is below the threshold disappear, so raising
+/// the threshold from `0.0` to `1.0` dissolves the whole sprite.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct Dissolve {
+    /// Color of the edge around the dissolved parts.
+    pub edge_color: mint::Vector4<f32>,
+    /// Noise value below which pixels disappear.
+    pub threshold: f32,
+    /// Width of the edge, in noise values.
+    pub edge_width: f32,
+}
+
+impl Dissolve {
+    /// Creates a dissolve at `threshold`.
+    pub fn new(edge_color: Color, threshold: f32, edge_width: f32) -> Self {
+        Dissolve {
+            edge_color: self::color(edge_color),
+            threshold,
+            edge_width,
+        }
+    }
+}
+
+impl Default for Dissolve {
+    fn default() -> Self {
+        Self::new(Color::new(1., 0.5, 0., 1.), 0., 0.05)
+    }
+}
+
+effect!(Dissolve, "dissolve.wgsl");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Color, b: Color) -> bool {
+        let (a, b): ([f32; 4], [f32; 4]) = (a.into(), b.into());
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-4)
+    }
+
+    #[test]
+    fn color_matrices() {
+        let color = Color::new(0.2, 0.4, 0.6, 0.8);
+        assert!(close(ColorMatrix::identity().apply(color), color));
+        assert!(close(ColorMatrix::saturation(1.).apply(color), color));
+        assert!(close(ColorMatrix::hue_rotate(0.).apply(color), color));
+
+        let gray = ColorMatrix::saturation(0.).apply(color);
+        assert!((gray.r - gray.g).abs() < 1e-4 && (gray.g - gray.b).abs() < 1e-4);
+
+        let first = ColorMatrix::contrast(1.5);
+        let second = ColorMatrix::brightness(0.1);
+        assert!(close(
+            first.then(second).apply(color),
+            second.apply(first.apply(color))
+        ));
+    }
+}
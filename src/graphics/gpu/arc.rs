@@ -1,10 +1,50 @@
-use std::sync::{
-    atomic::{AtomicU64, Ordering::SeqCst},
-    Arc,
+use std::{
+    any::TypeId,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering::SeqCst},
+        Arc,
+    },
 };
 
 static NEXT_ID: AtomicU64 = AtomicU64::new(0);
 
+static LIVE_BUFFERS: AtomicUsize = AtomicUsize::new(0);
+static LIVE_TEXTURES: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the number of [`ArcBuffer`]s currently alive.
+pub fn live_buffers() -> usize {
+    LIVE_BUFFERS.load(SeqCst)
+}
+
+/// Returns the number of [`ArcTexture`]s currently alive.
+pub fn live_textures() -> usize {
+    LIVE_TEXTURES.load(SeqCst)
+}
+
+/// Decrements a live resource counter once the last handle to the resource is dropped.
+#[derive(Debug)]
+struct LiveGuard(&'static AtomicUsize);
+
+impl LiveGuard {
+    fn new<T: 'static>() -> Option<Arc<Self>> {
+        let counter = if TypeId::of::<T>() == TypeId::of::<wgpu::Buffer>() {
+            &LIVE_BUFFERS
+        } else if TypeId::of::<T>() == TypeId::of::<wgpu::Texture>() {
+            &LIVE_TEXTURES
+        } else {
+            return None;
+        };
+        let _ = counter.fetch_add(1, SeqCst);
+        Some(Arc::new(LiveGuard(counter)))
+    }
+}
+
+impl Drop for LiveGuard {
+    fn drop(&mut self) {
+        let _ = self.0.fetch_sub(1, SeqCst);
+    }
+}
+
 /// Arc'd WGPU handles are used widely across the graphics module.
 ///
 /// Beyond allowing for Clone, they also allow different GPU resources to be
@@ -13,6 +53,7 @@ static NEXT_ID: AtomicU64 = AtomicU64::new(0);
 pub struct ArcHandle<T: 'static> {
     pub handle: Arc<T>,
     id: u64,
+    live: Option<Arc<LiveGuard>>,
 }
 
 impl<T: 'static> ArcHandle<T> {
@@ -20,6 +61,7 @@ impl<T: 'static> ArcHandle<T> {
         ArcHandle {
             handle: Arc::new(handle),
             id: NEXT_ID.fetch_add(1, SeqCst),
+            live: LiveGuard::new::<T>(),
         }
     }
 
@@ -34,6 +76,7 @@ impl<T: 'static> Clone for ArcHandle<T> {
         ArcHandle {
             handle: Arc::clone(&self.handle),
             id: self.id,
+            live: self.live.clone(),
         }
     }
 }
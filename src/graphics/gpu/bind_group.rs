@@ -1,6 +1,9 @@
 use super::arc::{ArcBindGroup, ArcBindGroupLayout, ArcBuffer, ArcSampler, ArcTextureView};
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap},
+    collections::{
+        hash_map::{DefaultHasher, Entry},
+        HashMap,
+    },
     hash::{Hash, Hasher},
     num::NonZeroU64,
 };
@@ -187,17 +190,24 @@ impl<'a> BindGroupBuilder<'a> {
     ) -> (ArcBindGroup, ArcBindGroupLayout) {
         let layout = self.layout.create(device, cache);
 
-        let group = cache
-            .groups
-            .entry(self.key)
-            .or_insert_with(|| {
-                ArcBindGroup::new(device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    label: None,
-                    layout: layout.as_ref(),
-                    entries: &self.entries,
-                }))
-            })
-            .clone();
+        let group = match cache.groups.entry(self.key) {
+            Entry::Occupied(entry) => {
+                cache.hits += 1;
+                entry.get().clone()
+            }
+            Entry::Vacant(entry) => {
+                cache.misses += 1;
+                entry
+                    .insert(ArcBindGroup::new(device.create_bind_group(
+                        &wgpu::BindGroupDescriptor {
+                            label: None,
+                            layout: layout.as_ref(),
+                            entries: &self.entries,
+                        },
+                    )))
+                    .clone()
+            }
+        };
 
         (group, layout)
     }
@@ -223,6 +233,9 @@ impl<'a> BindGroupBuilder<'a> {
 pub struct BindGroupCache {
     layouts: HashMap<(Vec<wgpu::BindGroupLayoutEntry>, u64), ArcBindGroupLayout>,
     groups: HashMap<Vec<BindGroupEntryKey>, ArcBindGroup>,
+    // lookups into `groups` since the last call to `take_stats`
+    hits: usize,
+    misses: usize,
 }
 
 impl BindGroupCache {
@@ -230,6 +243,16 @@ impl BindGroupCache {
         BindGroupCache {
             layouts: HashMap::new(),
             groups: HashMap::new(),
+            hits: 0,
+            misses: 0,
         }
     }
+
+    /// Returns the number of bind group cache (hits, misses) and resets both counters.
+    pub fn take_stats(&mut self) -> (usize, usize) {
+        let stats = (self.hits, self.misses);
+        self.hits = 0;
+        self.misses = 0;
+        stats
+    }
 }
@@ -1,5 +1,7 @@
 pub mod arc;
 pub mod bind_group;
 pub mod growing;
+pub mod mipmap;
 pub mod pipeline;
+pub mod readback;
 pub mod text;
@@ -1,6 +1,9 @@
 use super::arc::{ArcBindGroupLayout, ArcPipelineLayout, ArcRenderPipeline, ArcShaderModule};
 use std::collections::{hash_map::DefaultHasher, HashMap};
 
+/// Format of the depth-stencil attachments of canvases.
+pub const DEPTH_STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+
 /// Hashable representation of a render pipeline, used as a key in the HashMap cache.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RenderPipelineInfo {
@@ -11,7 +14,8 @@ pub struct RenderPipelineInfo {
     pub samples: u32,
     pub format: wgpu::TextureFormat,
     pub blend: Option<wgpu::BlendState>,
-    pub depth: bool,
+    pub depth_stencil: Option<wgpu::DepthStencilState>,
+    pub write_mask: wgpu::ColorWrites,
     pub vertices: bool,
     pub topology: wgpu::PrimitiveTopology,
     pub vertex_layout: wgpu::VertexBufferLayout<'static>,
@@ -61,17 +65,7 @@ impl PipelineCache {
                             polygon_mode: wgpu::PolygonMode::Fill,
                             conservative: false,
                         },
-                        depth_stencil: if info.depth {
-                            Some(wgpu::DepthStencilState {
-                                format: wgpu::TextureFormat::Depth32Float,
-                                depth_write_enabled: true,
-                                depth_compare: wgpu::CompareFunction::Always,
-                                stencil: Default::default(),
-                                bias: Default::default(),
-                            })
-                        } else {
-                            None
-                        },
+                        depth_stencil: info.depth_stencil.clone(),
                         multisample: wgpu::MultisampleState {
                             count: info.samples,
                             mask: !0,
@@ -83,7 +77,7 @@ impl PipelineCache {
                             targets: &[Some(wgpu::ColorTargetState {
                                 format: info.format,
                                 blend: info.blend,
-                                write_mask: wgpu::ColorWrites::ALL,
+                                write_mask: info.write_mask,
                             })],
                         }),
                         multiview: None,
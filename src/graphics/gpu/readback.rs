@@ -0,0 +1,49 @@
+use super::arc::ArcBuffer;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Mappable buffers used to read images back from the GPU, kept around for reuse.
+///
+/// Cloning the pool is cheap; all clones share the same buffers.
+#[derive(Debug, Clone, Default)]
+pub struct ReadbackPool {
+    buffers: Arc<Mutex<Vec<ArcBuffer>>>,
+}
+
+impl ReadbackPool {
+    const MAX_BUFFERS: usize = 4;
+
+    /// Returns an unmapped buffer of at least `size` bytes, creating one if none is free.
+    pub fn get(&self, device: &wgpu::Device, size: u64) -> ArcBuffer {
+        let mut buffers = self.buffers.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let best = buffers
+            .iter()
+            .enumerate()
+            .filter(|(_, buffer)| buffer.size() >= size)
+            .min_by_key(|(_, buffer)| buffer.size())
+            .map(|(i, _)| i);
+
+        match best {
+            Some(i) => buffers.swap_remove(i),
+            None => ArcBuffer::new(device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            })),
+        }
+    }
+
+    /// Gives an unmapped buffer back to the pool.
+    pub fn put(&self, buffer: ArcBuffer) {
+        let mut buffers = self.buffers.lock().unwrap_or_else(PoisonError::into_inner);
+        if buffers.len() < Self::MAX_BUFFERS {
+            buffers.push(buffer);
+        } else if let Some(smallest) = buffers.iter_mut().min_by_key(|b| b.size()) {
+            // prefer keeping the larger buffers, they can serve more requests
+            if smallest.size() < buffer.size() {
+                *smallest = buffer;
+            }
+        }
+    }
+}
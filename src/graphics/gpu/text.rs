@@ -3,7 +3,7 @@ use super::{
     bind_group::BindGroupBuilder,
     growing::GrowingBufferArena,
 };
-use crate::graphics::{context::FrameArenas, LinearColor};
+use crate::graphics::{context::FrameArenas, stats::FrameStats, LinearColor};
 use crevice::std140::AsStd140;
 use glyph_brush::{GlyphBrush, GlyphBrushBuilder};
 use ordered_float::OrderedFloat;
@@ -101,6 +101,7 @@ impl TextRenderer {
         queue: &wgpu::Queue,
         arenas: &'a FrameArenas,
         pass: &mut wgpu::RenderPass<'a>,
+        stats: &mut FrameStats,
     ) {
         let res = self.glyph_brush.borrow_mut().process_queued(
             |rect, pixels| {
@@ -168,6 +169,12 @@ impl TextRenderer {
                 // Also note that vertex data is stepped PER INSTANCE.
                 // Therefore we only store ONE VERTEX for ONE GLYPH (and in the vertex shader we generate the quad vertices on the fly).
                 pass.draw(0..4, 0..verts.len() as u32);
+
+                stats.draw_calls += 1;
+                stats.vertices += 4 * verts.len();
+                stats.instances += verts.len();
+                stats.glyphs += verts.len();
+                stats.bytes_written += verts_size as u64;
             }
             Err(glyph_brush::BrushError::TextureTooSmall { suggested }) => {
                 // increase texture size as recommended by glyph_brush
@@ -206,7 +213,7 @@ impl TextRenderer {
                         entries: cache_bind.entries(),
                     }));
 
-                self.draw_queued(device, queue, arenas, pass)
+                self.draw_queued(device, queue, arenas, pass, stats)
             }
             _ => unreachable!(),
         }
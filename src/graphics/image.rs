@@ -1,19 +1,28 @@
 use super::{
+    compressed::TextureContainer,
     context::GraphicsContext,
     gpu::{
-        arc::{ArcBindGroup, ArcSampler, ArcTexture, ArcTextureView},
+        arc::{ArcBindGroup, ArcBuffer, ArcSampler, ArcTexture, ArcTextureView},
         bind_group::BindGroupBuilder,
+        mipmap::full_mip_chain,
+        readback::ReadbackPool,
     },
-    Canvas, Color, Draw, DrawParam, Drawable, Rect, WgpuContext,
+    Canvas, Color, Draw, DrawParam, Drawable, ImageData, LinearColor, Rect, WgpuContext,
 };
-use crate::{context::Has, Context, GameError, GameResult};
-use image::ImageEncoder;
+use crate::{
+    context::{Has, HasMut},
+    GameError, GameResult,
+};
+use ::image::DynamicImage;
 use std::{
     collections::BTreeMap,
     fs::File,
-    io::Read,
+    future::Future,
+    io::{Read, Write},
     path::Path,
-    sync::{Arc, RwLock},
+    pin::Pin,
+    sync::{mpsc, Arc, RwLock},
+    task::{Context as TaskContext, Poll},
 };
 
 // maintaing a massive enum of all possible texture formats?
@@ -24,6 +33,150 @@ pub type ImageFormat = wgpu::TextureFormat;
 /// Describes the format of an encoded image.
 pub type ImageEncodingFormat = ::image::ImageFormat;
 
+/// Options for decoding images with [`Image::from_path_with_options`] and
+/// [`Image::from_bytes_with_options`].
+///
+/// By default images are decoded to `Rgba8UnormSrgb`, like [`Image::from_bytes`] does.
+///
+/// ```rust,no_run
+/// # use ggez::graphics::{Image, ImageLoadOptions};
+/// # fn t(ctx: &mut ggez::Context) -> ggez::GameResult {
+/// let map = Image::from_path_with_options(ctx, "/map.png", ImageLoadOptions::default().mipmaps(true))?;
+/// let normals = Image::from_path_with_options(ctx, "/normals.png", ImageLoadOptions::data())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, smart_default::SmartDefault)]
+pub struct ImageLoadOptions {
+    /// Whether the color channels are sRGB encoded, as is usual for pictures.
+    ///
+    /// Turn this off for normal maps, lookup tables and other data that must be sampled as is.
+    #[default = true]
+    pub srgb: bool,
+    /// Whether to multiply the color channels by alpha, for drawing with [`BlendMode::PREMULTIPLIED`](super::BlendMode::PREMULTIPLIED).
+    pub premultiply: bool,
+    /// Whether to keep grayscale images without alpha as single channel `R8Unorm` images, or
+    /// `R16Float` ones for 16-bit sources with [`high_precision`](Self::high_precision).
+    ///
+    /// Those take a quarter of the memory, but are always linear, and the default shader
+    /// draws them in shades of red; they are meant to be read by custom shaders.
+    pub single_channel: bool,
+    /// Whether to keep 16-bit and floating point (e.g. HDR or EXR) sources as `Rgba16Float` images
+    /// instead of reducing them to 8 bits. sRGB encoded 16-bit sources are converted to linear.
+    pub high_precision: bool,
+    /// Whether to generate a full chain of mip levels, i.e. successively halved copies of
+    /// the image that the GPU samples from when the image is drawn scaled down.
+    ///
+    /// This avoids shimmering and aliasing when zooming out, at the cost of a third more memory.
+    /// Images with mip levels can't be drawn to by a [`Canvas`].
+    pub mipmaps: bool,
+}
+
+impl ImageLoadOptions {
+    /// Options for images holding data rather than pictures: linear, and at their source precision.
+    pub fn data() -> Self {
+        ImageLoadOptions {
+            srgb: false,
+            single_channel: true,
+            high_precision: true,
+            ..Default::default()
+        }
+    }
+
+    /// Sets whether the color channels are sRGB encoded.
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// Sets whether to premultiply alpha.
+    pub fn premultiply(mut self, premultiply: bool) -> Self {
+        self.premultiply = premultiply;
+        self
+    }
+
+    /// Sets whether to keep grayscale images as a single channel.
+    pub fn single_channel(mut self, single_channel: bool) -> Self {
+        self.single_channel = single_channel;
+        self
+    }
+
+    /// Sets whether to keep 16-bit and floating point sources at a higher precision.
+    pub fn high_precision(mut self, high_precision: bool) -> Self {
+        self.high_precision = high_precision;
+        self
+    }
+
+    /// Sets whether to generate mip levels.
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    /// Converts a decoded image into pixels of the GPU format chosen by these options.
+    pub(crate) fn convert(&self, decoded: DynamicImage) -> GameResult<(Vec<u8>, ImageFormat)> {
+        use ::image::ColorType;
+
+        let color = decoded.color();
+        if self.single_channel && color == ColorType::L16 && self.high_precision {
+            let pixels = decoded
+                .into_luma16()
+                .into_raw()
+                .into_iter()
+                .flat_map(|l| f32_to_f16(f32::from(l) / f32::from(u16::MAX)).to_le_bytes())
+                .collect();
+            return Ok((pixels, ImageFormat::R16Float));
+        }
+        if self.single_channel && matches!(color, ColorType::L8 | ColorType::L16) {
+            return Ok((decoded.into_luma8().into_raw(), ImageFormat::R8Unorm));
+        }
+
+        let wide = matches!(
+            color,
+            ColorType::L16 | ColorType::La16 | ColorType::Rgb16 | ColorType::Rgba16
+        );
+        let float = matches!(color, ColorType::Rgb32F | ColorType::Rgba32F);
+        if self.high_precision && (wide || float) {
+            let linearize = self.srgb && wide;
+            let pixels = decoded
+                .into_rgba32f()
+                .pixels()
+                .flat_map(|px| {
+                    let [r, g, b, a] = px.0;
+                    let c = if linearize {
+                        LinearColor::from(Color::new(r, g, b, a))
+                    } else {
+                        LinearColor { r, g, b, a }
+                    };
+                    let m = if self.premultiply { c.a } else { 1. };
+                    [c.r * m, c.g * m, c.b * m, c.a]
+                })
+                .flat_map(|c| f32_to_f16(c).to_le_bytes())
+                .collect();
+            return Ok((pixels, ImageFormat::Rgba16Float));
+        }
+
+        let format = if self.srgb {
+            ImageFormat::Rgba8UnormSrgb
+        } else {
+            ImageFormat::Rgba8Unorm
+        };
+        let rgba8 = decoded.into_rgba8();
+        if self.premultiply {
+            let mut data =
+                ImageData::from_pixels(rgba8.width(), rgba8.height(), format, rgba8.into_raw())?;
+            data.premultiply();
+            Ok((data.into_pixels(), format))
+        } else {
+            Ok((rgba8.into_raw(), format))
+        }
+    }
+}
+
+/// Describes the format of an encoded image together with format specific settings,
+/// such as [`ImageOutputFormat::Jpeg`]'s quality. Every [`ImageEncodingFormat`] converts into one.
+pub type ImageOutputFormat = ::image::ImageOutputFormat;
+
 /// Handle to an image stored in GPU memory.
 #[derive(Debug, Clone)]
 pub struct Image {
@@ -54,7 +207,8 @@ impl Image {
             samples,
             wgpu::TextureUsages::RENDER_ATTACHMENT
                 | wgpu::TextureUsages::TEXTURE_BINDING
-                | wgpu::TextureUsages::COPY_SRC,
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
         )
     }
 
@@ -102,17 +256,127 @@ impl Image {
         width: u32,
         height: u32,
     ) -> Self {
-        let image = Self::new(
+        Self::upload(
             wgpu,
+            pixels,
+            format,
+            width,
+            height,
+            1,
+            wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        )
+    }
+
+    /// Creates a new image with a full mip chain, rendered on the GPU from the given pixel data.
+    ///
+    /// Fails if the format can't be both rendered to and filtered.
+    pub(crate) fn from_pixels_mipmapped(
+        gfx: &GraphicsContext,
+        pixels: &[u8],
+        format: ImageFormat,
+        width: u32,
+        height: u32,
+    ) -> GameResult<Self> {
+        let features = format.guaranteed_format_features(gfx.wgpu.device.features());
+        if !features
+            .allowed_usages
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+            || !features
+                .flags
+                .contains(wgpu::TextureFormatFeatureFlags::FILTERABLE)
+        {
+            return Err(GameError::RenderError(format!(
+                "cannot generate mipmaps for images in the {format:?} format"
+            )));
+        }
+
+        let levels = full_mip_chain(width, height);
+        let image = Self::upload(
+            &gfx.wgpu,
+            pixels,
             format,
             width,
             height,
+            levels,
+            wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::RENDER_ATTACHMENT,
+        );
+
+        let mut cmd = gfx
+            .wgpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        gfx.mipmaps
+            .generate(&gfx.wgpu.device, &mut cmd, &image.texture, format, levels);
+        let _ = gfx.wgpu.queue.submit([cmd.finish()]);
+
+        Ok(image)
+    }
+
+    fn from_container(gfx: &GraphicsContext, container: TextureContainer) -> GameResult<Self> {
+        let format = container.format;
+        let missing = format.required_features() - gfx.wgpu.device.features();
+        if !missing.is_empty() {
+            return Err(GameError::RenderError(format!(
+                "the graphics adapter doesn't support images in the {format:?} format (missing {missing:?})"
+            )));
+        }
+
+        let image = Self::with_mip_levels(
+            &gfx.wgpu,
+            format,
+            container.width,
+            container.height,
             1,
+            container.levels.len() as u32,
             wgpu::TextureUsages::COPY_DST
                 | wgpu::TextureUsages::TEXTURE_BINDING
                 | wgpu::TextureUsages::COPY_SRC,
         );
 
+        let (block_width, _) = format.block_dimensions();
+        let block_size = format.block_size(None).unwrap(); // Unwrap since it only fails with depth formats.
+        for (level, data) in container.levels.iter().enumerate() {
+            let size = wgpu::Extent3d {
+                width: (container.width >> level).max(1),
+                height: (container.height >> level).max(1),
+                depth_or_array_layers: 1,
+            };
+            gfx.wgpu.queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &image.texture,
+                    mip_level: level as u32,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                data,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(size.width.div_ceil(block_width) * block_size),
+                    rows_per_image: None,
+                },
+                size.physical_size(format),
+            );
+        }
+
+        Ok(image)
+    }
+
+    fn upload(
+        wgpu: &WgpuContext,
+        pixels: &[u8],
+        format: ImageFormat,
+        width: u32,
+        height: u32,
+        mip_levels: u32,
+        usage: wgpu::TextureUsages,
+    ) -> Self {
+        let image = Self::with_mip_levels(wgpu, format, width, height, 1, mip_levels, usage);
+
         wgpu.queue.write_texture(
             image.texture.as_image_copy(),
             pixels,
@@ -133,39 +397,90 @@ impl Image {
 
     /// Creates a new image initialized with pixel data loaded from a given path as an
     /// encoded image `Read` (e.g. PNG or JPEG).
-    #[allow(unused_results)]
     pub fn from_path(gfx: &impl Has<GraphicsContext>, path: impl AsRef<Path>) -> GameResult<Self> {
+        Self::from_path_with_options(gfx, path, ImageLoadOptions::default())
+    }
+
+    /// Like [`Image::from_path`], but decodes the image as described by `options`.
+    #[allow(unused_results)]
+    pub fn from_path_with_options(
+        gfx: &impl Has<GraphicsContext>,
+        path: impl AsRef<Path>,
+        options: ImageLoadOptions,
+    ) -> GameResult<Self> {
+        trace_scope!("Image::from_path", path = %path.as_ref().display());
         let gfx = gfx.retrieve();
 
         let mut encoded = Vec::new();
         File::open(path)?.read_to_end(&mut encoded)?;
 
-        Self::from_bytes(gfx, encoded.as_slice())
+        Self::from_bytes_with_options(gfx, encoded.as_slice(), options)
     }
 
     /// Creates a new image initialized with pixel data from a given encoded image (e.g. PNG or JPEG)
+    ///
+    /// DDS and KTX2 files are uploaded as stored, keeping their mip levels and GPU compressed
+    /// format (BC1-BC7, or ETC2/ASTC where the graphics adapter supports them).
     pub fn from_bytes(gfx: &impl Has<GraphicsContext>, encoded: &[u8]) -> Result<Image, GameError> {
+        Self::from_bytes_with_options(gfx, encoded, ImageLoadOptions::default())
+    }
+
+    /// Like [`Image::from_bytes`], but decodes the image as described by `options`.
+    pub fn from_bytes_with_options(
+        gfx: &impl Has<GraphicsContext>,
+        encoded: &[u8],
+        options: ImageLoadOptions,
+    ) -> GameResult<Self> {
+        trace_scope!("Image::from_bytes", bytes = encoded.len());
+        let gfx = gfx.retrieve();
+        if TextureContainer::is_container(encoded) {
+            return Self::from_container(gfx, TextureContainer::parse(encoded, &options)?);
+        }
+
         let decoded = image::load_from_memory(encoded)
             .map_err(|_| GameError::ResourceLoadError(String::from("failed to load image")))?;
-        let rgba8 = decoded.to_rgba8();
-        let (width, height) = (rgba8.width(), rgba8.height());
+        let (width, height) = (decoded.width(), decoded.height());
+        let (pixels, format) = options.convert(decoded)?;
 
-        Ok(Self::from_pixels(
+        if options.mipmaps {
+            Self::from_pixels_mipmapped(gfx, &pixels, format, width, height)
+        } else {
+            Ok(Self::from_pixels_wgpu(
+                &gfx.wgpu, &pixels, format, width, height,
+            ))
+        }
+    }
+
+    /// Creates a new image from pixels prepared on the CPU with [`ImageData`].
+    pub fn from_image_data(gfx: &impl Has<GraphicsContext>, data: &ImageData) -> Self {
+        Self::from_pixels(
             gfx,
-            rgba8.as_ref(),
-            ImageFormat::Rgba8UnormSrgb,
-            width,
-            height,
-        ))
+            data.pixels(),
+            data.format(),
+            data.width(),
+            data.height(),
+        )
     }
 
-    fn new(
+    pub(crate) fn new(
         wgpu: &WgpuContext,
         format: ImageFormat,
         width: u32,
         height: u32,
         samples: u32,
         usage: wgpu::TextureUsages,
+    ) -> Self {
+        Self::with_mip_levels(wgpu, format, width, height, samples, 1, usage)
+    }
+
+    pub(crate) fn with_mip_levels(
+        wgpu: &WgpuContext,
+        format: ImageFormat,
+        width: u32,
+        height: u32,
+        samples: u32,
+        mip_levels: u32,
+        usage: wgpu::TextureUsages,
     ) -> Self {
         assert!(width > 0);
         assert!(height > 0);
@@ -178,7 +493,7 @@ impl Image {
                 height,
                 depth_or_array_layers: 1,
             },
-            mip_level_count: 1,
+            mip_level_count: mip_levels,
             sample_count: samples,
             dimension: wgpu::TextureDimension::D2,
             format,
@@ -193,7 +508,7 @@ impl Image {
                 dimension: Some(wgpu::TextureViewDimension::D2),
                 aspect: wgpu::TextureAspect::All,
                 base_mip_level: 0,
-                mip_level_count: Some(1),
+                mip_level_count: Some(mip_levels),
                 base_array_layer: 0,
                 array_layer_count: Some(1),
             }));
@@ -215,103 +530,334 @@ impl Image {
         (&self.texture, &self.view)
     }
 
+    /// Replaces the pixels inside `rect` with `pixels`, tightly packed rows in this image's format.
+    ///
+    /// The new pixels take effect before any GPU work submitted afterwards, which includes
+    /// every [`Canvas`] of the current frame, even those finished before this call.
+    /// Use [`Image::copy_from`] or a [`DynamicTexture`](super::DynamicTexture) to keep writes in order
+    /// with drawing, or when updating many regions each frame.
+    pub fn write_pixels(
+        &self,
+        gfx: &impl Has<GraphicsContext>,
+        rect: Rect,
+        pixels: &[u8],
+    ) -> GameResult {
+        let gfx = gfx.retrieve();
+        let (origin, size, row) = self.region(rect)?;
+        if pixels.len() != row as usize * size.height as usize {
+            return Err(GameError::RenderError(String::from(
+                "pixel data doesn't match the size of the region",
+            )));
+        }
+
+        gfx.wgpu.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin,
+                aspect: wgpu::TextureAspect::All,
+            },
+            pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(row),
+                rows_per_image: None,
+            },
+            size,
+        );
+
+        Ok(())
+    }
+
+    /// Copies the pixels inside `src_rect` of `src` into this image, with the top-left corner at `dst`.
+    ///
+    /// Both images must have the same format and sample count. During a frame the copy is recorded
+    /// in order with canvases, i.e. it sees everything drawn by canvases finished before it, and
+    /// canvases finished after it see the copied pixels. Outside of a frame it's submitted right away.
+    pub fn copy_from(
+        &self,
+        gfx: &mut impl HasMut<GraphicsContext>,
+        src: &Image,
+        src_rect: Rect,
+        dst: impl Into<mint::Point2<f32>>,
+    ) -> GameResult {
+        let gfx = gfx.retrieve_mut();
+        if src.format != self.format || src.samples != self.samples {
+            return Err(GameError::RenderError(String::from(
+                "cannot copy between images of different formats or sample counts",
+            )));
+        }
+
+        let dst = dst.into();
+        let (src_origin, size, _) = src.region(src_rect)?;
+        let (dst_origin, _, _) = self.region(Rect::new(
+            dst.x,
+            dst.y,
+            size.width as f32,
+            size.height as f32,
+        ))?;
+
+        let copy = |cmd: &mut wgpu::CommandEncoder| {
+            cmd.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &src.texture,
+                    mip_level: 0,
+                    origin: src_origin,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: dst_origin,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                size,
+            )
+        };
+
+        if let Some(fcx) = &mut gfx.fcx {
+            copy(&mut fcx.cmd);
+        } else {
+            let mut cmd = gfx
+                .wgpu
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            copy(&mut cmd);
+            let _ = gfx.wgpu.queue.submit([cmd.finish()]);
+        }
+
+        Ok(())
+    }
+
+    /// Validates a region of this image for copies, returning its origin, size and bytes per row.
+    pub(crate) fn region(&self, rect: Rect) -> GameResult<(wgpu::Origin3d, wgpu::Extent3d, u32)> {
+        let block_size = match self.format.block_size(None) {
+            Some(size) if self.format.block_dimensions() == (1, 1) => size,
+            _ => {
+                return Err(GameError::RenderError(format!(
+                    "cannot copy regions of images in the {:?} format",
+                    self.format
+                )))
+            }
+        };
+
+        let (x, y, w, h) = (rect.x as u32, rect.y as u32, rect.w as u32, rect.h as u32);
+        if rect.x < 0. || rect.y < 0. || x + w > self.width || y + h > self.height {
+            return Err(GameError::RenderError(String::from(
+                "region is outside of the image",
+            )));
+        }
+
+        Ok((
+            wgpu::Origin3d { x, y, z: 0 },
+            wgpu::Extent3d {
+                width: w,
+                height: h,
+                depth_or_array_layers: 1,
+            },
+            w * block_size,
+        ))
+    }
+
     /// Reads the pixels of this `ImageView` and returns as `Vec<u8>`.
     /// The format matches the GPU image format.
     ///
-    /// **This is a very expensive operation - call sparingly.**
+    /// **This is a very expensive operation - call sparingly.** It waits for the GPU to finish
+    /// all submitted work; [`Image::to_pixels_async`] avoids that.
     pub fn to_pixels(&self, gfx: &impl Has<GraphicsContext>) -> GameResult<Vec<u8>> {
+        self.to_pixels_async(gfx)?.wait()
+    }
+
+    /// Reads the pixels of this image back into an [`ImageData`] for editing on the CPU.
+    ///
+    /// Only 8-bit RGBA and BGRA formats are supported. Like [`Image::to_pixels`], this waits for the GPU.
+    pub fn to_image_data(&self, gfx: &impl Has<GraphicsContext>) -> GameResult<ImageData> {
+        let pixels = self.to_pixels(gfx)?;
+        ImageData::from_pixels(self.width, self.height, self.format, pixels)
+    }
+
+    /// Starts reading the pixels of this image back from the GPU without waiting for it.
+    ///
+    /// The returned [`PixelReadback`] can be checked each frame with [`PixelReadback::try_read`],
+    /// or awaited. The pixels are read as of the GPU work submitted so far; draws to this image from a
+    /// [`Canvas`] in the current frame are only submitted by [`GraphicsContext::end_frame`].
+    pub fn to_pixels_async(&self, gfx: &impl Has<GraphicsContext>) -> GameResult<PixelReadback> {
         let gfx = gfx.retrieve();
         if self.samples > 1 {
             return Err(GameError::RenderError(String::from(
                 "cannot read the pixels of a multisampled image; resolve this image with a canvas",
             )));
         }
+        if self.format.is_compressed() {
+            return Err(GameError::RenderError(String::from(
+                "cannot read the pixels of a compressed image",
+            )));
+        }
 
-        let block_size = u64::from(self.format.block_size(None).unwrap()); // Unwrap since it only fails with depth formats.
+        let mut cmd = gfx
+            .wgpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let (buffer, padded_row) =
+            self.copy_to_readback_buffer(&gfx.wgpu.device, &gfx.readback_pool, &mut cmd);
+        let _ = gfx.wgpu.queue.submit([cmd.finish()]);
 
-        let buffer = gfx.wgpu.device.create_buffer(&wgpu::BufferDescriptor {
-            label: None,
-            size: block_size * u64::from(self.width) * u64::from(self.height),
-            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-            mapped_at_creation: false,
-        });
+        let block_size = self.format.block_size(None).unwrap(); // Unwrap since it only fails with depth formats.
+        Ok(PixelReadback::new(
+            gfx.wgpu.clone(),
+            gfx.readback_pool.clone(),
+            buffer,
+            block_size * self.width,
+            padded_row,
+            self.height,
+        ))
+    }
 
-        let cmd = {
-            let mut encoder = gfx
-                .wgpu
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-            encoder.copy_texture_to_buffer(
-                self.texture.as_image_copy(),
-                wgpu::ImageCopyBuffer {
-                    buffer: &buffer,
-                    layout: wgpu::ImageDataLayout {
-                        offset: 0,
-                        bytes_per_row: Some(block_size as u32 * self.width),
-                        rows_per_image: None,
-                    },
-                },
-                wgpu::Extent3d {
-                    width: self.width,
-                    height: self.height,
-                    depth_or_array_layers: 1,
+    /// Records a copy of this image into a mappable buffer taken from `pool`.
+    ///
+    /// Returns the buffer together with the size of a row in bytes, which is padded to
+    /// [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`].
+    pub(crate) fn copy_to_readback_buffer(
+        &self,
+        device: &wgpu::Device,
+        pool: &ReadbackPool,
+        cmd: &mut wgpu::CommandEncoder,
+    ) -> (ArcBuffer, u32) {
+        let block_size = self.format.block_size(None).unwrap(); // Unwrap since it only fails with depth formats.
+        let padded_row = align_row(block_size * self.width);
+
+        let buffer = pool.get(device, u64::from(padded_row) * u64::from(self.height));
+
+        cmd.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: buffer.as_ref(),
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: None,
                 },
-            );
-            encoder.finish()
-        };
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
 
-        let _ = gfx.wgpu.queue.submit([cmd]);
+        (buffer, padded_row)
+    }
 
-        // wait...
-        let (tx, rx) = std::sync::mpsc::sync_channel(1);
-        buffer
-            .slice(..)
-            .map_async(wgpu::MapMode::Read, move |result| tx.send(result).unwrap()); // Unwrap is fine as this should never fail
-        let _ = gfx.wgpu.device.poll(wgpu::Maintain::Wait);
-        let map_result = rx
-            .recv()
-            .expect("All senders dropped, this should not be possible.");
-        map_result?;
+    /// Creates a new image holding a copy of the current contents of this image.
+    ///
+    /// The copy is submitted to the GPU queue right away.
+    pub(crate) fn duplicate(&self, wgpu: &WgpuContext) -> GameResult<Image> {
+        if self.samples > 1 {
+            return Err(GameError::RenderError(String::from(
+                "cannot copy a multisampled image; resolve this image with a canvas",
+            )));
+        }
+        if !self.texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return Err(GameError::RenderError(String::from(
+                "cannot copy an image that wasn't created with copy usage",
+            )));
+        }
+
+        let copy = Self::new(
+            wgpu,
+            self.format,
+            self.width,
+            self.height,
+            1,
+            wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        );
 
-        let out = buffer.slice(..).get_mapped_range().to_vec();
-        Ok(out)
+        let mut cmd = wgpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        cmd.copy_texture_to_texture(
+            self.texture.as_image_copy(),
+            copy.texture.as_image_copy(),
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        let _ = wgpu.queue.submit([cmd.finish()]);
+
+        Ok(copy)
     }
 
-    /// Encodes the `ImageView` to the given file format and return the encoded bytes.
+    /// Encodes the image to the given format and writes it to the file at `path`.
+    ///
+    /// `format` is either an [`ImageEncodingFormat`], or an [`ImageOutputFormat`] to choose
+    /// format specific settings such as the JPEG quality. See [`Image::encode_to_vec`] for
+    /// the supported formats and how pixels are converted.
     ///
     /// **This is a very expensive operation - call sparingly.**
     pub fn encode(
         &self,
-        ctx: &Context,
-        format: ImageEncodingFormat,
+        gfx: &impl Has<GraphicsContext>,
+        format: impl Into<ImageOutputFormat>,
         path: impl AsRef<std::path::Path>,
     ) -> GameResult {
-        let color = match self.format {
-            ImageFormat::Rgba8Unorm | ImageFormat::Rgba8UnormSrgb => ::image::ColorType::Rgba8,
-            ImageFormat::R8Unorm => ::image::ColorType::L8,
-            ImageFormat::R16Unorm => ::image::ColorType::L16,
-            format => {
-                return Err(GameError::RenderError(format!(
-                    "cannot ImageView::encode for the {format:#?} GPU image format"
-                )))
-            }
-        };
+        let encoded = self.encode_to_vec(gfx, format)?;
+        std::fs::write(path, encoded)?;
+        Ok(())
+    }
 
-        let pixels = self.to_pixels(ctx)?;
-        let f = File::create(path)?;
-        let writer = &mut std::io::BufWriter::new(f);
+    /// Encodes the image to the given format and writes it to `writer`.
+    ///
+    /// **This is a very expensive operation - call sparingly.**
+    pub fn encode_to_writer(
+        &self,
+        gfx: &impl Has<GraphicsContext>,
+        format: impl Into<ImageOutputFormat>,
+        mut writer: impl Write,
+    ) -> GameResult {
+        let encoded = self.encode_to_vec(gfx, format)?;
+        writer.write_all(&encoded)?;
+        Ok(())
+    }
 
-        match format {
-            ImageEncodingFormat::Png => ::image::codecs::png::PngEncoder::new(writer)
-                .write_image(&pixels, self.width, self.height, color)
-                .map_err(Into::into),
-            ImageEncodingFormat::Bmp => ::image::codecs::bmp::BmpEncoder::new(writer)
-                .encode(&pixels, self.width, self.height, color)
-                .map_err(Into::into),
-            _ => Err(GameError::RenderError(String::from(
-                "cannot ImageView::encode for formats other than Png and Bmp",
-            ))),
+    /// Encodes the image to the given format and returns the encoded bytes.
+    ///
+    /// PNG, JPEG, BMP, TGA, WebP (lossless), QOI, PNM, TIFF, GIF and OpenEXR are supported.
+    /// The pixels are converted to what the format can store: alpha is dropped for JPEG, OpenEXR
+    /// is always written as 32-bit float, PNG and TIFF keep 16 bits per channel for 16-bit and
+    /// float images, PNM pixmaps are written as 8-bit RGB, graymaps as 8-bit luma and bitmaps
+    /// with black for pixels darker than half gray, and everything else, including arbitrary PNM
+    /// maps, as 8-bit RGBA.
+    ///
+    /// Most color formats can be encoded, including BGRA formats such as the usual surface
+    /// format and 16/32-bit float formats, so the target of any non-MSAA [`Canvas`] can be encoded.
+    ///
+    /// **This is a very expensive operation - call sparingly.**
+    pub fn encode_to_vec(
+        &self,
+        gfx: &impl Has<GraphicsContext>,
+        format: impl Into<ImageOutputFormat>,
+    ) -> GameResult<Vec<u8>> {
+        let format = format.into();
+        if let ImageOutputFormat::Unsupported(name) = &format {
+            return Err(GameError::RenderError(format!(
+                "cannot encode images to the {name} format"
+            )));
         }
+
+        encode_dynamic_image(self.to_dynamic_image(gfx)?, format)
+    }
+
+    /// Reads the pixels of this image back and converts them to an [`image::DynamicImage`](DynamicImage).
+    pub(crate) fn to_dynamic_image(
+        &self,
+        gfx: &impl Has<GraphicsContext>,
+    ) -> GameResult<DynamicImage> {
+        let pixels = self.to_pixels(gfx)?;
+        pixels_to_dynamic_image(pixels, self.format, self.width, self.height)
     }
 
     /// Returns the image format of this image.
@@ -326,6 +872,13 @@ impl Image {
         self.samples
     }
 
+    /// Returns the number of mip levels of the image, which is 1 unless it was loaded with
+    /// [`ImageLoadOptions::mipmaps`].
+    #[inline]
+    pub fn mip_levels(&self) -> u32 {
+        self.texture.mip_level_count()
+    }
+
     /// Returns the width (in pixels) of the image.
     #[inline]
     pub fn width(&self) -> u32 {
@@ -372,6 +925,256 @@ impl Image {
     }
 }
 
+/// Pending read of an image's pixels, started with [`Image::to_pixels_async`].
+///
+/// Check it once per frame with [`PixelReadback::try_read`], block on it with [`PixelReadback::wait`],
+/// or `.await` it. The pixels are returned tightly packed, without the row padding the GPU requires.
+#[derive(Debug)]
+pub struct PixelReadback {
+    wgpu: Arc<WgpuContext>,
+    pool: ReadbackPool,
+    buffer: Option<ArcBuffer>,
+    mapped: mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>,
+    // set once the map callback has fired
+    map_result: Option<Result<(), wgpu::BufferAsyncError>>,
+    row: u32,
+    padded_row: u32,
+    height: u32,
+}
+
+impl PixelReadback {
+    fn new(
+        wgpu: Arc<WgpuContext>,
+        pool: ReadbackPool,
+        buffer: ArcBuffer,
+        row: u32,
+        padded_row: u32,
+        height: u32,
+    ) -> Self {
+        let (tx, rx) = mpsc::sync_channel(1);
+        buffer
+            .slice(..u64::from(padded_row) * u64::from(height))
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = tx.send(result);
+            });
+
+        PixelReadback {
+            wgpu,
+            pool,
+            buffer: Some(buffer),
+            mapped: rx,
+            map_result: None,
+            row,
+            padded_row,
+            height,
+        }
+    }
+
+    /// Returns whether the pixels are ready to be read, without blocking.
+    pub fn is_ready(&mut self) -> bool {
+        if self.map_result.is_none() {
+            let _ = self.wgpu.device.poll(wgpu::Maintain::Poll);
+            self.map_result = self.mapped.try_recv().ok();
+        }
+        self.map_result.is_some()
+    }
+
+    /// Returns the pixels if they are ready, or `None` if the GPU isn't done yet.
+    ///
+    /// Returns an error if the pixels were already taken.
+    pub fn try_read(&mut self) -> GameResult<Option<Vec<u8>>> {
+        if self.is_ready() {
+            self.read().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Blocks until the pixels are ready, then returns them.
+    pub fn wait(mut self) -> GameResult<Vec<u8>> {
+        if self.map_result.is_none() {
+            let _ = self.wgpu.device.poll(wgpu::Maintain::Wait);
+            self.map_result = Some(
+                self.mapped
+                    .recv()
+                    .expect("All senders dropped, this should not be possible."),
+            );
+        }
+        self.read()
+    }
+
+    fn read(&mut self) -> GameResult<Vec<u8>> {
+        let buffer = self
+            .buffer
+            .take()
+            .ok_or_else(|| GameError::RenderError(String::from("the pixels were already read")))?;
+        // a failed map has nothing to unmap, so the buffer can't go back to the pool as is
+        self.map_result.clone().unwrap(/* only called once ready */)?;
+
+        let size = u64::from(self.padded_row) * u64::from(self.height);
+        let mut pixels = Vec::with_capacity(self.row as usize * self.height as usize);
+        {
+            let data = buffer.slice(..size).get_mapped_range();
+            for padded in data.chunks_exact(self.padded_row as usize) {
+                pixels.extend_from_slice(&padded[..self.row as usize]);
+            }
+        }
+        buffer.unmap();
+        self.pool.put(buffer);
+
+        Ok(pixels)
+    }
+}
+
+impl Future for PixelReadback {
+    type Output = GameResult<Vec<u8>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        match self.try_read() {
+            Ok(Some(pixels)) => Poll::Ready(Ok(pixels)),
+            // wgpu only makes progress when polled, so ask to be polled again
+            Ok(None) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+impl Drop for PixelReadback {
+    fn drop(&mut self) {
+        // buffers whose mapping is still pending can't be reused, those are simply dropped
+        if let (Some(buffer), Some(Ok(()))) = (self.buffer.take(), &self.map_result) {
+            buffer.unmap();
+            self.pool.put(buffer);
+        }
+    }
+}
+
+/// Converts tightly packed pixels in the given GPU format to a [`DynamicImage`].
+pub(crate) fn pixels_to_dynamic_image(
+    mut pixels: Vec<u8>,
+    format: ImageFormat,
+    width: u32,
+    height: u32,
+) -> GameResult<DynamicImage> {
+    fn u16s(pixels: &[u8]) -> Vec<u16> {
+        pixels
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect()
+    }
+    fn f16s(pixels: &[u8]) -> Vec<f32> {
+        u16s(pixels).into_iter().map(f16_to_f32).collect()
+    }
+    fn f32s(pixels: &[u8]) -> Vec<f32> {
+        pixels
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect()
+    }
+    // single channel float images are stored as grey, there is no float luma image type
+    fn grey(values: Vec<f32>) -> Vec<f32> {
+        values.into_iter().flat_map(|v| [v, v, v]).collect()
+    }
+
+    let image = match format {
+        ImageFormat::Rgba8Unorm | ImageFormat::Rgba8UnormSrgb => {
+            ::image::RgbaImage::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8)
+        }
+        ImageFormat::Bgra8Unorm | ImageFormat::Bgra8UnormSrgb => {
+            for px in pixels.chunks_exact_mut(4) {
+                px.swap(0, 2);
+            }
+            ::image::RgbaImage::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8)
+        }
+        ImageFormat::R8Unorm => {
+            ::image::GrayImage::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8)
+        }
+        ImageFormat::R16Unorm => ::image::ImageBuffer::from_raw(width, height, u16s(&pixels))
+            .map(DynamicImage::ImageLuma16),
+        ImageFormat::Rgba16Unorm => ::image::ImageBuffer::from_raw(width, height, u16s(&pixels))
+            .map(DynamicImage::ImageRgba16),
+        ImageFormat::Rgb10a2Unorm => {
+            let rgba = pixels
+                .chunks_exact(4)
+                .flat_map(|c| {
+                    let v = u32::from_le_bytes([c[0], c[1], c[2], c[3]]);
+                    // widen 10 and 2 bit channels to 16 bits
+                    let c10 = |shift: u32| (((v >> shift) & 0x3ff) * 0xffff / 0x3ff) as u16;
+                    [c10(0), c10(10), c10(20), ((v >> 30) * 0xffff / 3) as u16]
+                })
+                .collect();
+            ::image::ImageBuffer::from_raw(width, height, rgba).map(DynamicImage::ImageRgba16)
+        }
+        ImageFormat::R16Float => ::image::ImageBuffer::from_raw(width, height, grey(f16s(&pixels)))
+            .map(DynamicImage::ImageRgb32F),
+        ImageFormat::Rgba16Float => ::image::ImageBuffer::from_raw(width, height, f16s(&pixels))
+            .map(DynamicImage::ImageRgba32F),
+        ImageFormat::R32Float => ::image::ImageBuffer::from_raw(width, height, grey(f32s(&pixels)))
+            .map(DynamicImage::ImageRgb32F),
+        ImageFormat::Rgba32Float => ::image::ImageBuffer::from_raw(width, height, f32s(&pixels))
+            .map(DynamicImage::ImageRgba32F),
+        format => {
+            return Err(GameError::RenderError(format!(
+                "cannot convert pixels in the {format:?} GPU image format"
+            )))
+        }
+    };
+
+    image.ok_or_else(|| {
+        GameError::RenderError(String::from(
+            "pixel data doesn't match the size of the image",
+        ))
+    })
+}
+
+/// Converts an `f32` to the bits of the nearest IEEE 754 half precision float.
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let raw_exponent = (bits >> 23) & 0xff;
+    let exponent = raw_exponent as i32 - 127 + 15;
+    let mantissa = bits & 0x7f_ffff;
+
+    if raw_exponent == 0xff {
+        // infinity or NaN
+        sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 }
+    } else if exponent >= 0x1f {
+        sign | 0x7c00
+    } else if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        let mantissa = (mantissa | 0x80_0000) >> (1 - exponent);
+        sign | ((mantissa + 0x1000) >> 13) as u16
+    } else {
+        // rounding may carry into the exponent, which is what we want
+        let half = sign | ((exponent as u16) << 10) | (mantissa >> 13) as u16;
+        half + u16::from(mantissa & 0x1000 != 0)
+    }
+}
+
+/// Converts the bits of an IEEE 754 half precision float to an `f32`.
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1. } else { 1. };
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f32::from(bits & 0x3ff);
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0. => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1. + mantissa / 1024.) * 2f32.powi(exponent - 15),
+    }
+}
+
+/// Rounds a row size in bytes up to the alignment required for texture to buffer copies.
+pub(crate) fn align_row(bytes: u32) -> u32 {
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    bytes.div_ceil(align) * align
+}
+
 impl Drawable for Image {
     fn draw(&self, canvas: &mut Canvas, param: impl Into<DrawParam>) {
         canvas.push_draw(
@@ -459,3 +1262,209 @@ impl ScreenImage {
         Image::new_canvas_image(gfx, format, width, height, samples)
     }
 }
+
+/// Encodes `image` to `format`, converting its pixels as described in [`Image::encode_to_vec`].
+fn encode_dynamic_image(image: DynamicImage, format: ImageOutputFormat) -> GameResult<Vec<u8>> {
+    use ::image::codecs::pnm::PnmSubtype;
+
+    let high_precision = matches!(
+        image,
+        DynamicImage::ImageLuma16(_)
+            | DynamicImage::ImageLumaA16(_)
+            | DynamicImage::ImageRgb16(_)
+            | DynamicImage::ImageRgba16(_)
+            | DynamicImage::ImageRgb32F(_)
+            | DynamicImage::ImageRgba32F(_)
+    );
+    let image = match format {
+        ImageOutputFormat::Jpeg(_) => DynamicImage::ImageRgb8(image.into_rgb8()),
+        ImageOutputFormat::OpenExr => DynamicImage::ImageRgba32F(image.into_rgba32f()),
+        ImageOutputFormat::Png | ImageOutputFormat::Tiff if high_precision => {
+            DynamicImage::ImageRgba16(image.into_rgba16())
+        }
+        // the PNM encoder only takes 8-bit pixels, of a single layout for the fixed subtypes
+        ImageOutputFormat::Pnm(PnmSubtype::Pixmap(_)) => DynamicImage::ImageRgb8(image.into_rgb8()),
+        ImageOutputFormat::Pnm(PnmSubtype::Graymap(_)) => {
+            DynamicImage::ImageLuma8(image.into_luma8())
+        }
+        ImageOutputFormat::Pnm(PnmSubtype::Bitmap(encoding)) => {
+            return Ok(encode_pbm(&image.into_luma8(), encoding));
+        }
+        _ => DynamicImage::ImageRgba8(image.into_rgba8()),
+    };
+
+    let mut encoded = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut encoded, format)?;
+    Ok(encoded.into_inner())
+}
+
+/// Writes a PBM bitmap, in which pixels darker than half gray are black.
+///
+/// The `image` crate's PBM encoder rejects bitmaps without any white pixel, so it isn't used.
+fn encode_pbm(
+    image: &::image::GrayImage,
+    encoding: ::image::codecs::pnm::SampleEncoding,
+) -> Vec<u8> {
+    use ::image::codecs::pnm::SampleEncoding;
+
+    let (width, height) = image.dimensions();
+    let magic = match encoding {
+        SampleEncoding::Binary => "P4",
+        SampleEncoding::Ascii => "P1",
+    };
+    let mut encoded = format!("{magic}\n{width} {height}\n").into_bytes();
+    for row in image.rows() {
+        let black = row.map(|pixel| pixel.0[0] < 128).collect::<Vec<_>>();
+        match encoding {
+            // rows are padded to whole bytes, with the first pixel in the highest bit
+            SampleEncoding::Binary => encoded.extend(black.chunks(8).map(|bits| {
+                bits.iter()
+                    .enumerate()
+                    .filter(|(_, &black)| black)
+                    .fold(0u8, |byte, (i, _)| byte | 0x80 >> i)
+            })),
+            // lines must not be longer than 70 characters
+            SampleEncoding::Ascii => {
+                for line in black.chunks(35) {
+                    for &black in line {
+                        encoded.extend_from_slice(if black { b"1 " } else { b"0 " });
+                    }
+                    let _ = encoded.pop();
+                    encoded.push(b'\n');
+                }
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bgra_pixels_are_swizzled() {
+        let image = pixels_to_dynamic_image(vec![1, 2, 3, 4], ImageFormat::Bgra8UnormSrgb, 1, 1)
+            .unwrap()
+            .into_rgba8();
+        assert_eq!(image.into_raw(), vec![3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn half_floats() {
+        assert_eq!(f16_to_f32(0x3c00), 1.);
+        assert_eq!(f16_to_f32(0xc000), -2.);
+        assert_eq!(f16_to_f32(0x3800), 0.5);
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert!(f16_to_f32(0x7e00).is_nan());
+
+        for value in [0., 1., -2., 0.5, 65504., 2f32.powi(-24)] {
+            assert_eq!(f16_to_f32(f32_to_f16(value)), value, "{value}");
+        }
+        assert_eq!(f32_to_f16(0.1), 0x2e66);
+        assert_eq!(f32_to_f16(1e6), 0x7c00);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+    }
+
+    #[test]
+    fn every_pnm_subtype_encodes() {
+        use ::image::codecs::pnm::{PnmSubtype, SampleEncoding};
+
+        let images = [
+            DynamicImage::new_rgba8(2, 2),
+            DynamicImage::new_rgba16(2, 2),
+            DynamicImage::new_luma16(2, 2),
+            DynamicImage::new_rgba32f(2, 2),
+        ];
+        for subtype in [
+            PnmSubtype::Bitmap(SampleEncoding::Binary),
+            PnmSubtype::Bitmap(SampleEncoding::Ascii),
+            PnmSubtype::Graymap(SampleEncoding::Binary),
+            PnmSubtype::Graymap(SampleEncoding::Ascii),
+            PnmSubtype::Pixmap(SampleEncoding::Binary),
+            PnmSubtype::Pixmap(SampleEncoding::Ascii),
+            PnmSubtype::ArbitraryMap,
+        ] {
+            for image in &images {
+                let encoded = encode_dynamic_image(image.clone(), ImageOutputFormat::Pnm(subtype));
+                assert!(
+                    encoded.is_ok(),
+                    "{subtype:?} {:?}: {encoded:?}",
+                    image.color()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pbm_bitmaps() {
+        use ::image::codecs::pnm::{PnmSubtype, SampleEncoding};
+
+        // wider than a byte and than an ASCII line
+        let image =
+            ::image::GrayImage::from_fn(37, 3, |x, y| ::image::Luma([((x + y) * 40) as u8]));
+        for encoding in [SampleEncoding::Binary, SampleEncoding::Ascii] {
+            let encoded = encode_dynamic_image(
+                DynamicImage::ImageLuma8(image.clone()),
+                ImageOutputFormat::Pnm(PnmSubtype::Bitmap(encoding)),
+            )
+            .unwrap();
+            let decoded =
+                ::image::load_from_memory_with_format(&encoded, ::image::ImageFormat::Pnm)
+                    .unwrap()
+                    .into_luma8();
+            for (x, y, pixel) in image.enumerate_pixels() {
+                let white = decoded.get_pixel(x, y).0[0] > 0;
+                assert_eq!(white, pixel.0[0] >= 128, "{encoding:?} ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn png_keeps_16_bits() {
+        let encoded =
+            encode_dynamic_image(DynamicImage::new_rgba32f(1, 1), ImageOutputFormat::Png).unwrap();
+        let decoded =
+            ::image::load_from_memory_with_format(&encoded, ::image::ImageFormat::Png).unwrap();
+        assert_eq!(decoded.color(), ::image::ColorType::Rgba16);
+    }
+
+    #[test]
+    fn grayscale_load_options() {
+        let l8 = DynamicImage::ImageLuma8(::image::GrayImage::from_raw(1, 1, vec![51]).unwrap());
+        let l16 =
+            DynamicImage::ImageLuma16(::image::ImageBuffer::from_raw(1, 1, vec![0x3334]).unwrap());
+        let single = ImageLoadOptions::default().srgb(false).single_channel(true);
+        let high = ImageLoadOptions::default().srgb(false).high_precision(true);
+
+        let convert = |options: ImageLoadOptions, image: &DynamicImage| {
+            options.convert(image.clone()).unwrap()
+        };
+        let r16 = |pixels: Vec<u8>| f16_to_f32(u16::from_le_bytes([pixels[0], pixels[1]]));
+
+        assert_eq!(convert(single, &l8), (vec![51], ImageFormat::R8Unorm));
+        assert_eq!(convert(single, &l16), (vec![51], ImageFormat::R8Unorm));
+        assert_eq!(
+            convert(ImageLoadOptions::data(), &l8),
+            (vec![51], ImageFormat::R8Unorm)
+        );
+
+        let (pixels, format) = convert(ImageLoadOptions::data(), &l16);
+        assert_eq!(format, ImageFormat::R16Float);
+        assert!((r16(pixels) - 0x3334 as f32 / 65535.).abs() < 1e-4);
+
+        assert_eq!(
+            convert(high, &l8),
+            (vec![51, 51, 51, 255], ImageFormat::Rgba8Unorm)
+        );
+        let (pixels, format) = convert(high, &l16);
+        assert_eq!((pixels.len(), format), (8, ImageFormat::Rgba16Float));
+        assert!((r16(pixels) - 0x3334 as f32 / 65535.).abs() < 1e-4);
+
+        assert_eq!(
+            convert(ImageLoadOptions::default(), &l16),
+            (vec![51, 51, 51, 255], ImageFormat::Rgba8UnormSrgb)
+        );
+    }
+}
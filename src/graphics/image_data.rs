@@ -0,0 +1,497 @@
+use super::{
+    BlendComponent, BlendFactor, BlendMode, BlendOperation, Color, FilterMode, ImageFormat,
+    LinearColor,
+};
+use crate::{GameError, GameResult};
+use ::image::{imageops, Rgba, RgbaImage};
+
+type PixelView<'a> = ::image::ImageBuffer<Rgba<u8>, &'a [u8]>;
+
+/// Pixel data stored in CPU memory, for generating and editing images before uploading them
+/// with [`Image::from_image_data`](super::Image::from_image_data).
+///
+/// Pixels are stored with 8 bits per channel in one of the [`ImageFormat`]s
+/// `Rgba8UnormSrgb` (the default), `Rgba8Unorm`, `Bgra8UnormSrgb` or `Bgra8Unorm`.
+/// Operations taking or returning a [`Color`] take care of the channel order; for the sRGB
+/// formats, blending and premultiplying happen in linear space, just like on the GPU.
+///
+/// ```rust
+/// # use ggez::graphics::{BlendMode, Color, ImageData};
+/// let mut data = ImageData::from_color(16, 16, Color::BLACK);
+/// for i in 0..16 {
+///     data.set_pixel(i, i, Color::RED);
+/// }
+/// let flipped = {
+///     let mut copy = data.clone();
+///     copy.flip_horizontal();
+///     copy
+/// };
+/// data.blit(&flipped, 0, 0, BlendMode::ADD);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageData {
+    width: u32,
+    height: u32,
+    format: ImageFormat,
+    pixels: Vec<u8>,
+}
+
+impl ImageData {
+    /// Creates a new, fully transparent `Rgba8UnormSrgb` image.
+    pub fn new(width: u32, height: u32) -> Self {
+        ImageData {
+            width,
+            height,
+            format: ImageFormat::Rgba8UnormSrgb,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Creates a new `Rgba8UnormSrgb` image filled with `color`.
+    pub fn from_color(width: u32, height: u32, color: Color) -> Self {
+        let (r, g, b, a) = color.to_rgba();
+        ImageData {
+            width,
+            height,
+            format: ImageFormat::Rgba8UnormSrgb,
+            pixels: [r, g, b, a].repeat(width as usize * height as usize),
+        }
+    }
+
+    /// Creates an image from tightly packed pixels in the given format.
+    ///
+    /// Fails if the format isn't one of the supported 8-bit RGBA/BGRA formats,
+    /// or if the number of bytes doesn't match the size.
+    pub fn from_pixels(
+        width: u32,
+        height: u32,
+        format: ImageFormat,
+        pixels: Vec<u8>,
+    ) -> GameResult<Self> {
+        if !matches!(
+            format,
+            ImageFormat::Rgba8Unorm
+                | ImageFormat::Rgba8UnormSrgb
+                | ImageFormat::Bgra8Unorm
+                | ImageFormat::Bgra8UnormSrgb
+        ) {
+            return Err(GameError::RenderError(format!(
+                "ImageData doesn't support the {format:?} image format"
+            )));
+        }
+        if pixels.len() != width as usize * height as usize * 4 {
+            return Err(GameError::RenderError(String::from(
+                "pixel data doesn't match the size of the image",
+            )));
+        }
+
+        Ok(ImageData {
+            width,
+            height,
+            format,
+            pixels,
+        })
+    }
+
+    /// Decodes an encoded image (e.g. PNG or JPEG) into an `Rgba8UnormSrgb` image.
+    pub fn from_bytes(encoded: &[u8]) -> GameResult<Self> {
+        let decoded = ::image::load_from_memory(encoded)
+            .map_err(|_| GameError::ResourceLoadError(String::from("failed to load image")))?;
+        Ok(decoded.into_rgba8().into())
+    }
+
+    /// Returns the width of the image in pixels.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the image in pixels.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the pixel format of the image.
+    #[inline]
+    pub fn format(&self) -> ImageFormat {
+        self.format
+    }
+
+    /// Returns the raw pixel data, row by row from the top.
+    #[inline]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns the raw pixel data mutably, row by row from the top.
+    #[inline]
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
+    /// Consumes the image, returning the raw pixel data.
+    #[inline]
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// Returns the color of the pixel at (`x`, `y`), or `None` if it's out of bounds.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<Color> {
+        let i = self.index(x, y)?;
+        let [r, g, b, a] = self.rgba(&self.pixels[i..i + 4]);
+        Some(Color::from_rgba(r, g, b, a))
+    }
+
+    /// Sets the color of the pixel at (`x`, `y`). Does nothing if it's out of bounds.
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if let Some(i) = self.index(x, y) {
+            let (r, g, b, a) = color.to_rgba();
+            let px = self.rgba(&[r, g, b, a]);
+            self.pixels[i..i + 4].copy_from_slice(&px);
+        }
+    }
+
+    /// Draws `src` onto this image with its top-left corner at (`x`, `y`), combining the pixels
+    /// with `blend` the same way the GPU would. Parts of `src` outside of this image are skipped.
+    ///
+    /// [`BlendMode::REPLACE`] copies the pixels as they are.
+    pub fn blit(&mut self, src: &ImageData, x: i32, y: i32, blend: BlendMode) {
+        if blend == BlendMode::REPLACE && src.format == self.format {
+            self.copy_rows(src, x, y);
+            return;
+        }
+
+        for sy in 0..src.height {
+            let Ok(dy) = u32::try_from(y + sy as i32) else {
+                continue;
+            };
+            for sx in 0..src.width {
+                let Ok(dx) = u32::try_from(x + sx as i32) else {
+                    continue;
+                };
+                let Some(d) = self.get_linear(dx, dy) else {
+                    continue;
+                };
+                let s = src.get_linear(sx, sy).unwrap(/* within src */);
+
+                let channel =
+                    |s_c: f32, d_c: f32| blend_component(blend.color, s_c, d_c, s.a, d.a, 0.);
+                let out = LinearColor {
+                    r: channel(s.r, d.r),
+                    g: channel(s.g, d.g),
+                    b: channel(s.b, d.b),
+                    a: blend_component(blend.alpha, s.a, d.a, s.a, d.a, 1.),
+                };
+                self.set_linear(dx, dy, out);
+            }
+        }
+    }
+
+    // copies the part of `src` that overlaps this image row by row
+    fn copy_rows(&mut self, src: &ImageData, x: i32, y: i32) {
+        let (x0, y0) = (x.max(0), y.max(0));
+        let x1 = (x + src.width as i32).min(self.width as i32);
+        let y1 = (y + src.height as i32).min(self.height as i32);
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+
+        let len = (x1 - x0) as usize * 4;
+        for dy in y0..y1 {
+            let s = ((dy - y) as usize * src.width as usize + (x0 - x) as usize) * 4;
+            let d = (dy as usize * self.width as usize + x0 as usize) * 4;
+            self.pixels[d..d + len].copy_from_slice(&src.pixels[s..s + len]);
+        }
+    }
+
+    /// Returns a copy of the `w` x `h` region with its top-left corner at (`x`, `y`),
+    /// or `None` if the region doesn't fit into the image.
+    pub fn sub_image(&self, x: u32, y: u32, w: u32, h: u32) -> Option<ImageData> {
+        if x.checked_add(w)? > self.width || y.checked_add(h)? > self.height {
+            return None;
+        }
+        let row = self.width as usize * 4;
+        let (start, len) = (x as usize * 4, w as usize * 4);
+        let pixels = self
+            .pixels
+            .chunks_exact(row)
+            .skip(y as usize)
+            .take(h as usize)
+            .flat_map(|r| &r[start..start + len])
+            .copied()
+            .collect();
+        Some(ImageData {
+            width: w,
+            height: h,
+            format: self.format,
+            pixels,
+        })
+    }
+
+    /// Mirrors the image around its vertical axis.
+    pub fn flip_horizontal(&mut self) {
+        let mut buffer = self.take_buffer();
+        imageops::flip_horizontal_in_place(&mut buffer);
+        self.pixels = buffer.into_raw();
+    }
+
+    /// Mirrors the image around its horizontal axis.
+    pub fn flip_vertical(&mut self) {
+        let mut buffer = self.take_buffer();
+        imageops::flip_vertical_in_place(&mut buffer);
+        self.pixels = buffer.into_raw();
+    }
+
+    /// Returns the image rotated by 90 degrees clockwise.
+    pub fn rotate90(&self) -> ImageData {
+        self.with_pixels(imageops::rotate90(&self.view()))
+    }
+
+    /// Returns the image rotated by 180 degrees.
+    pub fn rotate180(&self) -> ImageData {
+        self.with_pixels(imageops::rotate180(&self.view()))
+    }
+
+    /// Returns the image rotated by 270 degrees clockwise (90 degrees counter-clockwise).
+    pub fn rotate270(&self) -> ImageData {
+        self.with_pixels(imageops::rotate270(&self.view()))
+    }
+
+    /// Returns the image scaled to `width` x `height`, sampled with `filter`.
+    pub fn resize(&self, width: u32, height: u32, filter: FilterMode) -> ImageData {
+        let filter = match filter {
+            FilterMode::Nearest => imageops::FilterType::Nearest,
+            FilterMode::Linear => imageops::FilterType::Triangle,
+        };
+        self.with_pixels(imageops::resize(&self.view(), width, height, filter))
+    }
+
+    /// Multiplies the color of every pixel by its alpha, for drawing with [`BlendMode::PREMULTIPLIED`].
+    pub fn premultiply(&mut self) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = self.get_linear(x, y).unwrap(/* within bounds */);
+                self.set_linear(
+                    x,
+                    y,
+                    LinearColor {
+                        r: c.r * c.a,
+                        g: c.g * c.a,
+                        b: c.b * c.a,
+                        a: c.a,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Makes every pixel whose color matches `key` (ignoring alpha) fully transparent.
+    pub fn color_key(&mut self, key: Color) {
+        let (r, g, b, _) = key.to_rgba();
+        let bgra = self.is_bgra();
+        for px in self.pixels.chunks_exact_mut(4) {
+            let (pr, pb) = if bgra { (px[2], px[0]) } else { (px[0], px[2]) };
+            if (pr, px[1], pb) == (r, g, b) {
+                px[3] = 0;
+            }
+        }
+    }
+
+    /// Copies the image into an [`RgbaImage`](::image::RgbaImage), swapping channels if needed.
+    pub fn to_rgba_image(&self) -> RgbaImage {
+        let mut pixels = self.pixels.clone();
+        if self.is_bgra() {
+            for px in pixels.chunks_exact_mut(4) {
+                px.swap(0, 2);
+            }
+        }
+        RgbaImage::from_raw(self.width, self.height, pixels).unwrap(/* sizes match */)
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height)
+            .then(|| (y as usize * self.width as usize + x as usize) * 4)
+    }
+
+    fn is_bgra(&self) -> bool {
+        matches!(
+            self.format,
+            ImageFormat::Bgra8Unorm | ImageFormat::Bgra8UnormSrgb
+        )
+    }
+
+    fn is_srgb(&self) -> bool {
+        self.format.is_srgb()
+    }
+
+    // swaps between RGBA and the stored channel order, which is its own inverse
+    fn rgba(&self, px: &[u8]) -> [u8; 4] {
+        if self.is_bgra() {
+            [px[2], px[1], px[0], px[3]]
+        } else {
+            [px[0], px[1], px[2], px[3]]
+        }
+    }
+
+    fn get_linear(&self, x: u32, y: u32) -> Option<LinearColor> {
+        let c = self.get_pixel(x, y)?;
+        Some(if self.is_srgb() {
+            LinearColor::from(c)
+        } else {
+            LinearColor {
+                r: c.r,
+                g: c.g,
+                b: c.b,
+                a: c.a,
+            }
+        })
+    }
+
+    fn set_linear(&mut self, x: u32, y: u32, c: LinearColor) {
+        let c = if self.is_srgb() {
+            Color::from(c)
+        } else {
+            Color::new(c.r, c.g, c.b, c.a)
+        };
+        // round rather than truncate, so that unchanged pixels survive the trip through linear space
+        let to_u8 = |v: f32| (v.clamp(0., 1.) * 255.).round() as u8;
+        if let Some(i) = self.index(x, y) {
+            let px = self.rgba(&[to_u8(c.r), to_u8(c.g), to_u8(c.b), to_u8(c.a)]);
+            self.pixels[i..i + 4].copy_from_slice(&px);
+        }
+    }
+
+    fn view(&self) -> PixelView<'_> {
+        PixelView::from_raw(self.width, self.height, &self.pixels[..]).unwrap(/* sizes match */)
+    }
+
+    fn take_buffer(&mut self) -> RgbaImage {
+        let pixels = std::mem::take(&mut self.pixels);
+        RgbaImage::from_raw(self.width, self.height, pixels).unwrap(/* sizes match */)
+    }
+
+    // channel order is kept as is, so `buffer` holds pixels in this image's format
+    fn with_pixels(&self, buffer: RgbaImage) -> ImageData {
+        ImageData {
+            width: buffer.width(),
+            height: buffer.height(),
+            format: self.format,
+            pixels: buffer.into_raw(),
+        }
+    }
+}
+
+impl From<RgbaImage> for ImageData {
+    fn from(image: RgbaImage) -> Self {
+        ImageData {
+            width: image.width(),
+            height: image.height(),
+            format: ImageFormat::Rgba8UnormSrgb,
+            pixels: image.into_raw(),
+        }
+    }
+}
+
+impl From<ImageData> for RgbaImage {
+    fn from(data: ImageData) -> Self {
+        data.to_rgba_image()
+    }
+}
+
+/// Evaluates a blend equation for a single channel. `constant` is the channel's blend constant,
+/// which canvases set to opaque black.
+fn blend_component(
+    component: BlendComponent,
+    src: f32,
+    dst: f32,
+    src_alpha: f32,
+    dst_alpha: f32,
+    constant: f32,
+) -> f32 {
+    let factor = |factor: BlendFactor| match factor {
+        BlendFactor::Zero => 0.,
+        BlendFactor::One => 1.,
+        BlendFactor::Src => src,
+        BlendFactor::OneMinusSrc => 1. - src,
+        BlendFactor::SrcAlpha => src_alpha,
+        BlendFactor::OneMinusSrcAlpha => 1. - src_alpha,
+        BlendFactor::Dst => dst,
+        BlendFactor::OneMinusDst => 1. - dst,
+        BlendFactor::DstAlpha => dst_alpha,
+        BlendFactor::OneMinusDstAlpha => 1. - dst_alpha,
+        BlendFactor::SrcAlphaSaturated => src_alpha.min(1. - dst_alpha),
+        BlendFactor::Constant => constant,
+        BlendFactor::OneMinusConstant => 1. - constant,
+    };
+
+    let s = src * factor(component.src_factor);
+    let d = dst * factor(component.dst_factor);
+    let out = match component.operation {
+        BlendOperation::Add => s + d,
+        BlendOperation::Subtract => s - d,
+        BlendOperation::ReverseSubtract => d - s,
+        BlendOperation::Min => src.min(dst),
+        BlendOperation::Max => src.max(dst),
+    };
+    out.clamp(0., 1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bgra_channel_order() {
+        let mut data =
+            ImageData::from_pixels(1, 1, ImageFormat::Bgra8Unorm, vec![0, 0, 0, 0]).unwrap();
+        data.set_pixel(0, 0, Color::from_rgba(10, 20, 30, 40));
+        assert_eq!(data.pixels(), &[30, 20, 10, 40]);
+        assert_eq!(data.get_pixel(0, 0).unwrap().to_rgba(), (10, 20, 30, 40));
+        assert_eq!(data.to_rgba_image().into_raw(), vec![10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn blit_blends_and_clips() {
+        let mut dst = ImageData::from_color(2, 2, Color::BLACK);
+        let src = ImageData::from_color(2, 2, Color::WHITE);
+        dst.blit(&src, 1, -1, BlendMode::ALPHA);
+        assert_eq!(dst.get_pixel(1, 0), Some(Color::WHITE));
+        assert_eq!(dst.get_pixel(0, 0), Some(Color::BLACK));
+        assert_eq!(dst.get_pixel(1, 1), Some(Color::BLACK));
+
+        let half = ImageData::from_pixels(1, 1, ImageFormat::Rgba8Unorm, vec![255, 255, 255, 128])
+            .unwrap();
+        let mut dst =
+            ImageData::from_pixels(1, 1, ImageFormat::Rgba8Unorm, vec![0, 0, 0, 255]).unwrap();
+        dst.blit(&half, 0, 0, BlendMode::ALPHA);
+        assert_eq!(dst.pixels(), &[128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn rotate_and_sub_image() {
+        let mut data = ImageData::new(3, 2);
+        data.set_pixel(0, 0, Color::RED);
+        let rotated = data.rotate90();
+        assert_eq!((rotated.width(), rotated.height()), (2, 3));
+        assert_eq!(rotated.get_pixel(1, 0), Some(Color::RED));
+
+        assert!(data.sub_image(2, 0, 2, 1).is_none());
+        let sub = data.sub_image(0, 0, 1, 1).unwrap();
+        assert_eq!(sub.get_pixel(0, 0), Some(Color::RED));
+    }
+
+    #[test]
+    fn color_key_and_premultiply() {
+        let mut data = ImageData::from_pixels(
+            2,
+            1,
+            ImageFormat::Rgba8Unorm,
+            vec![255, 0, 255, 255, 200, 100, 50, 0],
+        )
+        .unwrap();
+        data.color_key(Color::MAGENTA);
+        data.premultiply();
+        assert_eq!(data.pixels(), &[0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+}
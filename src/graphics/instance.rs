@@ -2,10 +2,10 @@ use crate::{context::Has, graphics::gpu::bind_group::BindGroupBuilder, GameError
 
 use super::{
     context::GraphicsContext,
+    culling::Viewport,
     draw::{DrawParam, DrawUniforms, Std140DrawUniforms},
     gpu::arc::{ArcBindGroup, ArcBindGroupLayout, ArcBuffer},
-    internal_canvas::InstanceArrayView,
-    transform_rect, Canvas, Draw, Drawable, Image, Mesh, Rect, WgpuContext,
+    transform_rect, Canvas, Drawable, Image, Mesh, Rect, WgpuContext,
 };
 use crevice::std140::AsStd140;
 use std::{
@@ -192,9 +192,10 @@ impl InstanceArray {
         &self.params
     }
 
-    pub(crate) fn flush_wgpu(&self, wgpu: &WgpuContext) -> GameResult {
+    /// Uploads the instances if they changed, returning the number of bytes written.
+    pub(crate) fn flush_wgpu(&self, wgpu: &WgpuContext) -> GameResult<u64> {
         if !self.dirty.load(SeqCst) {
-            return Ok(());
+            return Ok(0);
         } else {
             self.dirty.store(false, SeqCst);
         }
@@ -217,11 +218,10 @@ impl InstanceArray {
         self.capacity.store(len, SeqCst);
         //}
 
-        wgpu.queue.write_buffer(
-            &self.buffer.lock().unwrap(),
-            0,
-            bytemuck::cast_slice(self.uniforms.as_slice()),
-        );
+        let uniforms: &[u8] = bytemuck::cast_slice(self.uniforms.as_slice());
+        wgpu.queue
+            .write_buffer(&self.buffer.lock().unwrap(), 0, uniforms);
+        let mut written = uniforms.len() as u64;
 
         if self.ordered {
             let mut layers = BTreeMap::<_, Vec<_>>::new();
@@ -229,14 +229,13 @@ impl InstanceArray {
                 layers.entry(param.z).or_default().push(i as u32);
             }
             let indices = layers.into_values().flatten().collect::<Vec<_>>();
-            wgpu.queue.write_buffer(
-                &self.indices.lock().unwrap(),
-                0,
-                bytemuck::cast_slice(indices.as_slice()),
-            );
+            let indices: &[u8] = bytemuck::cast_slice(indices.as_slice());
+            wgpu.queue
+                .write_buffer(&self.indices.lock().unwrap(), 0, indices);
+            written += indices.len() as u64;
         }
 
-        Ok(())
+        Ok(written)
     }
 
     /// Changes the capacity of this `InstanceArray` while preserving instances.
@@ -282,6 +281,57 @@ impl InstanceArray {
         self.capacity.load(SeqCst)
     }
 
+    /// Returns the uniforms of the instances that can be seen in `viewport`, in drawing order.
+    ///
+    /// `bounds` are the bounds of the instanced mesh, and `param` and `scale` are applied the same
+    /// way as when drawing.
+    pub(crate) fn visible(
+        &self,
+        viewport: &Viewport,
+        bounds: Rect,
+        param: &DrawParam,
+        scale: bool,
+    ) -> Vec<Std140DrawUniforms> {
+        let global = glam::Mat4::from(DrawUniforms::from_param(param, None).transform);
+        let image_size = glam::vec2(self.image.width() as f32, self.image.height() as f32);
+        let visible = |i: usize| {
+            overlaps(
+                viewport,
+                bounds,
+                global,
+                scale.then_some(image_size),
+                &self.params[i],
+            )
+        };
+
+        let mut order = (0..self.params.len()).collect::<Vec<_>>();
+        if self.ordered {
+            order.sort_by_key(|&i| self.params[i].z);
+        }
+        order
+            .into_iter()
+            .filter(|&i| visible(i))
+            .map(|i| self.uniforms[i])
+            .collect()
+    }
+
+    /// Copies the instances, in drawing order, so that they can be culled after the array changed.
+    pub(crate) fn record(&self) -> RecordedInstances {
+        let mut instances = self
+            .params
+            .iter()
+            .copied()
+            .zip(self.uniforms.iter().copied())
+            .collect::<Vec<_>>();
+        if self.ordered {
+            instances.sort_by_key(|(param, _)| param.z);
+        }
+        RecordedInstances {
+            instances,
+            image_size: glam::vec2(self.image.width() as f32, self.image.height() as f32),
+        }
+    }
+
     /// This is equivalent to `<InstanceArray as Drawable>::dimensions()` (see [`Drawable::dimensions()`]), but with a mesh taken into account.
     ///
     /// Essentially, consider `<InstanceArray as Drawable>::dimensions()` to be the bounds when the [`InstanceArray`] is drawn with `canvas.draw()`,
@@ -304,6 +354,51 @@ impl InstanceArray {
     }
 }
 
+/// The instances of an [`InstanceArray`] as they were when recorded into a draw list, so they can
+/// still be culled one by one when the list is drawn.
+#[derive(Debug)]
+pub(crate) struct RecordedInstances {
+    instances: Vec<(DrawParam, Std140DrawUniforms)>,
+    image_size: glam::Vec2,
+}
+
+impl RecordedInstances {
+    /// As [`InstanceArray::visible`].
+    pub fn visible(
+        &self,
+        viewport: &Viewport,
+        bounds: Rect,
+        param: &DrawParam,
+        scale: bool,
+    ) -> Vec<Std140DrawUniforms> {
+        let global = glam::Mat4::from(DrawUniforms::from_param(param, None).transform);
+        let image_size = scale.then_some(self.image_size);
+        self.instances
+            .iter()
+            .filter(|(instance, _)| overlaps(viewport, bounds, global, image_size, instance))
+            .map(|&(_, uniforms)| uniforms)
+            .collect()
+    }
+}
+
+/// Whether an instance, moved by the `global` transform of its array, can be seen in `viewport`.
+fn overlaps(
+    viewport: &Viewport,
+    bounds: Rect,
+    global: glam::Mat4,
+    image_size: Option<glam::Vec2>,
+    instance: &DrawParam,
+) -> bool {
+    let mut transform =
+        global * glam::Mat4::from(DrawUniforms::from_param(instance, None).transform);
+    if let Some(image_size) = image_size {
+        // as in the instance shader, the image scale is applied in local space
+        let size = image_size * glam::vec2(instance.src.w, instance.src.h);
+        transform *= glam::Mat4::from_scale(size.extend(1.));
+    }
+    viewport.overlaps(transform, bounds)
+}
+
 impl Drawable for InstanceArray {
     fn draw(&self, canvas: &mut Canvas, param: impl Into<DrawParam>) {
         // Only flush (and then push a draw) if there are any instances to draw.
@@ -311,15 +406,8 @@ impl Drawable for InstanceArray {
         if self.instances().is_empty() {
             return;
         }
-        self.flush_wgpu(&canvas.wgpu).unwrap();
-        canvas.push_draw(
-            Draw::MeshInstances {
-                mesh: canvas.default_resources().mesh.clone(),
-                instances: InstanceArrayView::from_instances(self).unwrap(),
-                scale: true,
-            },
-            param.into(),
-        );
+        let mesh = canvas.default_resources().mesh.clone();
+        canvas.push_instances(mesh, self, param.into(), true);
     }
 
     fn dimensions(&self, gfx: &impl Has<GraphicsContext>) -> Option<Rect> {
@@ -1,22 +1,33 @@
 use super::{
     context::{FrameArenas, GraphicsContext},
-    draw::{DrawParam, DrawUniforms},
+    depth::DepthMode,
+    draw::{DrawParam, DrawUniforms, Std140DrawUniforms},
     gpu::{
+        arc::ArcBuffer,
         arc::{ArcBindGroup, ArcBindGroupLayout, ArcShaderModule, ArcTextureView},
         bind_group::{BindGroupBuilder, BindGroupCache, BindGroupLayoutBuilder},
         growing::{ArenaAllocation, GrowingBufferArena},
-        pipeline::{PipelineCache, RenderPipelineInfo},
+        pipeline::{PipelineCache, RenderPipelineInfo, DEPTH_STENCIL_FORMAT},
         text::{TextRenderer, TextVertex},
     },
     image::Image,
+    instance::RecordedInstances,
     mesh::{Mesh, Vertex},
+    mesh3d::{Mesh3d, Vertex3d},
     sampler::{Sampler, SamplerCache},
     shader::Shader,
+    stats::FrameStats,
+    stencil::StencilMode,
     BlendMode, Color, InstanceArray, LinearColor, Rect, Text, Transform, WgpuContext,
 };
 use crate::{GameError, GameResult};
 use crevice::std140::AsStd140;
-use std::{collections::HashMap, hash::Hash};
+use std::{collections::HashMap, hash::Hash, sync::Arc};
+
+/// Largest number of sprites drawn by a single batched draw call.
+pub(crate) const BATCH_CAPACITY: usize = 4096;
+/// Smallest number of sprites a batch allocation is padded to.
+const BATCH_MIN_INSTANCES: usize = 64;
 
 /// A canvas represents a render pass and is how you render primitives such as meshes and text onto images.
 #[allow(missing_debug_implementations)]
@@ -29,6 +40,8 @@ pub struct InternalCanvas<'a> {
     text_renderer: &'a mut TextRenderer,
     fonts: &'a HashMap<String, glyph_brush::FontId>,
     uniform_arena: &'a mut GrowingBufferArena,
+    batch_arena: &'a mut GrowingBufferArena,
+    stats: &'a mut FrameStats,
 
     shader: Shader,
     shader_bind_group: Option<(&'a wgpu::BindGroup, ArcBindGroupLayout, u32)>,
@@ -39,12 +52,17 @@ pub struct InternalCanvas<'a> {
     dirty_pipeline: bool,
     queuing_text: bool,
     blend_mode: BlendMode,
+    // whether the pass has a depth-stencil attachment; the modes are ignored otherwise
+    depth_stencil: bool,
+    depth_mode: DepthMode,
+    stencil_mode: StencilMode,
     pass: wgpu::RenderPass<'a>,
     samples: u32,
     format: wgpu::TextureFormat,
     text_uniforms: ArenaAllocation,
 
     draw_sm: ArcShaderModule,
+    draw3d_sm: ArcShaderModule,
     instance_sm: ArcShaderModule,
     instance_unordered_sm: ArcShaderModule,
     text_sm: ArcShaderModule,
@@ -61,12 +79,18 @@ impl<'a> InternalCanvas<'a> {
         gfx: &'a mut GraphicsContext,
         clear: impl Into<Option<Color>>,
         image: &'a Image,
+        depth_stencil: Option<&'a wgpu::TextureView>,
     ) -> GameResult<Self> {
         if image.samples() > 1 {
             return Err(GameError::RenderError(String::from("non-MSAA rendering requires an image with exactly 1 sample, for this image use Canvas::from_msaa instead")));
         }
+        if image.mip_levels() > 1 {
+            return Err(GameError::RenderError(String::from(
+                "cannot draw to an image with mip levels",
+            )));
+        }
 
-        Self::new(gfx, 1, image.format(), |cmd| {
+        Self::new(gfx, 1, image.format(), depth_stencil.is_some(), |cmd| {
             cmd.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -80,7 +104,7 @@ impl<'a> InternalCanvas<'a> {
                         store: true,
                     },
                 })],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: depth_stencil.map(depth_stencil_attachment),
             })
         })
     }
@@ -90,6 +114,7 @@ impl<'a> InternalCanvas<'a> {
         clear: impl Into<Option<Color>>,
         msaa_image: &'a Image,
         resolve_image: &'a Image,
+        depth_stencil: Option<&'a wgpu::TextureView>,
     ) -> GameResult<Self> {
         if msaa_image.samples() == 1 {
             return Err(GameError::RenderError(String::from(
@@ -103,35 +128,48 @@ impl<'a> InternalCanvas<'a> {
             )));
         }
 
+        if resolve_image.mip_levels() > 1 {
+            return Err(GameError::RenderError(String::from(
+                "cannot resolve into an image with mip levels",
+            )));
+        }
+
         if msaa_image.format() != resolve_image.format() {
             return Err(GameError::RenderError(String::from(
                 "MSAA image and resolve image must be the same format",
             )));
         }
 
-        Self::new(gfx, msaa_image.samples(), msaa_image.format(), |cmd| {
-            cmd.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: msaa_image.view.as_ref(),
-                    resolve_target: Some(resolve_image.view.as_ref()),
-                    ops: wgpu::Operations {
-                        load: match clear.into() {
-                            None => wgpu::LoadOp::Load,
-                            Some(color) => wgpu::LoadOp::Clear(LinearColor::from(color).into()),
+        Self::new(
+            gfx,
+            msaa_image.samples(),
+            msaa_image.format(),
+            depth_stencil.is_some(),
+            |cmd| {
+                cmd.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: None,
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: msaa_image.view.as_ref(),
+                        resolve_target: Some(resolve_image.view.as_ref()),
+                        ops: wgpu::Operations {
+                            load: match clear.into() {
+                                None => wgpu::LoadOp::Load,
+                                Some(color) => wgpu::LoadOp::Clear(LinearColor::from(color).into()),
+                            },
+                            store: true,
                         },
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: None,
-            })
-        })
+                    })],
+                    depth_stencil_attachment: depth_stencil.map(depth_stencil_attachment),
+                })
+            },
+        )
     }
 
     pub(crate) fn new(
         gfx: &'a mut GraphicsContext,
         samples: u32,
         format: wgpu::TextureFormat,
+        depth_stencil: bool,
         create_pass: impl FnOnce(&'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a>,
     ) -> GameResult<Self> {
         if gfx.fcx.is_none() {
@@ -149,6 +187,8 @@ impl<'a> InternalCanvas<'a> {
         let text_renderer = &mut gfx.text;
         let fonts = &gfx.fonts;
         let uniform_arena = &mut gfx.uniform_arena;
+        let batch_arena = &mut gfx.batch_arena;
+        let stats = &mut gfx.stats;
 
         let (arenas, mut pass) = {
             let fcx = gfx.fcx.as_mut().unwrap(/* see above */);
@@ -160,6 +200,7 @@ impl<'a> InternalCanvas<'a> {
         };
 
         pass.set_blend_constant(wgpu::Color::BLACK);
+        stats.render_passes += 1;
 
         let screen_coords = Rect {
             x: 0.,
@@ -182,15 +223,16 @@ impl<'a> InternalCanvas<'a> {
         let text_uniforms =
             uniform_arena.allocate(&wgpu.device, TextUniforms::std140_size_static() as _);
 
+        let text_uniforms_data = TextUniforms {
+            transform: transform.into(),
+        }
+        .as_std140();
         wgpu.queue.write_buffer(
             &text_uniforms.buffer,
             text_uniforms.offset,
-            (TextUniforms {
-                transform: transform.into(),
-            })
-            .as_std140()
-            .as_bytes(),
+            text_uniforms_data.as_bytes(),
         );
+        stats.bytes_written += text_uniforms_data.as_bytes().len() as u64;
 
         Ok(InternalCanvas {
             wgpu,
@@ -201,6 +243,8 @@ impl<'a> InternalCanvas<'a> {
             text_renderer,
             fonts,
             uniform_arena,
+            batch_arena,
+            stats,
 
             shader,
             shader_bind_group: None,
@@ -211,12 +255,16 @@ impl<'a> InternalCanvas<'a> {
             dirty_pipeline: true,
             queuing_text: false,
             blend_mode: BlendMode::ALPHA,
+            depth_stencil,
+            depth_mode: DepthMode::DISABLED,
+            stencil_mode: StencilMode::DISABLED,
             pass,
             samples,
             format,
             text_uniforms,
 
             draw_sm: gfx.draw_shader.clone(),
+            draw3d_sm: gfx.draw3d_shader.clone(),
             instance_sm: gfx.instance_shader.clone(),
             instance_unordered_sm: gfx.instance_unordered_shader.clone(),
             text_sm: gfx.text_shader.clone(),
@@ -275,6 +323,25 @@ impl<'a> InternalCanvas<'a> {
         self.blend_mode = blend_mode;
     }
 
+    pub fn set_stencil_mode(&mut self, mode: StencilMode) {
+        if self.depth_stencil {
+            self.flush_text();
+            if self.stencil_mode.reference != mode.reference {
+                self.pass.set_stencil_reference(mode.reference);
+            }
+            self.stencil_mode = mode;
+            self.dirty_pipeline = true;
+        }
+    }
+
+    pub fn set_depth_mode(&mut self, mode: DepthMode) {
+        if self.depth_stencil {
+            self.flush_text();
+            self.depth_mode = mode;
+            self.dirty_pipeline = true;
+        }
+    }
+
     pub fn set_premultiplied_text(&mut self, premultiplied_text: bool) {
         self.flush_text();
         self.premul_text = premultiplied_text;
@@ -286,15 +353,16 @@ impl<'a> InternalCanvas<'a> {
         self.text_uniforms = self
             .uniform_arena
             .allocate(&self.wgpu.device, TextUniforms::std140_size_static() as _);
+        let text_uniforms_data = TextUniforms {
+            transform: self.transform.into(),
+        }
+        .as_std140();
         self.wgpu.queue.write_buffer(
             &self.text_uniforms.buffer,
             self.text_uniforms.offset,
-            (TextUniforms {
-                transform: self.transform.into(),
-            })
-            .as_std140()
-            .as_bytes(),
+            text_uniforms_data.as_bytes(),
         );
+        self.stats.bytes_written += text_uniforms_data.as_bytes().len() as u64;
     }
 
     pub fn set_scissor_rect(&mut self, (x, y, w, h): (u32, u32, u32, u32)) {
@@ -302,10 +370,38 @@ impl<'a> InternalCanvas<'a> {
         self.pass.set_scissor_rect(x, y, w, h);
     }
 
-    #[allow(unsafe_code)]
     pub fn draw_mesh(&mut self, mesh: &'a Mesh, image: &Image, param: DrawParam, scale: bool) {
+        let image_scale = if scale {
+            Some(glam::Vec2::new(image.width() as f32, image.height() as f32).into())
+        } else {
+            None
+        };
+        self.draw_indexed(
+            ShaderType::Draw,
+            (&mesh.verts, &mesh.inds, mesh.index_count),
+            image,
+            DrawUniforms::from_param(&param, image_scale),
+        );
+    }
+
+    pub fn draw_mesh3d(&mut self, mesh: &'a Mesh3d, image: &Image, param: DrawParam) {
+        self.draw_indexed(
+            ShaderType::Draw3d,
+            (&mesh.verts, &mesh.inds, mesh.index_count),
+            image,
+            DrawUniforms::from_param(&param, None),
+        );
+    }
+
+    fn draw_indexed(
+        &mut self,
+        ty: ShaderType,
+        (verts, inds, index_count): (&'a ArcBuffer, &'a ArcBuffer, usize),
+        image: &Image,
+        mut uniforms: DrawUniforms,
+    ) {
         self.flush_text();
-        self.update_pipeline(ShaderType::Draw);
+        self.update_pipeline(ty);
 
         let alloc_size = DrawUniforms::std140_size_static() as u64;
         let uniform_alloc = self.uniform_arena.allocate(&self.wgpu.device, alloc_size);
@@ -323,25 +419,19 @@ impl<'a> InternalCanvas<'a> {
 
         self.set_image(image.clone());
 
-        let mut uniforms = DrawUniforms::from_param(
-            &param,
-            if scale {
-                Some(glam::Vec2::new(image.width() as f32, image.height() as f32).into())
-            } else {
-                None
-            },
-        );
         uniforms.transform = (self.transform * glam::Mat4::from(uniforms.transform)).into();
 
         // 1. allocate some uniform buffer memory from GrowingBufferArena.
         // 2. write the uniform data to that memory
         // 3. use a "dynamic offset" to offset into the memory
 
+        let uniforms = uniforms.as_std140();
         self.wgpu.queue.write_buffer(
             &uniform_alloc.buffer,
             uniform_alloc.offset,
-            uniforms.as_std140().as_bytes(),
+            uniforms.as_bytes(),
         );
+        self.stats.bytes_written += uniforms.as_bytes().len() as u64;
 
         self.pass.set_bind_group(
             0,
@@ -349,11 +439,99 @@ impl<'a> InternalCanvas<'a> {
             &[uniform_alloc.offset as u32], // <- the dynamic offset
         );
 
-        self.pass.set_vertex_buffer(0, mesh.verts.slice(..));
+        self.pass.set_vertex_buffer(0, verts.slice(..));
         self.pass
-            .set_index_buffer(mesh.inds.slice(..), wgpu::IndexFormat::Uint32);
+            .set_index_buffer(inds.slice(..), wgpu::IndexFormat::Uint32);
 
-        self.pass.draw_indexed(0..mesh.index_count as _, 0, 0..1);
+        self.pass.draw_indexed(0..index_count as _, 0, 0..1);
+        self.stats.draw_calls += 1;
+        self.stats.vertices += index_count;
+        self.stats.instances += 1;
+    }
+
+    /// Draws the mesh once per param, in order, with as few instanced draw calls as possible.
+    pub fn draw_mesh_batch(
+        &mut self,
+        mesh: &'a Mesh,
+        image: &Image,
+        params: &[DrawParam],
+        scale: bool,
+    ) {
+        // the image scale is baked into each instance, as `draw_mesh` does, rather than applied by
+        // the instance shader, which would scale the offsets along with the image
+        let image_scale = if scale {
+            Some(glam::Vec2::new(image.width() as f32, image.height() as f32).into())
+        } else {
+            None
+        };
+        let instances = params
+            .iter()
+            .map(|param| DrawUniforms::from_param(param, image_scale).as_std140())
+            .collect::<Vec<_>>();
+
+        self.draw_uploaded_instances(mesh, image, &instances, DrawParam::default(), false);
+    }
+
+    /// Uploads the instances to the batch arena and draws them in order, with as few draw calls as
+    /// possible.
+    fn draw_uploaded_instances(
+        &mut self,
+        mesh: &'a Mesh,
+        image: &Image,
+        instances: &[Std140DrawUniforms],
+        param: DrawParam,
+        scale: bool,
+    ) {
+        let stride = DrawUniforms::std140_size_static() as u64;
+
+        for chunk in instances.chunks(BATCH_CAPACITY) {
+            let bytes: &[u8] = bytemuck::cast_slice(chunk);
+
+            // the allocation is padded to a power of two instances so that only a few binding
+            // sizes exist per buffer, and is then selected by dynamic offset; this keeps the bind
+            // groups cacheable and draws from the first instance, which GL can't offset
+            let size = chunk.len().next_power_of_two().max(BATCH_MIN_INSTANCES) as u64 * stride;
+            let alloc = self.batch_arena.allocate(&self.wgpu.device, size);
+            self.wgpu
+                .queue
+                .write_buffer(&alloc.buffer, alloc.offset, bytes);
+            self.stats.bytes_written += bytes.len() as u64;
+
+            // the indices binding is unused by the unordered shader, but part of the layout
+            let (bind_group, _) = BindGroupBuilder::new()
+                .buffer(
+                    &alloc.buffer,
+                    0,
+                    wgpu::ShaderStages::VERTEX,
+                    wgpu::BufferBindingType::Storage { read_only: true },
+                    true,
+                    Some(size),
+                )
+                .buffer(
+                    &alloc.buffer,
+                    0,
+                    wgpu::ShaderStages::VERTEX,
+                    wgpu::BufferBindingType::Storage { read_only: true },
+                    false,
+                    None,
+                )
+                .create(&self.wgpu.device, self.bind_group_cache);
+            let bind_group = self.arenas.bind_groups.alloc(bind_group);
+
+            self.draw_instances(
+                mesh,
+                image,
+                bind_group,
+                &[alloc.offset as u32],
+                chunk.len() as u32,
+                ShaderType::Instance {
+                    ordered: false,
+                    dynamic: true,
+                },
+                param,
+                scale,
+            );
+        }
     }
 
     pub fn draw_mesh_instances(
@@ -369,9 +547,42 @@ impl<'a> InternalCanvas<'a> {
             return Ok(());
         }
 
-        self.update_pipeline(ShaderType::Instance {
-            ordered: instances.ordered,
-        });
+        if let Some(visible) = &instances.visible {
+            self.draw_uploaded_instances(mesh, &instances.image, visible, param, scale);
+            return Ok(());
+        }
+
+        self.draw_instances(
+            mesh,
+            &instances.image,
+            &instances.bind_group,
+            &[],
+            instances.len,
+            ShaderType::Instance {
+                ordered: instances.ordered,
+                dynamic: false,
+            },
+            param,
+            scale,
+        );
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_instances(
+        &mut self,
+        mesh: &'a Mesh,
+        image: &Image,
+        bind_group: &'a wgpu::BindGroup,
+        offsets: &[u32],
+        count: u32,
+        ty: ShaderType,
+        param: DrawParam,
+        scale: bool,
+    ) {
+        self.flush_text();
+        self.update_pipeline(ty);
 
         let alloc_size = u64::from(
             self.wgpu
@@ -392,7 +603,7 @@ impl<'a> InternalCanvas<'a> {
             )
             .create(&self.wgpu.device, self.bind_group_cache);
 
-        self.set_image(instances.image.clone());
+        self.set_image(image.clone());
 
         let uniforms = InstanceUniforms {
             transform: (self.transform
@@ -411,37 +622,37 @@ impl<'a> InternalCanvas<'a> {
             // we can't apply this when we first convert the instance array drawparams because we don't know the image size at the time the user inserts the drawparams.
             // we also can't apply image scaling in the global instance transform as it *must* be applied in local space.
             scale: if scale {
-                glam::Vec2::new(
-                    instances.image.width() as f32,
-                    instances.image.height() as f32,
-                )
+                glam::Vec2::new(image.width() as f32, image.height() as f32)
             } else {
                 glam::Vec2::ZERO
             }
             .into(),
         };
 
+        let uniforms = uniforms.as_std140();
         self.wgpu.queue.write_buffer(
             &uniform_alloc.buffer,
             uniform_alloc.offset,
-            uniforms.as_std140().as_bytes(),
+            uniforms.as_bytes(),
         );
+        self.stats.bytes_written += uniforms.as_bytes().len() as u64;
 
         self.pass.set_bind_group(
             0,
             self.arenas.bind_groups.alloc(uniform_bind_group),
             &[uniform_alloc.offset as u32],
         );
-        self.pass.set_bind_group(2, &instances.bind_group, &[]);
+        self.pass.set_bind_group(2, bind_group, offsets);
 
         self.pass.set_vertex_buffer(0, mesh.verts.slice(..));
         self.pass
             .set_index_buffer(mesh.inds.slice(..), wgpu::IndexFormat::Uint32);
 
         self.pass
-            .draw_indexed(0..mesh.index_count as _, 0, 0..instances.len as _);
-
-        Ok(())
+            .draw_indexed(0..mesh.index_count as _, 0, 0..count);
+        self.stats.draw_calls += 1;
+        self.stats.vertices += mesh.index_count * count as usize;
+        self.stats.instances += count as usize;
     }
 
     pub fn draw_bounded_text(&mut self, text: &Text, mut param: DrawParam) -> GameResult {
@@ -495,6 +706,7 @@ impl<'a> InternalCanvas<'a> {
                 &self.wgpu.queue,
                 self.arenas,
                 &mut self.pass,
+                self.stats,
             );
             if premul {
                 self.set_blend_mode(BlendMode::ALPHA);
@@ -524,7 +736,7 @@ impl<'a> InternalCanvas<'a> {
                 .buffer(
                     wgpu::ShaderStages::VERTEX,
                     wgpu::BufferBindingType::Storage { read_only: true },
-                    false,
+                    matches!(ty, ShaderType::Instance { dynamic: true, .. }),
                 )
                 .buffer(
                     wgpu::ShaderStages::VERTEX,
@@ -557,7 +769,7 @@ impl<'a> InternalCanvas<'a> {
             }
 
             let shader = match ty {
-                ShaderType::Draw | ShaderType::Instance { .. } => {
+                ShaderType::Draw | ShaderType::Draw3d | ShaderType::Instance { .. } => {
                     if let Some((bind_group, bind_group_layout, offset)) = &self.shader_bind_group {
                         self.pass.set_bind_group(3, bind_group, &[*offset]);
                         groups.push(bind_group_layout.clone());
@@ -590,7 +802,8 @@ impl<'a> InternalCanvas<'a> {
                         } else {
                             match ty {
                                 ShaderType::Draw => self.draw_sm.clone(),
-                                ShaderType::Instance { ordered } => {
+                                ShaderType::Draw3d => self.draw3d_sm.clone(),
+                                ShaderType::Instance { ordered, .. } => {
                                     if ordered {
                                         self.instance_sm.clone()
                                     } else {
@@ -604,9 +817,9 @@ impl<'a> InternalCanvas<'a> {
                             fs_module.clone()
                         } else {
                             match ty {
-                                ShaderType::Draw | ShaderType::Instance { .. } => {
-                                    self.draw_sm.clone()
-                                }
+                                ShaderType::Draw
+                                | ShaderType::Draw3d
+                                | ShaderType::Instance { .. } => self.draw_sm.clone(),
                                 ShaderType::Text => self.text_sm.clone(),
                             }
                         },
@@ -618,7 +831,18 @@ impl<'a> InternalCanvas<'a> {
                             color: self.blend_mode.color,
                             alpha: self.blend_mode.alpha,
                         }),
-                        depth: false,
+                        depth_stencil: self.depth_stencil.then(|| wgpu::DepthStencilState {
+                            format: DEPTH_STENCIL_FORMAT,
+                            depth_write_enabled: self.depth_mode.write,
+                            depth_compare: self.depth_mode.compare,
+                            stencil: self.stencil_mode.state(),
+                            bias: Default::default(),
+                        }),
+                        write_mask: if self.depth_stencil && !self.stencil_mode.write_color {
+                            wgpu::ColorWrites::empty()
+                        } else {
+                            wgpu::ColorWrites::ALL
+                        },
                         vertices: true,
                         topology: match ty {
                             ShaderType::Text => wgpu::PrimitiveTopology::TriangleStrip,
@@ -626,12 +850,14 @@ impl<'a> InternalCanvas<'a> {
                         },
                         vertex_layout: match ty {
                             ShaderType::Text => TextVertex::layout(),
+                            ShaderType::Draw3d => Vertex3d::layout(),
                             _ => Vertex::layout(),
                         },
                     },
                 ));
 
             self.pass.set_pipeline(pipeline);
+            self.stats.pipeline_switches += 1;
         }
     }
 
@@ -684,12 +910,16 @@ impl<'a> Drop for InternalCanvas<'a> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InstanceArrayView {
     pub bind_group: ArcBindGroup,
     pub image: Image,
     pub len: u32,
     pub ordered: bool,
+    // the instances left after culling, in drawing order
+    pub visible: Option<Vec<Std140DrawUniforms>>,
+    // the instances as recorded into a draw list, to cull them when it's drawn
+    pub recorded: Option<Arc<RecordedInstances>>,
 }
 
 impl InstanceArrayView {
@@ -703,6 +933,8 @@ impl InstanceArrayView {
             image: ia.image.clone(),
             len: ia.instances().len() as u32,
             ordered: ia.ordered,
+            visible: None,
+            recorded: None,
         })
     }
 }
@@ -710,7 +942,9 @@ impl InstanceArrayView {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum ShaderType {
     Draw,
-    Instance { ordered: bool },
+    Draw3d,
+    // `dynamic` instances are selected by a dynamic offset into their buffer
+    Instance { ordered: bool, dynamic: bool },
     Text,
 }
 
@@ -726,6 +960,22 @@ struct TextUniforms {
     transform: mint::ColumnMatrix4<f32>,
 }
 
+fn depth_stencil_attachment(
+    view: &wgpu::TextureView,
+) -> wgpu::RenderPassDepthStencilAttachment<'_> {
+    wgpu::RenderPassDepthStencilAttachment {
+        view,
+        depth_ops: Some(wgpu::Operations {
+            load: wgpu::LoadOp::Clear(1.),
+            store: false,
+        }),
+        stencil_ops: Some(wgpu::Operations {
+            load: wgpu::LoadOp::Clear(0),
+            store: false,
+        }),
+    }
+}
+
 pub(crate) fn screen_to_mat(screen: Rect) -> glam::Mat4 {
     glam::Mat4::orthographic_rh(
         screen.left(),
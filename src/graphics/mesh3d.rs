@@ -0,0 +1,176 @@
+use super::{
+    canvas::Draw, gpu::arc::ArcBuffer, Canvas, Color, DrawParam, Drawable, GraphicsContext, Image,
+    LinearColor, Rect,
+};
+use crate::context::Has;
+use wgpu::util::DeviceExt;
+
+/// Vertex format of [`Mesh3d`]es.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct Vertex3d {
+    /// `vec3` position.
+    pub position: [f32; 3],
+    /// `vec2` UV/texture coordinates.
+    pub uv: [f32; 2],
+    /// `vec4` color, in linear space as for 2D [`Vertex`](super::Vertex)es; convert a [`Color`]
+    /// with [`LinearColor::from`].
+    pub color: [f32; 4],
+}
+
+impl Vertex3d {
+    pub(crate) const fn layout() -> wgpu::VertexBufferLayout<'static> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 3] = [
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x3,
+                offset: 0,
+                shader_location: 0,
+            },
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x2,
+                offset: 12,
+                shader_location: 1,
+            },
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x4,
+                offset: 20,
+                shader_location: 2,
+            },
+        ];
+
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex3d>() as u64,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// A mesh with 3D vertices, stored on the GPU. Cheap to clone.
+///
+/// It's drawn like any other `Drawable`, in the same pass as sprites and text, with the
+/// `DrawParam` transform as its model matrix. A perspective projection is set with
+/// [`Canvas::set_projection`], and a [`DepthMode`](super::DepthMode) sorts the triangles:
+///
+/// ```rust,no_run
+/// # use ggez::{glam::*, graphics::{self, Color, DepthMode, DrawParam, Mesh3d}};
+/// # fn t(ctx: &mut ggez::Context, cube: &Mesh3d) -> ggez::GameResult {
+/// let (w, h) = ctx.gfx.drawable_size();
+/// let view = Mat4::look_at_rh(vec3(3., -4., 2.), Vec3::ZERO, Vec3::Z);
+/// let projection = Mat4::perspective_rh(1., w / h, 0.1, 100.);
+///
+/// let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
+/// canvas.set_depth_mode(DepthMode::LESS_EQUAL);
+/// canvas.set_projection(projection * view);
+/// canvas.draw(cube, DrawParam::new().transform(Mat4::from_rotation_z(0.5)));
+///
+/// // back to screen coordinates for the UI, drawn over the cube
+/// canvas.set_screen_coordinates(graphics::Rect::new(0., 0., w, h));
+/// canvas.draw(&graphics::Text::new("cube"), [10., 10.]);
+/// canvas.finish(ctx)
+/// # }
+/// ```
+///
+/// The image is sampled with the vertex UVs, and custom vertex shaders receive the position
+/// as a `vec3<f32>` at location 0.
+#[derive(Debug, Clone)]
+pub struct Mesh3d {
+    pub(crate) verts: ArcBuffer,
+    pub(crate) inds: ArcBuffer,
+    pub(crate) index_count: usize,
+    image: Option<Image>,
+}
+
+impl Mesh3d {
+    /// Creates a mesh from vertices and triangle indices.
+    pub fn new(gfx: &impl Has<GraphicsContext>, vertices: &[Vertex3d], indices: &[u32]) -> Self {
+        let device = &gfx.retrieve().wgpu.device;
+        let buffer = |contents: &[u8], usage: wgpu::BufferUsages| {
+            let descriptor = wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents,
+                usage: usage | wgpu::BufferUsages::COPY_DST,
+            };
+            ArcBuffer::new(device.create_buffer_init(&descriptor))
+        };
+
+        Mesh3d {
+            verts: buffer(bytemuck::cast_slice(vertices), wgpu::BufferUsages::VERTEX),
+            inds: buffer(bytemuck::cast_slice(indices), wgpu::BufferUsages::INDEX),
+            index_count: indices.len(),
+            image: None,
+        }
+    }
+
+    /// Creates a box of the given size centered on the origin, with the whole image on
+    /// every face.
+    pub fn new_cuboid(
+        gfx: &impl Has<GraphicsContext>,
+        size: impl Into<mint::Vector3<f32>>,
+        color: Color,
+    ) -> Self {
+        let size: mint::Vector3<f32> = size.into();
+        let half = glam::Vec3::from(size) / 2.;
+        let color: [f32; 4] = LinearColor::from(color).into();
+
+        // each face as its normal and the two axes spanning it
+        let faces = [
+            (glam::Vec3::X, glam::Vec3::Y, glam::Vec3::Z),
+            (glam::Vec3::NEG_X, glam::Vec3::NEG_Y, glam::Vec3::Z),
+            (glam::Vec3::Y, glam::Vec3::NEG_X, glam::Vec3::Z),
+            (glam::Vec3::NEG_Y, glam::Vec3::X, glam::Vec3::Z),
+            (glam::Vec3::Z, glam::Vec3::X, glam::Vec3::Y),
+            (glam::Vec3::NEG_Z, glam::Vec3::X, glam::Vec3::NEG_Y),
+        ];
+
+        let mut vertices = Vec::with_capacity(24);
+        let mut indices = Vec::with_capacity(36);
+        for (normal, right, up) in faces {
+            let base = vertices.len() as u32;
+            for (u, v) in [(0., 1.), (1., 1.), (1., 0.), (0., 0.)] {
+                let corner = normal + right * (u * 2. - 1.) + up * (1. - v * 2.);
+                vertices.push(Vertex3d {
+                    position: (corner * half).into(),
+                    uv: [u, v],
+                    color,
+                });
+            }
+            indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+
+        Self::new(gfx, &vertices, &indices)
+    }
+
+    /// Sets the image the mesh is textured with, or `None` for plain vertex colors.
+    #[must_use]
+    pub fn with_image(mut self, image: impl Into<Option<Image>>) -> Self {
+        self.image = image.into();
+        self
+    }
+
+    /// Returns the image the mesh is textured with.
+    #[inline]
+    pub fn image(&self) -> Option<&Image> {
+        self.image.as_ref()
+    }
+}
+
+impl Drawable for Mesh3d {
+    fn draw(&self, canvas: &mut Canvas, param: impl Into<DrawParam>) {
+        let image = self
+            .image
+            .clone()
+            .unwrap_or_else(|| canvas.default_resources().image.clone());
+        canvas.push_draw(
+            Draw::Mesh3d {
+                mesh: self.clone(),
+                image,
+            },
+            param.into(),
+        );
+    }
+
+    fn dimensions(&self, _gfx: &impl Has<GraphicsContext>) -> Option<Rect> {
+        None
+    }
+}
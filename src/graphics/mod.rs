@@ -21,23 +21,46 @@
 //! [custom shader]:Canvas::set_shader
 //! [blend mode]:Canvas::set_blend_mode
 
+pub(crate) mod animated_image;
+pub(crate) mod atlas;
+pub(crate) mod camera;
 pub(crate) mod canvas;
+pub(crate) mod capture;
+pub(crate) mod compressed;
 pub(crate) mod context;
+pub(crate) mod culling;
+pub(crate) mod depth;
 pub(crate) mod draw;
+pub(crate) mod draw_list;
+pub(crate) mod dynamic_texture;
+pub mod effects;
 pub(crate) mod gpu;
 pub(crate) mod image;
+pub(crate) mod image_data;
 pub(crate) mod instance;
 pub(crate) mod internal_canvas;
 pub(crate) mod mesh;
+pub(crate) mod mesh3d;
+pub(crate) mod nine_slice;
+pub(crate) mod overlay;
+pub(crate) mod particles;
+pub(crate) mod post_process;
 pub(crate) mod sampler;
 pub(crate) mod shader;
+pub(crate) mod sprite_sheet;
+pub(crate) mod stats;
+pub(crate) mod stencil;
 pub(crate) mod text;
+pub mod tilemap;
 mod types;
 
 pub use lyon::tessellation::{FillOptions, FillRule, LineCap, LineJoin, StrokeOptions};
 pub use {
-    self::image::*, canvas::*, context::*, draw::*, instance::*, mesh::*, sampler::*, shader::*,
-    text::*, types::*,
+    self::image::*, animated_image::AnimatedImage, atlas::*, camera::Camera2D, canvas::*,
+    capture::FrameRecorder, context::*, depth::DepthMode, draw::*, draw_list::DrawList,
+    dynamic_texture::DynamicTexture, image_data::ImageData, instance::*, mesh::*, mesh3d::*,
+    nine_slice::*, overlay::*, particles::*, post_process::PostProcess, sampler::*, shader::*,
+    sprite_sheet::*, stats::*, stencil::*, text::*, types::*,
 };
 
 /// Applies `DrawParam` to `Rect`.
@@ -0,0 +1,291 @@
+use super::{
+    context::GraphicsContext, image::Image, Canvas, DrawParam, Drawable, Mesh, MeshData, Rect,
+    Vertex,
+};
+use crate::context::Has;
+
+/// Widths of the borders of a [`NineSlice`] image, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Insets {
+    /// Width of the left border.
+    pub left: f32,
+    /// Height of the top border.
+    pub top: f32,
+    /// Width of the right border.
+    pub right: f32,
+    /// Height of the bottom border.
+    pub bottom: f32,
+}
+
+impl Insets {
+    /// Creates insets from the four border sizes.
+    pub const fn new(left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Insets {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// Creates insets with the same size on every side.
+    pub const fn uniform(size: f32) -> Self {
+        Self::new(size, size, size, size)
+    }
+}
+
+/// How the edges or the center of a [`NineSlice`] fill their part of the target rectangle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SliceMode {
+    /// Stretch the slice to fit.
+    #[default]
+    Stretch,
+    /// Repeat the slice at its original size, cutting off the last repetition.
+    Tile,
+}
+
+/// An image drawn as a scalable panel, also known as a nine-patch.
+///
+/// The image is cut into a 3x3 grid by the [`Insets`]. When it's drawn into a rectangle, the
+/// corners keep their size, the edges stretch or tile along one axis, and the center stretches
+/// or tiles along both. If the rectangle is smaller than the borders, they're shrunk to fit.
+///
+/// All nine parts are drawn with a single mesh, which is rebuilt when the rectangle or the
+/// modes change.
+///
+/// ```rust,no_run
+/// # use ggez::graphics::{self, Image, Insets, NineSlice, Rect, SliceMode};
+/// # fn t(ctx: &mut ggez::Context) -> ggez::GameResult {
+/// let image = Image::from_path(ctx, "/panel.png")?;
+/// let mut panel = NineSlice::new(ctx, image, Insets::uniform(8.), Rect::new(0., 0., 200., 120.));
+/// panel.set_modes(ctx, SliceMode::Tile, SliceMode::Stretch);
+///
+/// let mut canvas = graphics::Canvas::from_frame(ctx, None);
+/// canvas.draw(&panel, graphics::DrawParam::default().dest([20., 20.]));
+/// canvas.finish(ctx)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct NineSlice {
+    image: Image,
+    insets: Insets,
+    rect: Rect,
+    edges: SliceMode,
+    center: SliceMode,
+    mesh: Option<Mesh>,
+}
+
+impl NineSlice {
+    /// Creates a nine-slice drawn into `rect`, with stretched edges and center.
+    pub fn new(gfx: &impl Has<GraphicsContext>, image: Image, insets: Insets, rect: Rect) -> Self {
+        let mut slice = NineSlice {
+            image,
+            insets,
+            rect,
+            edges: SliceMode::Stretch,
+            center: SliceMode::Stretch,
+            mesh: None,
+        };
+        slice.rebuild(gfx);
+        slice
+    }
+
+    /// Returns the sliced image.
+    #[inline]
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// Returns the border sizes.
+    #[inline]
+    pub fn insets(&self) -> Insets {
+        self.insets
+    }
+
+    /// Returns the rectangle the image is drawn into.
+    #[inline]
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Changes the rectangle the image is drawn into.
+    pub fn set_rect(&mut self, gfx: &impl Has<GraphicsContext>, rect: Rect) {
+        if self.rect != rect {
+            self.rect = rect;
+            self.rebuild(gfx);
+        }
+    }
+
+    /// Changes the border sizes.
+    pub fn set_insets(&mut self, gfx: &impl Has<GraphicsContext>, insets: Insets) {
+        self.insets = insets;
+        self.rebuild(gfx);
+    }
+
+    /// Sets how the edges and the center fill their space.
+    pub fn set_modes(
+        &mut self,
+        gfx: &impl Has<GraphicsContext>,
+        edges: SliceMode,
+        center: SliceMode,
+    ) {
+        self.edges = edges;
+        self.center = center;
+        self.rebuild(gfx);
+    }
+
+    fn rebuild(&mut self, gfx: &impl Has<GraphicsContext>) {
+        let (iw, ih) = (self.image.width() as f32, self.image.height() as f32);
+        let Insets {
+            left,
+            top,
+            right,
+            bottom,
+        } = self.insets;
+        let columns = axis(self.rect.x, self.rect.w, left, right, iw);
+        let rows = axis(self.rect.y, self.rect.h, top, bottom, ih);
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for (row, &(y, v)) in rows.iter().enumerate() {
+            for (column, &(x, u)) in columns.iter().enumerate() {
+                let (tile_x, tile_y) = match (column, row) {
+                    (1, 1) => (self.center, self.center),
+                    (1, _) => (self.edges, SliceMode::Stretch),
+                    (_, 1) => (SliceMode::Stretch, self.edges),
+                    _ => (SliceMode::Stretch, SliceMode::Stretch),
+                };
+                for (x, u) in spans(x, u, iw, tile_x) {
+                    for (y, v) in spans(y, v, ih, tile_y) {
+                        quad(&mut vertices, &mut indices, x, y, u, v);
+                    }
+                }
+            }
+        }
+
+        self.mesh = (!indices.is_empty()).then(|| {
+            Mesh::from_data(
+                gfx,
+                MeshData {
+                    vertices: &vertices,
+                    indices: &indices,
+                },
+            )
+        });
+    }
+}
+
+impl Drawable for NineSlice {
+    fn draw(&self, canvas: &mut Canvas, param: impl Into<DrawParam>) {
+        if let Some(mesh) = &self.mesh {
+            canvas.draw_textured_mesh(mesh.clone(), self.image.clone(), param);
+        }
+    }
+
+    fn dimensions(&self, _gfx: &impl Has<GraphicsContext>) -> Option<Rect> {
+        Some(self.rect)
+    }
+}
+
+/// A range of positions paired with the range of texture coordinates drawn there.
+type Span = ((f32, f32), (f32, f32));
+
+/// Splits one axis of the target into the three slices, shrinking the borders
+/// if they don't fit.
+fn axis(start: f32, len: f32, before: f32, after: f32, size: f32) -> [Span; 3] {
+    let len = len.max(0.);
+    let shrink = if before + after > len && before + after > 0. {
+        len / (before + after)
+    } else {
+        1.
+    };
+    let (a, b) = (start + before * shrink, start + len - after * shrink);
+    let (ua, ub) = (before / size, 1. - after / size);
+    [
+        ((start, a), (0., ua)),
+        ((a, b), (ua, ub)),
+        ((b, start + len), (ub, 1.)),
+    ]
+}
+
+/// Cuts a slice into tiles of its original size, or leaves it whole when stretching.
+fn spans((p0, p1): (f32, f32), (u0, u1): (f32, f32), size: f32, mode: SliceMode) -> Vec<Span> {
+    if p1 - p0 <= 0. {
+        return Vec::new();
+    }
+    let tile = (u1 - u0) * size;
+    if mode == SliceMode::Stretch || tile <= 0. {
+        return vec![((p0, p1), (u0, u1))];
+    }
+
+    let mut tiles = Vec::new();
+    let mut p = p0;
+    while p < p1 {
+        let end = (p + tile).min(p1);
+        tiles.push(((p, end), (u0, u0 + (u1 - u0) * (end - p) / tile)));
+        p = end;
+    }
+    tiles
+}
+
+fn quad(
+    vertices: &mut Vec<Vertex>,
+    indices: &mut Vec<u32>,
+    (x0, x1): (f32, f32),
+    (y0, y1): (f32, f32),
+    (u0, u1): (f32, f32),
+    (v0, v1): (f32, f32),
+) {
+    let base = vertices.len() as u32;
+    for (position, uv) in [
+        ([x0, y0], [u0, v0]),
+        ([x1, y0], [u1, v0]),
+        ([x1, y1], [u1, v1]),
+        ([x0, y1], [u0, v1]),
+    ] {
+        vertices.push(Vertex {
+            position,
+            uv,
+            color: [1.; 4],
+        });
+    }
+    indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borders_shrink_to_fit() {
+        let [first, middle, last] = axis(10., 100., 8., 8., 32.);
+        assert_eq!(first, ((10., 18.), (0., 0.25)));
+        assert_eq!(middle, ((18., 102.), (0.25, 0.75)));
+        assert_eq!(last, ((102., 110.), (0.75, 1.)));
+
+        let [first, middle, last] = axis(0., 8., 8., 8., 32.);
+        assert_eq!(first.0, (0., 4.));
+        assert_eq!(middle.0, (4., 4.));
+        assert_eq!(last.0, (4., 8.));
+    }
+
+    #[test]
+    fn tiles_cut_off_the_last_repetition() {
+        // a 16 pixel slice tiled over 40 pixels
+        let tiles = spans((0., 40.), (0.25, 0.75), 32., SliceMode::Tile);
+        assert_eq!(
+            tiles,
+            [
+                ((0., 16.), (0.25, 0.75)),
+                ((16., 32.), (0.25, 0.75)),
+                ((32., 40.), (0.25, 0.5)),
+            ]
+        );
+        assert_eq!(
+            spans((0., 40.), (0.25, 0.75), 32., SliceMode::Stretch).len(),
+            1
+        );
+        assert!(spans((4., 4.), (0.25, 0.75), 32., SliceMode::Tile).is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+use super::{Canvas, Color, DrawMode, DrawParam, GraphicsContext, Mesh, MeshBuilder, Rect, Text};
+use crate::{input::keyboard::KeyCode, timer::TimeContext, GameResult};
+
+/// A built-in overlay showing frame timings and some renderer information.
+///
+/// When visible, the overlay is drawn by [`event::run`](crate::event::run) right after
+/// [`EventHandler::draw`](crate::event::EventHandler::draw), on top of the image that is
+/// going to be presented. It shows the FPS, a graph of the last frame times, the number of
+/// draw commands submitted by the last [`Canvas::finish`], and the window size and scale factor.
+///
+/// The overlay is hidden by default. It can be shown with [`DebugOverlay::set_visible`],
+/// or toggled at runtime by pressing the key set with [`DebugOverlay::set_toggle_key`]:
+///
+/// ```rust,no_run
+/// # use ggez::input::keyboard::KeyCode;
+/// # fn t(ctx: &mut ggez::Context) {
+/// ctx.debug_overlay.set_toggle_key(KeyCode::F3);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DebugOverlay {
+    visible: bool,
+    toggle_key: Option<KeyCode>,
+}
+
+impl DebugOverlay {
+    const MARGIN: f32 = 8.;
+    const PADDING: f32 = 6.;
+    const GRAPH_WIDTH: f32 = 200.;
+    const GRAPH_HEIGHT: f32 = 48.;
+    // The graph is scaled so that frames up to this long (30 FPS) always fit.
+    const GRAPH_MIN_RANGE: f32 = 1000. / 30.;
+
+    /// Creates a new, hidden overlay without a toggle key.
+    pub fn new() -> Self {
+        DebugOverlay {
+            visible: false,
+            toggle_key: None,
+        }
+    }
+
+    /// Returns whether the overlay is currently drawn.
+    #[inline]
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Shows or hides the overlay.
+    #[inline]
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Flips the visibility of the overlay.
+    #[inline]
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Returns the key that toggles the overlay, if any.
+    #[inline]
+    pub fn toggle_key(&self) -> Option<KeyCode> {
+        self.toggle_key
+    }
+
+    /// Sets the key that toggles the overlay. `None` disables toggling by keyboard.
+    #[inline]
+    pub fn set_toggle_key(&mut self, key: impl Into<Option<KeyCode>>) {
+        self.toggle_key = key.into();
+    }
+
+    /// Draws the overlay on top of the image that will be presented this frame.
+    ///
+    /// Does nothing if the overlay is hidden. [`event::run`](crate::event::run) calls this for you;
+    /// you only need to call it yourself when rolling your own event loop, between
+    /// [`GraphicsContext::begin_frame`] and [`GraphicsContext::end_frame`].
+    pub fn draw(&self, gfx: &mut GraphicsContext, time: &TimeContext) -> GameResult {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let target = match &gfx.fcx {
+            Some(fcx) => fcx.present.clone(),
+            None => return Ok(()),
+        };
+        // the presented image may be any image, but we can only draw to canvas images
+        if !target
+            .texture
+            .usage()
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+            || target.samples() > 1
+        {
+            return Ok(());
+        }
+
+        let draws = gfx.canvas_draws;
+        let (width, height) = gfx.drawable_size();
+        let scale_factor = gfx.window.scale_factor();
+
+        let mut text = Text::new(format!(
+            "FPS: {:.1} ({:.2} ms)\nDraws: {}\nWindow: {}x{} @ {:.2}x",
+            time.fps(),
+            time.average_delta().as_secs_f64() * 1000.,
+            draws,
+            width,
+            height,
+            scale_factor,
+        ));
+        let _ = text.set_scale(14.);
+        let text_size = text.measure(gfx)?;
+
+        let origin = glam::vec2(Self::MARGIN + Self::PADDING, Self::MARGIN + Self::PADDING);
+        let graph_top = origin.y + text_size.y + Self::PADDING;
+        let panel = Rect::new(
+            Self::MARGIN,
+            Self::MARGIN,
+            text_size.x.max(Self::GRAPH_WIDTH) + 2. * Self::PADDING,
+            text_size.y + Self::GRAPH_HEIGHT + 3. * Self::PADDING,
+        );
+
+        let frame_times = time
+            .frame_durations()
+            .map(|d| d.as_secs_f32() * 1000.)
+            .collect::<Vec<_>>();
+        let range = frame_times
+            .iter()
+            .copied()
+            .fold(Self::GRAPH_MIN_RANGE, f32::max);
+        let step = Self::GRAPH_WIDTH / (frame_times.len().max(2) - 1) as f32;
+        let to_graph = |ms: f32| graph_top + Self::GRAPH_HEIGHT * (1. - ms / range);
+
+        let mut builder = MeshBuilder::new();
+        let _ = builder.rectangle(DrawMode::fill(), panel, Color::new(0., 0., 0., 0.7))?;
+        let _ = builder.line(
+            &[
+                glam::vec2(origin.x, to_graph(1000. / 60.)),
+                glam::vec2(origin.x + Self::GRAPH_WIDTH, to_graph(1000. / 60.)),
+            ],
+            1.,
+            Color::new(1., 1., 0., 0.5),
+        )?;
+        if frame_times.len() > 1 {
+            let points = frame_times
+                .iter()
+                .enumerate()
+                .map(|(i, &ms)| glam::vec2(origin.x + i as f32 * step, to_graph(ms)))
+                .collect::<Vec<_>>();
+            let _ = builder.line(&points, 1., Color::GREEN)?;
+        }
+        let mesh = Mesh::from_data(gfx, builder.build());
+
+        let mut canvas = Canvas::from_image(gfx, target, None);
+        canvas.draw(&mesh, DrawParam::default());
+        canvas.draw(&text, DrawParam::default().dest(origin).color(Color::WHITE));
+        canvas.finish(gfx)
+    }
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,537 @@
+use super::{
+    context::GraphicsContext, image::Image, Canvas, Color, DrawParam, Drawable, InstanceArray, Rect,
+};
+use crate::{context::Has, timer::TimeContext};
+use glam::Vec2;
+use std::{f32::consts::TAU, time::Duration};
+
+/// Where new particles appear, relative to the position of the [`ParticleSystem`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SpawnShape {
+    /// At the position itself.
+    #[default]
+    Point,
+    /// Anywhere inside a circle around the position.
+    Circle {
+        /// Radius of the circle.
+        radius: f32,
+    },
+    /// Anywhere inside a rectangle centered on the position.
+    Rect {
+        /// Width of the rectangle.
+        width: f32,
+        /// Height of the rectangle.
+        height: f32,
+    },
+    /// Anywhere on a line from the position to `to`, which is relative to the position.
+    Line {
+        /// The end of the line.
+        to: mint::Point2<f32>,
+    },
+}
+
+/// Whether particles follow the [`ParticleSystem`] when it moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ParticleSpace {
+    /// Particles stay where they were emitted, like smoke left behind by a rocket.
+    #[default]
+    World,
+    /// Particles move along with the system, like flames on a torch that's carried around.
+    Local,
+}
+
+/// Values keyed by the fraction `0.0..=1.0` of a particle's lifetime, interpolated linearly.
+#[derive(Debug, Clone, PartialEq)]
+struct Curve<T> {
+    keys: Vec<(f32, T)>,
+}
+
+impl<T: Copy> Curve<T> {
+    fn new(keys: impl IntoIterator<Item = (f32, T)>, default: T) -> Self {
+        let mut keys: Vec<_> = keys.into_iter().collect();
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if keys.is_empty() {
+            keys.push((0., default));
+        }
+        Curve { keys }
+    }
+
+    fn sample(&self, t: f32, lerp: impl Fn(T, T, f32) -> T) -> T {
+        let next = self.keys.partition_point(|&(key, _)| key <= t);
+        match (
+            next.checked_sub(1).map(|i| self.keys[i]),
+            self.keys.get(next),
+        ) {
+            (Some((t0, a)), Some(&(t1, b))) => lerp(a, b, (t - t0) / (t1 - t0)),
+            (Some((_, a)), None) => a,
+            (None, Some(&(_, b))) => b,
+            (None, None) => unreachable!("curves have at least one key"),
+        }
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    Color::new(
+        lerp(a.r, b.r, t),
+        lerp(a.g, b.g, t),
+        lerp(a.b, b.b, t),
+        lerp(a.a, b.a, t),
+    )
+}
+
+/// Describes how a [`ParticleSystem`] emits particles and how they behave.
+///
+/// All ranges are `(min, max)` pairs that particles pick a random value from.
+/// Times are in seconds, angles in radians and distances in pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Emitter {
+    rate: f32,
+    bursts: Vec<(f32, u32)>,
+    max_particles: usize,
+    lifetime: (f32, f32),
+    direction: f32,
+    spread: f32,
+    speed: (f32, f32),
+    acceleration: Vec2,
+    rotation: (f32, f32),
+    angular_velocity: (f32, f32),
+    shape: SpawnShape,
+    space: ParticleSpace,
+    seed: u64,
+    color: Curve<Color>,
+    scale: Curve<f32>,
+    alpha: Curve<f32>,
+}
+
+impl Default for Emitter {
+    fn default() -> Self {
+        Emitter {
+            rate: 10.,
+            bursts: Vec::new(),
+            max_particles: 10_000,
+            lifetime: (1., 1.),
+            direction: 0.,
+            spread: TAU,
+            speed: (50., 50.),
+            acceleration: Vec2::ZERO,
+            rotation: (0., 0.),
+            angular_velocity: (0., 0.),
+            shape: SpawnShape::Point,
+            space: ParticleSpace::World,
+            seed: 0,
+            color: Curve::new([], Color::WHITE),
+            scale: Curve::new([], 1.),
+            alpha: Curve::new([], 1.),
+        }
+    }
+}
+
+impl Emitter {
+    /// Creates an emitter with ten particles per second, moving in all directions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many particles are emitted per second; `0.0` only emits bursts.
+    #[must_use]
+    pub fn rate(mut self, per_second: f32) -> Self {
+        self.rate = per_second.max(0.);
+        self
+    }
+
+    /// Emits `count` particles at once, `time` seconds after the system starts.
+    #[must_use]
+    pub fn burst(mut self, time: f32, count: u32) -> Self {
+        self.bursts.push((time, count));
+        self
+    }
+
+    /// Sets the most particles alive at once; further particles aren't emitted.
+    #[must_use]
+    pub fn max_particles(mut self, max: usize) -> Self {
+        self.max_particles = max;
+        self
+    }
+
+    /// Sets how long particles live.
+    #[must_use]
+    pub fn lifetime(mut self, min: f32, max: f32) -> Self {
+        self.lifetime = (min, max);
+        self
+    }
+
+    /// Sets the direction particles are emitted in, and how far they may deviate from it
+    /// in total. A spread of `TAU` emits in all directions.
+    #[must_use]
+    pub fn direction(mut self, angle: f32, spread: f32) -> Self {
+        self.direction = angle;
+        self.spread = spread;
+        self
+    }
+
+    /// Sets the initial speed of particles.
+    #[must_use]
+    pub fn speed(mut self, min: f32, max: f32) -> Self {
+        self.speed = (min, max);
+        self
+    }
+
+    /// Sets a constant acceleration, such as gravity.
+    #[must_use]
+    pub fn acceleration(mut self, acceleration: impl Into<mint::Vector2<f32>>) -> Self {
+        self.acceleration = Vec2::from(acceleration.into());
+        self
+    }
+
+    /// Sets the initial rotation of particles.
+    #[must_use]
+    pub fn rotation(mut self, min: f32, max: f32) -> Self {
+        self.rotation = (min, max);
+        self
+    }
+
+    /// Sets how fast particles spin, in radians per second.
+    #[must_use]
+    pub fn angular_velocity(mut self, min: f32, max: f32) -> Self {
+        self.angular_velocity = (min, max);
+        self
+    }
+
+    /// Sets where particles appear.
+    #[must_use]
+    pub fn shape(mut self, shape: SpawnShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets whether particles follow the system when it moves.
+    #[must_use]
+    pub fn space(mut self, space: ParticleSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Sets the seed of the random numbers. Systems with the same emitter and seed,
+    /// updated with the same time steps, produce the same particles.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the color of particles over their lifetime, as `(fraction of lifetime, color)` keys.
+    #[must_use]
+    pub fn color_curve(mut self, keys: impl IntoIterator<Item = (f32, Color)>) -> Self {
+        self.color = Curve::new(keys, Color::WHITE);
+        self
+    }
+
+    /// Sets the scale of particles over their lifetime, as `(fraction of lifetime, scale)` keys.
+    #[must_use]
+    pub fn scale_curve(mut self, keys: impl IntoIterator<Item = (f32, f32)>) -> Self {
+        self.scale = Curve::new(keys, 1.);
+        self
+    }
+
+    /// Sets the opacity of particles over their lifetime, as `(fraction of lifetime, alpha)`
+    /// keys. It's multiplied with the alpha of the color curve.
+    #[must_use]
+    pub fn alpha_curve(mut self, keys: impl IntoIterator<Item = (f32, f32)>) -> Self {
+        self.alpha = Curve::new(keys, 1.);
+        self
+    }
+}
+
+/// A small, fast generator (SplitMix64); particles don't need more.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0.0..1.0`.
+    fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn range(&mut self, (min, max): (f32, f32)) -> f32 {
+        min + (max - min) * self.unit()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    rotation: f32,
+    angular_velocity: f32,
+    age: f32,
+    lifetime: f32,
+}
+
+/// Simulates particles on the CPU and draws them all with one instanced draw.
+///
+/// ```rust,no_run
+/// # use ggez::graphics::{self, Color, Emitter, Image, ParticleSystem, SpawnShape};
+/// # fn t(ctx: &mut ggez::Context) -> ggez::GameResult {
+/// let emitter = Emitter::new()
+///     .rate(200.)
+///     .lifetime(0.5, 1.5)
+///     .direction(-std::f32::consts::FRAC_PI_2, 0.6)
+///     .speed(80., 160.)
+///     .acceleration([0., 120.])
+///     .shape(SpawnShape::Circle { radius: 6. })
+///     .color_curve([(0., Color::YELLOW), (1., Color::RED)])
+///     .alpha_curve([(0.7, 1.), (1., 0.)]);
+/// let mut fire = ParticleSystem::new(ctx, Image::from_path(ctx, "/spark.png")?, emitter);
+/// fire.set_position([400., 300.]);
+///
+/// // once per frame
+/// fire.update(&ctx.time);
+/// let mut canvas = graphics::Canvas::from_frame(ctx, None);
+/// canvas.draw(&fire, graphics::DrawParam::default());
+/// canvas.finish(ctx)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ParticleSystem {
+    emitter: Emitter,
+    particles: Vec<Particle>,
+    instances: InstanceArray,
+    rng: Rng,
+    position: Vec2,
+    time: f32,
+    pending: f32,
+    emitting: bool,
+}
+
+impl ParticleSystem {
+    /// Creates a particle system drawing particles with `image`, centered on the particle.
+    ///
+    /// If `image` is `None`, particles are 1x1 white squares, to be sized by the scale curve.
+    pub fn new(
+        gfx: &impl Has<GraphicsContext>,
+        image: impl Into<Option<Image>>,
+        emitter: Emitter,
+    ) -> Self {
+        ParticleSystem {
+            rng: Rng(emitter.seed),
+            emitter,
+            particles: Vec::new(),
+            instances: InstanceArray::new(gfx, image),
+            position: Vec2::ZERO,
+            time: 0.,
+            pending: 0.,
+            emitting: true,
+        }
+    }
+
+    /// Returns the emitter settings.
+    #[inline]
+    pub fn emitter(&self) -> &Emitter {
+        &self.emitter
+    }
+
+    /// Replaces the emitter settings, e.g. with `system.emitter().clone().rate(50.)`.
+    ///
+    /// Particles already alive keep the values they were emitted with, but follow the new
+    /// curves. The new seed is only used once the system is [reset](Self::reset).
+    pub fn set_emitter(&mut self, emitter: Emitter) {
+        self.emitter = emitter;
+    }
+
+    /// Moves the point particles are emitted from.
+    pub fn set_position(&mut self, position: impl Into<mint::Point2<f32>>) {
+        let position: mint::Point2<f32> = position.into();
+        self.position = Vec2::new(position.x, position.y);
+    }
+
+    /// Returns the point particles are emitted from.
+    pub fn position(&self) -> mint::Point2<f32> {
+        self.position.into()
+    }
+
+    /// Resumes continuous emission.
+    pub fn start(&mut self) {
+        self.emitting = true;
+    }
+
+    /// Stops continuous emission and scheduled bursts; live particles play out.
+    pub fn stop(&mut self) {
+        self.emitting = false;
+    }
+
+    /// Returns `true` unless emission was stopped.
+    #[inline]
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+
+    /// Returns the number of live particles.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Returns `true` if there are no live particles.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Removes all particles and starts over, with scheduled bursts and the random
+    /// numbers reset to the seed.
+    pub fn reset(&mut self) {
+        self.particles.clear();
+        self.instances.clear();
+        self.rng = Rng(self.emitter.seed);
+        self.time = 0.;
+        self.pending = 0.;
+        self.emitting = true;
+    }
+
+    /// Emits `count` particles right away, regardless of whether emission is stopped.
+    pub fn burst(&mut self, count: u32) {
+        for _ in 0..count {
+            self.emit();
+        }
+        self.rebuild();
+    }
+
+    /// Advances the simulation by the duration of the last frame.
+    pub fn update(&mut self, time: &TimeContext) {
+        self.advance(time.delta());
+    }
+
+    /// Advances the simulation by `dt`.
+    pub fn advance(&mut self, dt: Duration) {
+        let dt = dt.as_secs_f32();
+        let before = self.time;
+        self.time += dt;
+
+        let acceleration = self.emitter.acceleration;
+        self.particles.retain_mut(|p| {
+            p.age += dt;
+            p.velocity += acceleration * dt;
+            p.position += p.velocity * dt;
+            p.rotation += p.angular_velocity * dt;
+            p.age < p.lifetime
+        });
+
+        if self.emitting {
+            self.pending += self.emitter.rate * dt;
+            let mut count = self.pending as u32;
+            self.pending -= count as f32;
+            count += self
+                .emitter
+                .bursts
+                .iter()
+                .filter(|&&(t, _)| before <= t && t < self.time)
+                .map(|&(_, count)| count)
+                .sum::<u32>();
+            for _ in 0..count {
+                self.emit();
+            }
+        }
+
+        self.rebuild();
+    }
+
+    fn emit(&mut self) {
+        if self.particles.len() >= self.emitter.max_particles {
+            return;
+        }
+        let e = &self.emitter;
+        let rng = &mut self.rng;
+
+        let offset = match e.shape {
+            SpawnShape::Point => Vec2::ZERO,
+            SpawnShape::Circle { radius } => {
+                // the square root spreads particles evenly over the area
+                Vec2::from_angle(rng.unit() * TAU) * radius * rng.unit().sqrt()
+            }
+            SpawnShape::Rect { width, height } => {
+                Vec2::new((rng.unit() - 0.5) * width, (rng.unit() - 0.5) * height)
+            }
+            SpawnShape::Line { to } => Vec2::new(to.x, to.y) * rng.unit(),
+        };
+        let origin = match e.space {
+            ParticleSpace::World => self.position,
+            ParticleSpace::Local => Vec2::ZERO,
+        };
+        let angle = e.direction + (rng.unit() - 0.5) * e.spread;
+
+        self.particles.push(Particle {
+            position: origin + offset,
+            velocity: Vec2::from_angle(angle) * rng.range(e.speed),
+            rotation: rng.range(e.rotation),
+            angular_velocity: rng.range(e.angular_velocity),
+            age: 0.,
+            lifetime: rng.range(e.lifetime).max(f32::EPSILON),
+        });
+    }
+
+    fn rebuild(&mut self) {
+        let e = &self.emitter;
+        let origin = match e.space {
+            ParticleSpace::World => Vec2::ZERO,
+            ParticleSpace::Local => self.position,
+        };
+        self.instances.set(self.particles.iter().map(|p| {
+            let t = p.age / p.lifetime;
+            let mut color = e.color.sample(t, lerp_color);
+            color.a *= e.alpha.sample(t, lerp);
+            let scale = e.scale.sample(t, lerp);
+            DrawParam::default()
+                .dest(origin + p.position)
+                .rotation(p.rotation)
+                .scale([scale, scale])
+                .offset([0.5, 0.5])
+                .color(color)
+        }));
+    }
+}
+
+impl Drawable for ParticleSystem {
+    fn draw(&self, canvas: &mut Canvas, param: impl Into<DrawParam>) {
+        self.instances.draw(canvas, param)
+    }
+
+    fn dimensions(&self, gfx: &impl Has<GraphicsContext>) -> Option<Rect> {
+        self.instances.dimensions(gfx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves() {
+        let curve = Curve::new([(1., 0.), (0.5, 1.)], 1.);
+        assert_eq!(curve.sample(0., lerp), 1.);
+        assert_eq!(curve.sample(0.75, lerp), 0.5);
+        assert_eq!(curve.sample(2., lerp), 0.);
+        assert_eq!(Curve::new([], 3.).sample(0.5, lerp), 3.);
+    }
+
+    #[test]
+    fn seeded_random_numbers() {
+        let (mut a, mut b) = (Rng(7), Rng(7));
+        let first: Vec<_> = (0..100).map(|_| a.unit()).collect();
+        assert!(first.iter().all(|x| (0. ..1.).contains(x)));
+        assert_eq!(first, (0..100).map(|_| b.unit()).collect::<Vec<_>>());
+        assert_ne!(Rng(8).unit(), first[0]);
+    }
+}
@@ -0,0 +1,265 @@
+use crevice::std140::AsStd140;
+
+use super::{
+    canvas::default_shader,
+    gpu::arc::{ArcBindGroup, ArcBindGroupLayout},
+    BlendMode, Canvas, Color, DrawParam, GraphicsContext, Image, ImageFormat, Sampler, ScreenImage,
+    Shader, ShaderParams,
+};
+use crate::{context::HasMut, GameResult};
+
+#[derive(Debug, Clone)]
+struct Pass {
+    shader: Shader,
+    params: Option<(ArcBindGroup, ArcBindGroupLayout, u32)>,
+    sampler: Sampler,
+    enabled: bool,
+}
+
+/// An ordered chain of full-screen fragment shaders.
+///
+/// Each pass draws the output of the previous one through its [`Shader`], so the shaders
+/// sample the previous image the same way they would sample any image drawn on a
+/// [`Canvas`]. The intermediate images are [`ScreenImage`]s that are reused every frame and
+/// follow the size of the window.
+///
+/// A typical frame renders the scene into [`PostProcess::input`] and then presents the
+/// processed result:
+///
+/// ```rust,no_run
+/// # use ggez::graphics::{self, Color, PostProcess};
+/// # fn t(ctx: &mut ggez::Context, post: &mut PostProcess) -> ggez::GameResult {
+/// let scene = post.input(ctx);
+/// let mut canvas = graphics::Canvas::from_image(ctx, scene.clone(), Color::BLACK);
+/// // ... draw the scene ...
+/// canvas.finish(ctx)?;
+///
+/// post.present(ctx, &scene)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Shader parameters are captured when they are passed in; like with
+/// [`Canvas::set_shader_params`], [`PostProcess::set_params`] needs to be called again after
+/// [`ShaderParams::set_uniforms`] for new uniforms to take effect.
+#[derive(Debug, Clone)]
+pub struct PostProcess {
+    passes: Vec<Pass>,
+    buffers: [ScreenImage; 2],
+}
+
+impl PostProcess {
+    /// Creates an empty chain whose intermediate images have the format of the window surface.
+    pub fn new(gfx: &mut impl HasMut<GraphicsContext>) -> Self {
+        let format = gfx.retrieve_mut().surface_format();
+        Self::with_format(gfx, format)
+    }
+
+    /// Creates an empty chain whose intermediate images have the given format, e.g.
+    /// [`ImageFormat::Rgba16Float`] to keep HDR values between passes.
+    pub fn with_format(gfx: &mut impl HasMut<GraphicsContext>, format: ImageFormat) -> Self {
+        let gfx = gfx.retrieve_mut();
+        PostProcess {
+            passes: Vec::new(),
+            buffers: [
+                ScreenImage::new(gfx, format, 1., 1., 1),
+                ScreenImage::new(gfx, format, 1., 1., 1),
+            ],
+        }
+    }
+
+    /// Appends a pass without shader parameters, returning its index.
+    pub fn push(&mut self, shader: Shader) -> usize {
+        self.passes.push(Pass {
+            shader,
+            params: None,
+            sampler: Sampler::default(),
+            enabled: true,
+        });
+        self.passes.len() - 1
+    }
+
+    /// Appends a pass with shader parameters, returning its index.
+    pub fn push_with_params<Uniforms: AsStd140>(
+        &mut self,
+        shader: Shader,
+        params: &ShaderParams<Uniforms>,
+    ) -> usize {
+        let index = self.push(shader);
+        self.set_params(index, params);
+        index
+    }
+
+    /// Replaces the shader parameters of a pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_params<Uniforms: AsStd140>(
+        &mut self,
+        index: usize,
+        params: &ShaderParams<Uniforms>,
+    ) {
+        self.passes[index].params = Some((
+            params.bind_group.clone().unwrap(/* always Some */),
+            params.layout.clone().unwrap(/* always Some */),
+            params.buffer_offset,
+        ));
+    }
+
+    /// Sets the sampler a pass reads the previous image with. Defaults to linear filtering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_sampler(&mut self, index: usize, sampler: impl Into<Sampler>) {
+        self.passes[index].sampler = sampler.into();
+    }
+
+    /// Enables or disables a pass without removing it from the chain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        self.passes[index].enabled = enabled;
+    }
+
+    /// Returns whether a pass is enabled, or `None` if `index` is out of bounds.
+    pub fn is_enabled(&self, index: usize) -> Option<bool> {
+        self.passes.get(index).map(|pass| pass.enabled)
+    }
+
+    /// Removes a pass, shifting the indices of the passes after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) {
+        let _ = self.passes.remove(index);
+    }
+
+    /// Removes all passes.
+    pub fn clear(&mut self) {
+        self.passes.clear();
+    }
+
+    /// Returns the number of passes, including disabled ones.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.passes.len()
+    }
+
+    /// Returns `true` if there are no passes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    /// Returns a window-sized image to render the scene into before processing it.
+    ///
+    /// This is one of the intermediate images, so processing it doesn't need an extra copy.
+    pub fn input(&mut self, gfx: &mut impl HasMut<GraphicsContext>) -> Image {
+        self.buffers[0].image(gfx.retrieve_mut())
+    }
+
+    /// Runs `source` through every enabled pass and returns the result.
+    ///
+    /// The returned image is overwritten by the next call, so it should be used before then.
+    /// Without enabled passes, `source` itself is returned.
+    pub fn process(
+        &mut self,
+        gfx: &mut impl HasMut<GraphicsContext>,
+        source: &Image,
+    ) -> GameResult<Image> {
+        self.run(gfx.retrieve_mut(), source, None)
+    }
+
+    /// Runs `source` through every enabled pass, with the last one drawing into `output`.
+    ///
+    /// `output` must be a canvas image with a sample count of 1. Without enabled passes, `source`
+    /// is copied into it, or left as is if `output` is `source`; with enabled passes, `output`
+    /// must not be `source`.
+    pub fn process_into(
+        &mut self,
+        gfx: &mut impl HasMut<GraphicsContext>,
+        source: &Image,
+        output: &Image,
+    ) -> GameResult {
+        let _ = self.run(gfx.retrieve_mut(), source, Some(output))?;
+        Ok(())
+    }
+
+    /// Runs `source` through every enabled pass and presents the result at the end of the frame
+    /// with [`GraphicsContext::present`].
+    pub fn present(
+        &mut self,
+        gfx: &mut impl HasMut<GraphicsContext>,
+        source: &Image,
+    ) -> GameResult {
+        let gfx = gfx.retrieve_mut();
+        let result = self.run(gfx, source, None)?;
+        gfx.present(&result)
+    }
+
+    fn run(
+        &mut self,
+        gfx: &mut GraphicsContext,
+        source: &Image,
+        output: Option<&Image>,
+    ) -> GameResult<Image> {
+        let passes: Vec<_> = self.passes.iter().filter(|pass| pass.enabled).collect();
+        if passes.is_empty() {
+            // an image can't be drawn into itself, and there's nothing to copy
+            if let Some(output) = output.filter(|output| output.view != source.view) {
+                let pass = Pass {
+                    shader: default_shader(),
+                    params: None,
+                    sampler: Sampler::default(),
+                    enabled: true,
+                };
+                Self::draw_pass(gfx, &pass, source, output.clone())?;
+                return Ok(output.clone());
+            }
+            return Ok(source.clone());
+        }
+
+        let mut current = source.clone();
+        for (i, pass) in passes.iter().enumerate() {
+            let target = match output {
+                Some(output) if i == passes.len() - 1 => output.clone(),
+                _ => {
+                    // ping-pong between the two buffers, never drawing into the image being read
+                    let first = self.buffers[0].image(gfx);
+                    if first.view == current.view {
+                        self.buffers[1].image(gfx)
+                    } else {
+                        first
+                    }
+                }
+            };
+            Self::draw_pass(gfx, pass, &current, target.clone())?;
+            current = target;
+        }
+        Ok(current)
+    }
+
+    fn draw_pass(
+        gfx: &mut GraphicsContext,
+        pass: &Pass,
+        source: &Image,
+        target: Image,
+    ) -> GameResult {
+        let scale = [
+            target.width() as f32 / source.width() as f32,
+            target.height() as f32 / source.height() as f32,
+        ];
+        let mut canvas = Canvas::from_image(gfx, target, Color::new(0., 0., 0., 0.));
+        canvas.set_shader(&pass.shader);
+        canvas.set_raw_shader_params(pass.params.clone());
+        canvas.set_sampler(pass.sampler);
+        canvas.set_blend_mode(BlendMode::REPLACE);
+        canvas.draw(source, DrawParam::new().scale(scale));
+        canvas.finish(gfx)
+    }
+}
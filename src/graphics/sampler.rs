@@ -1,8 +1,15 @@
 use super::gpu::arc::ArcSampler;
-use std::collections::HashMap;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
 
 /// Sampler state that is used when sampling images on the GPU.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///
+/// The mipmap fields only matter for images with mip levels, e.g. those loaded with
+/// [`ImageLoadOptions::mipmaps`](super::ImageLoadOptions::mipmaps).
+#[derive(Debug, Clone, Copy)]
 pub struct Sampler {
     /// Clamping mode in the U (x) direction.
     pub clamp_u: ClampMode,
@@ -14,6 +21,15 @@ pub struct Sampler {
     pub mag: FilterMode,
     /// Minification (downscaling) filter.
     pub min: FilterMode,
+    /// Filter used between mip levels.
+    pub mipmap_filter: FilterMode,
+    /// Lowest mip level that may be sampled; 0 is the full size image.
+    pub lod_min_clamp: f32,
+    /// Highest mip level that may be sampled.
+    pub lod_max_clamp: f32,
+    /// Maximum anisotropy, from 1 (off) to 16. Values above 1 only take effect when
+    /// all of `mag`, `min` and `mipmap_filter` are [`FilterMode::Linear`].
+    pub anisotropy: u16,
 }
 
 impl Sampler {
@@ -25,6 +41,10 @@ impl Sampler {
             clamp_w: ClampMode::Clamp,
             mag: FilterMode::Linear,
             min: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 32.0,
+            anisotropy: 1,
         }
     }
 
@@ -35,9 +55,77 @@ impl Sampler {
         Sampler {
             mag: FilterMode::Nearest,
             min: FilterMode::Nearest,
+            mipmap_filter: FilterMode::Nearest,
             ..Self::linear_clamp()
         }
     }
+
+    /// Returns this sampler with anisotropic filtering of up to `anisotropy` samples,
+    /// switching all filters to [`FilterMode::Linear`] as required.
+    ///
+    /// Improves the look of images viewed at steep angles, or squashed along one axis.
+    pub fn anisotropic(self, anisotropy: u16) -> Self {
+        Sampler {
+            mag: FilterMode::Linear,
+            min: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            anisotropy,
+            ..self
+        }
+    }
+
+    // the lod clamps are compared by their bits, so that samplers can be used as cache keys
+    fn key(
+        &self,
+    ) -> (
+        ClampMode,
+        ClampMode,
+        ClampMode,
+        FilterMode,
+        FilterMode,
+        FilterMode,
+        u32,
+        u32,
+        u16,
+    ) {
+        (
+            self.clamp_u,
+            self.clamp_v,
+            self.clamp_w,
+            self.mag,
+            self.min,
+            self.mipmap_filter,
+            self.lod_min_clamp.to_bits(),
+            self.lod_max_clamp.to_bits(),
+            self.anisotropy,
+        )
+    }
+}
+
+impl PartialEq for Sampler {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Sampler {}
+
+impl PartialOrd for Sampler {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Sampler {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for Sampler {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
 }
 
 impl Default for Sampler {
@@ -55,11 +143,18 @@ impl<'a> From<Sampler> for wgpu::SamplerDescriptor<'a> {
             address_mode_w: sampler.clamp_w.into(),
             mag_filter: sampler.mag.into(),
             min_filter: sampler.min.into(),
-            mipmap_filter: wgpu::FilterMode::Linear,
-            lod_min_clamp: 0.0,
-            lod_max_clamp: 1.0,
+            mipmap_filter: sampler.mipmap_filter.into(),
+            lod_min_clamp: sampler.lod_min_clamp,
+            lod_max_clamp: sampler.lod_max_clamp,
             compare: None,
-            anisotropy_clamp: 1,
+            // wgpu rejects anisotropy unless every filter is linear
+            anisotropy_clamp: if (sampler.mag, sampler.min, sampler.mipmap_filter)
+                == (FilterMode::Linear, FilterMode::Linear, FilterMode::Linear)
+            {
+                sampler.anisotropy.clamp(1, 16)
+            } else {
+                1
+            },
             border_color: None,
         }
     }
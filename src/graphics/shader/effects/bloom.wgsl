@@ -0,0 +1,33 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct Bloom {
+    threshold: f32,
+    knee: f32,
+    intensity: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> bloom: Bloom;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t, s, in.uv) * in.color;
+    let brightness = max(color.r, max(color.g, color.b));
+
+    // quadratic soft knee around the threshold instead of a hard cut
+    let soft = clamp(brightness - bloom.threshold + bloom.knee, 0.0, 2.0 * bloom.knee);
+    let curve = soft * soft / (4.0 * bloom.knee + 0.00001);
+    let contribution = max(curve, brightness - bloom.threshold) / max(brightness, 0.00001);
+
+    return vec4<f32>(color.rgb * contribution * bloom.intensity, color.a);
+}
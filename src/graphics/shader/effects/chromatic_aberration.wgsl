@@ -0,0 +1,30 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct ChromaticAberration {
+    center: vec2<f32>,
+    amount: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> aberration: ChromaticAberration;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let dimensions = vec2<f32>(textureDimensions(t));
+    // channels drift apart away from the center, by `amount` pixels at the edges
+    let offset = (in.uv - aberration.center) * 2.0 * aberration.amount / dimensions;
+    let middle = textureSample(t, s, in.uv);
+    let red = textureSample(t, s, in.uv + offset).r;
+    let blue = textureSample(t, s, in.uv - offset).b;
+    return vec4<f32>(red, middle.g, blue, middle.a) * in.color;
+}
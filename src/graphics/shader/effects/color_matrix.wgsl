@@ -0,0 +1,25 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct ColorMatrix {
+    matrix: mat4x4<f32>,
+    offset: vec4<f32>,
+}
+
+@group(3) @binding(0)
+var<uniform> grading: ColorMatrix;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t, s, in.uv) * in.color;
+    return clamp(grading.matrix * color + grading.offset, vec4<f32>(0.0), vec4<f32>(1.0));
+}
@@ -0,0 +1,37 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct Crt {
+    curvature: f32,
+    scanline_intensity: f32,
+    scanline_count: f32,
+    vignette: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> crt: Crt;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // barrel distortion around the center
+    let centered = in.uv * 2.0 - 1.0;
+    let bent = centered + centered * centered.yx * centered.yx * crt.curvature;
+    let uv = bent * 0.5 + 0.5;
+    let color = textureSampleLevel(t, s, uv, 0.0) * in.color;
+
+    let scanline = sin(uv.y * crt.scanline_count * 3.14159265) * 0.5 + 0.5;
+    let edges = pow(clamp(16.0 * uv.x * uv.y * (1.0 - uv.x) * (1.0 - uv.y), 0.0, 1.0), 0.25);
+    let rgb = color.rgb * mix(1.0, scanline, crt.scanline_intensity) * mix(1.0, edges, crt.vignette);
+
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+    return select(vec4<f32>(0.0, 0.0, 0.0, color.a), vec4<f32>(rgb, color.a), inside);
+}
@@ -0,0 +1,38 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct Dissolve {
+    edge_color: vec4<f32>,
+    threshold: f32,
+    edge_width: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> dissolve: Dissolve;
+
+@group(3) @binding(1)
+var noise: texture_2d<f32>;
+
+@group(3) @binding(2)
+var noise_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t, s, in.uv) * in.color;
+    let n = textureSample(noise, noise_sampler, in.uv).r;
+
+    let visible = select(0.0, 1.0, n >= dissolve.threshold);
+    let burning = 1.0 - clamp((n - dissolve.threshold) / max(dissolve.edge_width, 0.00001), 0.0, 1.0);
+    let edge = burning * select(0.0, 1.0, dissolve.threshold > 0.0) * dissolve.edge_color.a;
+
+    return vec4<f32>(mix(color.rgb, dissolve.edge_color.rgb, edge), color.a * visible);
+}
@@ -0,0 +1,48 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct DropShadow {
+    color: vec4<f32>,
+    offset: vec2<f32>,
+    softness: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> shadow: DropShadow;
+
+// draws `top` over `bottom`, both with straight alpha
+fn over(top: vec4<f32>, bottom: vec4<f32>) -> vec4<f32> {
+    let a = top.a + bottom.a * (1.0 - top.a);
+    let rgb = (top.rgb * top.a + bottom.rgb * bottom.a * (1.0 - top.a)) / max(a, 0.00001);
+    return vec4<f32>(rgb, a);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(t));
+    let base = textureSample(t, s, in.uv) * in.color;
+    let origin = in.uv - shadow.offset * texel;
+    let radius = min(i32(ceil(shadow.softness)), 8);
+
+    var alpha = 0.0;
+    var count = 0.0;
+    for (var y = -radius; y <= radius; y += 1) {
+        for (var x = -radius; x <= radius; x += 1) {
+            let uv = origin + vec2<f32>(f32(x), f32(y)) * texel;
+            alpha += textureSampleLevel(t, s, uv, 0.0).a;
+            count += 1.0;
+        }
+    }
+
+    let below = vec4<f32>(shadow.color.rgb, shadow.color.a * in.color.a * alpha / count);
+    return over(base, below);
+}
@@ -0,0 +1,36 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct GaussianBlur {
+    direction: vec2<f32>,
+    sigma: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> blur: GaussianBlur;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = blur.direction / vec2<f32>(textureDimensions(t));
+    let sigma = max(blur.sigma, 0.0001);
+    let radius = min(i32(ceil(sigma * 3.0)), 64);
+
+    var sum = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var i = -radius; i <= radius; i += 1) {
+        let x = f32(i);
+        let weight = exp(-x * x / (2.0 * sigma * sigma));
+        sum += textureSampleLevel(t, s, in.uv + texel * x, 0.0) * weight;
+        total += weight;
+    }
+    return sum / total * in.color;
+}
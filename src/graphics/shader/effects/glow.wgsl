@@ -0,0 +1,51 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct Glow {
+    color: vec4<f32>,
+    radius: f32,
+    intensity: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> glow: Glow;
+
+// draws `top` over `bottom`, both with straight alpha
+fn over(top: vec4<f32>, bottom: vec4<f32>) -> vec4<f32> {
+    let a = top.a + bottom.a * (1.0 - top.a);
+    let rgb = (top.rgb * top.a + bottom.rgb * bottom.a * (1.0 - top.a)) / max(a, 0.00001);
+    return vec4<f32>(rgb, a);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(t));
+    let base = textureSample(t, s, in.uv) * in.color;
+
+    // three rings of samples, the inner ones weighing more
+    var alpha = 0.0;
+    var total = 0.0;
+    for (var ring = 1; ring <= 3; ring += 1) {
+        let distance = glow.radius * f32(ring) / 3.0;
+        let weight = 4.0 - f32(ring);
+        for (var i = 0; i < 12; i += 1) {
+            let angle = (f32(i) + f32(ring) * 0.5) * 0.52359878;
+            let offset = vec2<f32>(cos(angle), sin(angle)) * distance * texel;
+            alpha += textureSampleLevel(t, s, in.uv + offset, 0.0).a * weight;
+            total += weight;
+        }
+    }
+
+    let strength = clamp(alpha / total * glow.intensity, 0.0, 1.0);
+    let below = vec4<f32>(glow.color.rgb, glow.color.a * in.color.a * strength);
+    return over(base, below);
+}
@@ -0,0 +1,47 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct LutGrading {
+    size: f32,
+    amount: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> grading: LutGrading;
+
+@group(3) @binding(1)
+var lut: texture_2d<f32>;
+
+@group(3) @binding(2)
+var lut_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t, s, in.uv) * in.color;
+    let rgb = clamp(color.rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+    let n = grading.size;
+
+    // the LUT is a strip of n tiles of n by n texels, blue selecting the tile
+    let blue = rgb.b * (n - 1.0);
+    let tile0 = floor(blue);
+    let tile1 = min(tile0 + 1.0, n - 1.0);
+    let xy = rgb.rg * (n - 1.0) + 0.5;
+    let uv0 = vec2<f32>((tile0 * n + xy.x) / (n * n), xy.y / n);
+    let uv1 = vec2<f32>((tile1 * n + xy.x) / (n * n), xy.y / n);
+    let graded = mix(
+        textureSampleLevel(lut, lut_sampler, uv0, 0.0).rgb,
+        textureSampleLevel(lut, lut_sampler, uv1, 0.0).rgb,
+        blue - tile0,
+    );
+
+    return vec4<f32>(mix(color.rgb, graded, grading.amount), color.a);
+}
@@ -0,0 +1,42 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct Outline {
+    color: vec4<f32>,
+    thickness: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> outline: Outline;
+
+// draws `top` over `bottom`, both with straight alpha
+fn over(top: vec4<f32>, bottom: vec4<f32>) -> vec4<f32> {
+    let a = top.a + bottom.a * (1.0 - top.a);
+    let rgb = (top.rgb * top.a + bottom.rgb * bottom.a * (1.0 - top.a)) / max(a, 0.00001);
+    return vec4<f32>(rgb, a);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(t));
+    let base = textureSample(t, s, in.uv) * in.color;
+
+    var alpha = 0.0;
+    for (var i = 0; i < 16; i += 1) {
+        let angle = f32(i) * 0.39269908;
+        let offset = vec2<f32>(cos(angle), sin(angle)) * outline.thickness * texel;
+        alpha = max(alpha, textureSampleLevel(t, s, in.uv + offset, 0.0).a);
+    }
+
+    let below = vec4<f32>(outline.color.rgb, outline.color.a * in.color.a * alpha);
+    return over(base, below);
+}
@@ -0,0 +1,26 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct Pixelate {
+    size: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> pixelate: Pixelate;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let dimensions = vec2<f32>(textureDimensions(t));
+    let size = max(pixelate.size, 1.0);
+    let uv = (floor(in.uv * dimensions / size) + 0.5) * size / dimensions;
+    return textureSampleLevel(t, s, uv, 0.0) * in.color;
+}
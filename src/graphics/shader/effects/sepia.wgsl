@@ -0,0 +1,29 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct Sepia {
+    amount: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> sepia: Sepia;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t, s, in.uv) * in.color;
+    let toned = vec3<f32>(
+        dot(color.rgb, vec3<f32>(0.393, 0.769, 0.189)),
+        dot(color.rgb, vec3<f32>(0.349, 0.686, 0.168)),
+        dot(color.rgb, vec3<f32>(0.272, 0.534, 0.131)),
+    );
+    return vec4<f32>(mix(color.rgb, min(toned, vec3<f32>(1.0)), sepia.amount), color.a);
+}
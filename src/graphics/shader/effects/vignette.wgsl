@@ -0,0 +1,29 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+struct Vignette {
+    color: vec4<f32>,
+    radius: f32,
+    softness: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> vignette: Vignette;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t, s, in.uv) * in.color;
+    // 0 in the center, 1 in the corners
+    let distance = length(in.uv - 0.5) * 1.41421356;
+    let amount = smoothstep(vignette.radius, vignette.radius + max(vignette.softness, 0.0001), distance);
+    return vec4<f32>(mix(color.rgb, vignette.color.rgb, amount * vignette.color.a), color.a);
+}
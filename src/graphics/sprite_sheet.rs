@@ -0,0 +1,866 @@
+use super::{context::GraphicsContext, image::Image, Canvas, DrawParam, Drawable, Rect, Transform};
+use crate::{context::Has, GameError, GameResult};
+use serde::{de, Deserialize, Deserializer};
+use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
+
+/// How long frames are shown when the sheet doesn't say.
+const DEFAULT_FRAME_DURATION: Duration = Duration::from_millis(100);
+
+/// A single frame of a [`SpriteSheet`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteFrame {
+    /// Position and size of the frame on the sheet, in pixels.
+    pub rect: Rect,
+    /// Position of `rect` inside the untrimmed frame.
+    ///
+    /// Tools that trim transparent borders off frames record where the trimmed part sat,
+    /// so that drawing the frame at the same spot doesn't make the sprite jitter.
+    pub offset: mint::Point2<f32>,
+    /// Size of the untrimmed frame.
+    pub source_size: mint::Vector2<f32>,
+    /// How long the frame is shown by an [`Animation`].
+    pub duration: Duration,
+}
+
+impl SpriteFrame {
+    /// Creates an untrimmed frame shown for 100 milliseconds.
+    pub fn new(rect: Rect) -> Self {
+        SpriteFrame {
+            rect,
+            offset: mint::Point2 { x: 0., y: 0. },
+            source_size: mint::Vector2 {
+                x: rect.w,
+                y: rect.h,
+            },
+            duration: DEFAULT_FRAME_DURATION,
+        }
+    }
+}
+
+/// How an [`Animation`] continues after its last frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PlayMode {
+    /// Start over from the first frame.
+    #[default]
+    Loop,
+    /// Stop on the last frame.
+    Once,
+    /// Play backwards to the first frame, then forwards again, and so on.
+    PingPong,
+}
+
+/// A named range of frames in a [`SpriteSheet`], such as an Aseprite tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameTag {
+    /// Index of the first frame.
+    pub from: usize,
+    /// Index of the last frame, inclusive.
+    pub to: usize,
+    /// How the range should be played.
+    pub mode: PlayMode,
+    /// Whether the range should be played from `to` to `from`.
+    pub reverse: bool,
+}
+
+#[derive(Debug, Default)]
+struct SheetData {
+    frames: Vec<SpriteFrame>,
+    names: HashMap<String, usize>,
+    tags: HashMap<String, FrameTag>,
+}
+
+/// An image divided into frames, either by a grid or by a list of rectangles.
+///
+/// Sheets can also be read from the JSON files written by Aseprite and TexturePacker,
+/// in both their hash and array layouts. Cloning a sheet is cheap.
+///
+/// ```rust,no_run
+/// # use ggez::graphics::{self, Animation, Image, PlayMode, SpriteSheet};
+/// # use std::time::Duration;
+/// # fn t(ctx: &mut ggez::Context) -> ggez::GameResult {
+/// let image = Image::from_path(ctx, "/player_sheet.png")?;
+/// let sheet = SpriteSheet::from_grid(image, 14, 19);
+///
+/// // the run cycle is on the fourth row
+/// let mut run = Animation::new(sheet, 3 * 14..3 * 14 + 8)
+///     .frame_duration(Duration::from_millis(80))
+///     .mode(PlayMode::Loop)
+///     .event(2, "step");
+///
+/// run.update(ctx.time.delta());
+/// for event in run.events() {
+///     println!("{event}");
+/// }
+///
+/// let mut canvas = graphics::Canvas::from_frame(ctx, None);
+/// canvas.draw(&run, graphics::DrawParam::default().dest([100., 100.]));
+/// canvas.finish(ctx)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+    image: Image,
+    data: Arc<SheetData>,
+}
+
+impl SpriteSheet {
+    /// Divides the image into `columns` by `rows` frames of equal size, numbered row by row.
+    pub fn from_grid(image: Image, columns: u32, rows: u32) -> Self {
+        let w = image.width() as f32 / columns.max(1) as f32;
+        let h = image.height() as f32 / rows.max(1) as f32;
+        let rects = (0..rows)
+            .flat_map(|y| (0..columns).map(move |x| Rect::new(x as f32 * w, y as f32 * h, w, h)));
+        Self::from_rects(image, rects)
+    }
+
+    /// Creates a sheet with one frame per rectangle, given in pixels.
+    pub fn from_rects(image: Image, rects: impl IntoIterator<Item = Rect>) -> Self {
+        Self::from_frames(image, rects.into_iter().map(SpriteFrame::new))
+    }
+
+    /// Creates a sheet from fully described frames.
+    pub fn from_frames(image: Image, frames: impl IntoIterator<Item = SpriteFrame>) -> Self {
+        SpriteSheet {
+            image,
+            data: Arc::new(SheetData {
+                frames: frames.into_iter().collect(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Reads frames, frame durations and tags from a JSON file exported by Aseprite.
+    ///
+    /// Frames are named after their `filename` and tags map to [`FrameTag`]s, so an animation
+    /// can be created with [`Animation::from_tag`]. The sheet's image isn't loaded from the
+    /// path in the file; it has to be passed in.
+    pub fn from_aseprite_json(image: Image, json: &[u8]) -> GameResult<Self> {
+        let file: JsonSheet = serde_json::from_slice(json)?;
+        let mut data = Self::parse_frames(file.frames)?;
+        for tag in file.meta.frame_tags {
+            if tag.from > tag.to || tag.to >= data.frames.len() {
+                return Err(GameError::ResourceLoadError(format!(
+                    "frame tag `{}` is out of range",
+                    tag.name
+                )));
+            }
+            let (mode, reverse) = match tag.direction.as_str() {
+                "forward" => (PlayMode::Loop, false),
+                "reverse" => (PlayMode::Loop, true),
+                "pingpong" => (PlayMode::PingPong, false),
+                "pingpong_reverse" => (PlayMode::PingPong, true),
+                other => {
+                    return Err(GameError::ResourceLoadError(format!(
+                        "unknown frame tag direction `{other}`"
+                    )))
+                }
+            };
+            let _ = data.tags.insert(
+                tag.name,
+                FrameTag {
+                    from: tag.from,
+                    to: tag.to,
+                    mode,
+                    reverse,
+                },
+            );
+        }
+        Ok(SpriteSheet {
+            image,
+            data: Arc::new(data),
+        })
+    }
+
+    /// Reads frames from a JSON file exported by TexturePacker, in the "JSON (Hash)"
+    /// or "JSON (Array)" format.
+    ///
+    /// Frames are named after their `filename`. Rotated frames aren't supported.
+    pub fn from_texture_packer_json(image: Image, json: &[u8]) -> GameResult<Self> {
+        let file: JsonSheet = serde_json::from_slice(json)?;
+        Ok(SpriteSheet {
+            image,
+            data: Arc::new(Self::parse_frames(file.frames)?),
+        })
+    }
+
+    fn parse_frames(frames: JsonFrames) -> GameResult<SheetData> {
+        let mut data = SheetData::default();
+        for (name, frame) in frames.0 {
+            if frame.rotated {
+                return Err(GameError::ResourceLoadError(format!(
+                    "frame `{name}` is rotated, which isn't supported"
+                )));
+            }
+            let rect = frame.frame.rect();
+            // untrimmed frames have no source rectangle and sit at the frame's origin
+            let source = frame
+                .sprite_source_size
+                .map_or(Rect::new(0., 0., rect.w, rect.h), JsonRect::rect);
+            let _ = data.names.insert(name, data.frames.len());
+            data.frames.push(SpriteFrame {
+                rect,
+                offset: mint::Point2 {
+                    x: source.x,
+                    y: source.y,
+                },
+                source_size: frame.source_size.map_or(
+                    mint::Vector2 {
+                        x: rect.w,
+                        y: rect.h,
+                    },
+                    |size| mint::Vector2 {
+                        x: size.w,
+                        y: size.h,
+                    },
+                ),
+                duration: frame
+                    .duration
+                    .map_or(DEFAULT_FRAME_DURATION, Duration::from_millis),
+            });
+        }
+        Ok(data)
+    }
+
+    /// Returns the image the frames are taken from.
+    #[inline]
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// Returns all frames of the sheet.
+    #[inline]
+    pub fn frames(&self) -> &[SpriteFrame] {
+        &self.data.frames
+    }
+
+    /// Returns the frame at `index`, if there is one.
+    #[inline]
+    pub fn frame(&self, index: usize) -> Option<&SpriteFrame> {
+        self.data.frames.get(index)
+    }
+
+    /// Returns the index of the frame with the given name, for sheets read from JSON.
+    pub fn frame_index(&self, name: &str) -> Option<usize> {
+        self.data.names.get(name).copied()
+    }
+
+    /// Returns the tag with the given name, for sheets read from Aseprite JSON.
+    pub fn tag(&self, name: &str) -> Option<FrameTag> {
+        self.data.tags.get(name).copied()
+    }
+
+    /// Returns the source rectangle of the frame at `index` in UV coordinates,
+    /// for [`DrawParam::src`].
+    pub fn uv(&self, index: usize) -> Option<Rect> {
+        let frame = self.frame(index)?;
+        let (w, h) = (self.image.width() as f32, self.image.height() as f32);
+        Some(Rect::new(
+            frame.rect.x / w,
+            frame.rect.y / h,
+            frame.rect.w / w,
+            frame.rect.h / h,
+        ))
+    }
+
+    /// Draws the frame at `index`, positioned as if it had its untrimmed size.
+    ///
+    /// `param.src` selects a part of the frame rather than of the whole image.
+    /// Does nothing if there's no such frame.
+    pub fn draw_frame(&self, canvas: &mut Canvas, index: usize, param: impl Into<DrawParam>) {
+        let (Some(frame), Some(uv)) = (self.frame(index), self.uv(index)) else {
+            return;
+        };
+        if frame.rect.w <= 0. || frame.rect.h <= 0. {
+            return;
+        }
+
+        let mut param = param.into();
+        param.src = Rect::new(
+            uv.x + param.src.x * uv.w,
+            uv.y + param.src.y * uv.h,
+            uv.w * param.src.w,
+            uv.h * param.src.h,
+        );
+        // the image is drawn at the size of the trimmed frame, so both kinds of transform
+        // need to be shifted by where the trimmed frame sits inside the untrimmed one
+        param.transform = match param.transform {
+            Transform::Values {
+                dest,
+                rotation,
+                scale,
+                offset,
+            } => Transform::Values {
+                dest,
+                rotation,
+                scale,
+                offset: mint::Point2 {
+                    x: (offset.x * frame.source_size.x - frame.offset.x) / frame.rect.w,
+                    y: (offset.y * frame.source_size.y - frame.offset.y) / frame.rect.h,
+                },
+            },
+            Transform::Matrix(m) => Transform::Matrix(
+                (glam::Mat4::from(m)
+                    * glam::Mat4::from_translation(glam::vec3(frame.offset.x, frame.offset.y, 0.)))
+                .into(),
+            ),
+        };
+
+        self.image.draw(canvas, param);
+    }
+}
+
+/// Plays a sequence of [`SpriteSheet`] frames.
+///
+/// Each frame is shown for its own duration, which can be overridden. Advance the animation
+/// with [`Animation::update`] and draw it like any other [`Drawable`]. Events can be attached
+/// to positions in the sequence and are reported by [`Animation::events`] once that position
+/// is reached, e.g. to play a footstep sound on the right frame.
+#[derive(Debug, Clone)]
+pub struct Animation {
+    sheet: SpriteSheet,
+    playback: Playback,
+}
+
+impl Animation {
+    /// Creates a looping animation of the given sheet frames, in order.
+    ///
+    /// Indices without a frame in the sheet are skipped.
+    pub fn new(sheet: SpriteSheet, frames: impl IntoIterator<Item = usize>) -> Self {
+        let frames = frames
+            .into_iter()
+            .filter_map(|index| Some((index, sheet.frame(index)?.duration)))
+            .collect();
+        Animation {
+            sheet,
+            playback: Playback::new(frames),
+        }
+    }
+
+    /// Creates an animation of the frames of a tag, played the way the tag says.
+    ///
+    /// Returns `None` if the sheet has no such tag.
+    pub fn from_tag(sheet: SpriteSheet, tag: &str) -> Option<Self> {
+        let tag = sheet.tag(tag)?;
+        let frames: Vec<_> = if tag.reverse {
+            (tag.from..=tag.to).rev().collect()
+        } else {
+            (tag.from..=tag.to).collect()
+        };
+        Some(Self::new(sheet, frames).mode(tag.mode))
+    }
+
+    /// Sets how the animation continues after its last frame.
+    #[must_use]
+    pub fn mode(mut self, mode: PlayMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    /// Sets the playback speed, see [`Animation::set_speed`].
+    #[must_use]
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.set_speed(speed);
+        self
+    }
+
+    /// Shows every frame for the same duration, replacing the durations from the sheet.
+    #[must_use]
+    pub fn frame_duration(mut self, duration: Duration) -> Self {
+        for (_, d) in &mut self.playback.frames {
+            *d = duration;
+        }
+        self
+    }
+
+    /// Attaches an event to a position in the sequence (not a sheet frame index).
+    ///
+    /// A position can have any number of events.
+    #[must_use]
+    pub fn event(mut self, position: usize, name: impl Into<String>) -> Self {
+        self.playback.events.push((position, name.into()));
+        self
+    }
+
+    /// Sets how the animation continues after its last frame.
+    pub fn set_mode(&mut self, mode: PlayMode) {
+        self.playback.set_mode(mode);
+    }
+
+    /// Sets the playback speed. `1.0` is normal speed, `2.0` twice as fast;
+    /// negative values are treated as `0.0`.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.playback.set_speed(speed);
+    }
+
+    /// Returns the playback speed.
+    #[inline]
+    pub fn get_speed(&self) -> f32 {
+        self.playback.speed
+    }
+
+    /// Sets how long the frame at `position` in the sequence is shown.
+    pub fn set_frame_duration(&mut self, position: usize, duration: Duration) {
+        if let Some((_, d)) = self.playback.frames.get_mut(position) {
+            *d = duration;
+        }
+    }
+
+    /// Returns the sheet the frames are taken from.
+    #[inline]
+    pub fn sheet(&self) -> &SpriteSheet {
+        &self.sheet
+    }
+
+    /// Returns the number of frames in the sequence.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.playback.frames.len()
+    }
+
+    /// Returns `true` if the sequence has no frames.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.playback.frames.is_empty()
+    }
+
+    /// Returns the current position in the sequence.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.playback.position
+    }
+
+    /// Jumps to a position in the sequence, firing its events on the next update.
+    pub fn set_position(&mut self, position: usize) {
+        self.playback.set_position(position);
+    }
+
+    /// Returns the sheet index of the frame being shown, or `None` if the sequence is empty.
+    pub fn current_frame(&self) -> Option<usize> {
+        self.playback.current_frame()
+    }
+
+    /// Resumes playback.
+    pub fn play(&mut self) {
+        self.playback.playing = true;
+    }
+
+    /// Pauses playback on the current frame.
+    pub fn pause(&mut self) {
+        self.playback.playing = false;
+    }
+
+    /// Returns `true` unless the animation is paused or a [`PlayMode::Once`] animation has ended.
+    #[inline]
+    pub fn is_playing(&self) -> bool {
+        self.playback.playing
+    }
+
+    /// Returns `true` if a [`PlayMode::Once`] animation reached the end of its last frame.
+    pub fn is_finished(&self) -> bool {
+        self.playback.finished
+    }
+
+    /// Starts playing again from the first frame.
+    pub fn restart(&mut self) {
+        self.playback.set_position(0);
+        self.playback.backwards = false;
+        self.playback.playing = true;
+    }
+
+    /// Returns the events of the positions reached during the last [`Animation::update`].
+    pub fn events(&self) -> impl Iterator<Item = &str> {
+        self.playback.events()
+    }
+
+    /// Advances the animation by `dt`, scaled by the playback speed.
+    ///
+    /// Several frames can be passed in one update if `dt` is long enough;
+    /// the events of all of them are reported.
+    pub fn update(&mut self, dt: Duration) {
+        self.playback.update(dt);
+    }
+}
+
+// the timing of an `Animation`, apart from the sheet it draws
+#[derive(Debug, Clone)]
+struct Playback {
+    frames: Vec<(usize, Duration)>,
+    mode: PlayMode,
+    speed: f32,
+    events: Vec<(usize, String)>,
+    fired: Vec<usize>,
+    position: usize,
+    elapsed: Duration,
+    backwards: bool,
+    playing: bool,
+    finished: bool,
+    entered: bool,
+}
+
+impl Playback {
+    fn new(frames: Vec<(usize, Duration)>) -> Self {
+        Playback {
+            frames,
+            mode: PlayMode::Loop,
+            speed: 1.,
+            events: Vec::new(),
+            fired: Vec::new(),
+            position: 0,
+            elapsed: Duration::ZERO,
+            backwards: false,
+            playing: true,
+            finished: false,
+            entered: false,
+        }
+    }
+
+    fn set_mode(&mut self, mode: PlayMode) {
+        self.mode = mode;
+        if mode != PlayMode::PingPong {
+            self.backwards = false;
+        }
+    }
+
+    fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.);
+    }
+
+    fn set_position(&mut self, position: usize) {
+        self.position = position.min(self.frames.len().saturating_sub(1));
+        self.elapsed = Duration::ZERO;
+        self.finished = false;
+        self.entered = false;
+    }
+
+    fn current_frame(&self) -> Option<usize> {
+        self.frames.get(self.position).map(|&(index, _)| index)
+    }
+
+    fn events(&self) -> impl Iterator<Item = &str> {
+        self.fired.iter().map(|&i| self.events[i].1.as_str())
+    }
+
+    fn update(&mut self, dt: Duration) {
+        self.fired.clear();
+        if !self.playing || self.frames.is_empty() {
+            return;
+        }
+        if !self.entered {
+            self.enter();
+        }
+
+        // frames that take no time at all would never let a looping animation catch up
+        if self.frames.iter().all(|&(_, d)| d.is_zero()) {
+            return;
+        }
+
+        self.elapsed += dt.mul_f32(self.speed);
+        while self.elapsed >= self.frames[self.position].1 {
+            let next = match self.next_position() {
+                Some(next) => next,
+                None => {
+                    self.elapsed = self.frames[self.position].1;
+                    self.playing = false;
+                    self.finished = true;
+                    return;
+                }
+            };
+            self.elapsed -= self.frames[self.position].1;
+            self.position = next;
+            self.enter();
+        }
+    }
+
+    fn next_position(&mut self) -> Option<usize> {
+        let last = self.frames.len() - 1;
+        match self.mode {
+            PlayMode::Loop => Some(if self.position >= last {
+                0
+            } else {
+                self.position + 1
+            }),
+            PlayMode::Once => (self.position < last).then_some(self.position + 1),
+            PlayMode::PingPong => {
+                if last == 0 {
+                    return Some(0);
+                }
+                if (self.backwards && self.position == 0)
+                    || (!self.backwards && self.position >= last)
+                {
+                    self.backwards = !self.backwards;
+                }
+                Some(if self.backwards {
+                    self.position - 1
+                } else {
+                    self.position + 1
+                })
+            }
+        }
+    }
+
+    fn enter(&mut self) {
+        self.entered = true;
+        let position = self.position;
+        self.fired.extend(
+            self.events
+                .iter()
+                .enumerate()
+                .filter(|(_, (p, _))| *p == position)
+                .map(|(i, _)| i),
+        );
+    }
+}
+
+impl Drawable for Animation {
+    fn draw(&self, canvas: &mut Canvas, param: impl Into<DrawParam>) {
+        if let Some(index) = self.current_frame() {
+            self.sheet.draw_frame(canvas, index, param);
+        }
+    }
+
+    fn dimensions(&self, _gfx: &impl Has<GraphicsContext>) -> Option<Rect> {
+        let frame = self.sheet.frame(self.current_frame()?)?;
+        Some(Rect::new(0., 0., frame.source_size.x, frame.source_size.y))
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonSheet {
+    frames: JsonFrames,
+    #[serde(default)]
+    meta: JsonMeta,
+}
+
+#[derive(Deserialize, Default)]
+struct JsonMeta {
+    #[serde(default, rename = "frameTags")]
+    frame_tags: Vec<JsonTag>,
+}
+
+#[derive(Deserialize)]
+struct JsonTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default = "forward")]
+    direction: String,
+}
+
+fn forward() -> String {
+    String::from("forward")
+}
+
+#[derive(Deserialize)]
+struct JsonFrame {
+    #[serde(default)]
+    filename: Option<String>,
+    frame: JsonRect,
+    #[serde(default)]
+    rotated: bool,
+    #[serde(default, rename = "spriteSourceSize")]
+    sprite_source_size: Option<JsonRect>,
+    #[serde(default, rename = "sourceSize")]
+    source_size: Option<JsonSize>,
+    #[serde(default)]
+    duration: Option<u64>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+struct JsonRect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+impl JsonRect {
+    fn rect(self) -> Rect {
+        Rect::new(self.x, self.y, self.w, self.h)
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonSize {
+    w: f32,
+    h: f32,
+}
+
+/// Named frames in file order, from either a JSON object keyed by name or an array.
+///
+/// `serde_json` sorts object keys, which would put `frame 10` before `frame 2`,
+/// so the object is walked by hand.
+struct JsonFrames(Vec<(String, JsonFrame)>);
+
+impl<'de> Deserialize<'de> for JsonFrames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FramesVisitor;
+
+        impl<'de> de::Visitor<'de> for FramesVisitor {
+            type Value = JsonFrames;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map or list of frames")
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    frames.push(entry);
+                }
+                Ok(JsonFrames(frames))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::new();
+                while let Some(frame) = seq.next_element::<JsonFrame>()? {
+                    let name = frame
+                        .filename
+                        .clone()
+                        .unwrap_or_else(|| frames.len().to_string());
+                    frames.push((name, frame));
+                }
+                Ok(JsonFrames(frames))
+            }
+        }
+
+        deserializer.deserialize_any(FramesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_frames_keep_file_order() {
+        let json = r#"{
+            "frames": {
+                "run 2.aseprite": { "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "duration": 50 },
+                "run 10.aseprite": { "frame": { "x": 8, "y": 0, "w": 8, "h": 8 }, "duration": 70 }
+            },
+            "meta": { "frameTags": [{ "name": "run", "from": 0, "to": 1, "direction": "pingpong" }] }
+        }"#;
+        let sheet: JsonSheet = serde_json::from_str(json).unwrap();
+        let names: Vec<_> = sheet.frames.0.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["run 2.aseprite", "run 10.aseprite"]);
+        assert_eq!(sheet.frames.0[1].1.duration, Some(70));
+        assert_eq!(sheet.meta.frame_tags[0].direction, "pingpong");
+
+        let array = r#"{ "frames": [
+            { "filename": "b", "frame": { "x": 0, "y": 0, "w": 4, "h": 4 }, "rotated": false,
+              "trimmed": true, "spriteSourceSize": { "x": 1, "y": 2, "w": 4, "h": 4 },
+              "sourceSize": { "w": 6, "h": 8 } }
+        ] }"#;
+        let sheet: JsonSheet = serde_json::from_str(array).unwrap();
+        let data = SpriteSheet::parse_frames(sheet.frames).unwrap();
+        assert_eq!(data.names["b"], 0);
+        assert_eq!(data.frames[0].offset, mint::Point2 { x: 1., y: 2. });
+        assert_eq!(data.frames[0].source_size, mint::Vector2 { x: 6., y: 8. });
+        assert_eq!(data.frames[0].duration, DEFAULT_FRAME_DURATION);
+    }
+
+    // frames of an eighth of a second, which scale by the speed without rounding
+    fn playback(frames: usize, mode: PlayMode) -> Playback {
+        let mut playback = Playback::new(
+            (0..frames)
+                .map(|index| (index, Duration::from_millis(125)))
+                .collect(),
+        );
+        playback.set_mode(mode);
+        playback
+    }
+
+    fn eighths(n: u64) -> Duration {
+        Duration::from_millis(125 * n)
+    }
+
+    const SIXTEENTH: Duration = Duration::from_micros(62_500);
+
+    #[test]
+    fn once_stops_on_the_last_frame() {
+        let mut playback = playback(3, PlayMode::Once);
+        playback.update(eighths(1));
+        assert_eq!(playback.position, 1);
+        playback.update(SIXTEENTH);
+        assert_eq!(playback.position, 1);
+        playback.update(SIXTEENTH);
+        assert_eq!(playback.position, 2);
+        assert!(playback.playing && !playback.finished);
+
+        // the last frame is shown for its whole duration before finishing
+        playback.update(SIXTEENTH);
+        assert!(playback.playing && !playback.finished);
+        playback.update(SIXTEENTH);
+        assert_eq!(playback.position, 2);
+        assert!(!playback.playing && playback.finished);
+
+        playback.update(eighths(10));
+        assert_eq!(playback.position, 2);
+    }
+
+    #[test]
+    fn loop_and_ping_pong_turn_around() {
+        let mut looping = playback(3, PlayMode::Loop);
+        let mut ping_pong = playback(3, PlayMode::PingPong);
+        let (mut looped, mut ponged) = (Vec::new(), Vec::new());
+        for _ in 0..7 {
+            looping.update(eighths(1));
+            ping_pong.update(eighths(1));
+            looped.push(looping.position);
+            ponged.push(ping_pong.position);
+        }
+        assert_eq!(looped, [1, 2, 0, 1, 2, 0, 1]);
+        assert_eq!(ponged, [1, 2, 1, 0, 1, 2, 1]);
+
+        // a single frame has nowhere to turn to
+        let mut single = playback(1, PlayMode::PingPong);
+        single.update(eighths(3));
+        assert_eq!(single.position, 0);
+    }
+
+    #[test]
+    fn long_updates_pass_several_frames_and_fire_their_events() {
+        let mut playback = playback(4, PlayMode::Loop);
+        playback.events = vec![
+            (0, "start".to_owned()),
+            (1, "one".to_owned()),
+            (3, "three".to_owned()),
+        ];
+
+        playback.update(Duration::ZERO);
+        assert_eq!(playback.events().collect::<Vec<_>>(), ["start"]);
+
+        // frames 1, 2, 3 and back to 0, then halfway through it
+        playback.update(eighths(4) + Duration::from_millis(60));
+        assert_eq!(playback.position, 0);
+        assert_eq!(
+            playback.events().collect::<Vec<_>>(),
+            ["one", "three", "start"]
+        );
+        playback.update(Duration::from_millis(65));
+        assert_eq!(playback.position, 1);
+        assert_eq!(playback.events().collect::<Vec<_>>(), ["one"]);
+
+        playback.update(SIXTEENTH);
+        assert_eq!(playback.events().count(), 0);
+    }
+
+    #[test]
+    fn speed_scales_time() {
+        let mut playback = playback(4, PlayMode::Loop);
+        playback.set_speed(2.);
+        playback.update(eighths(1));
+        assert_eq!(playback.position, 2);
+
+        playback.set_speed(0.);
+        playback.update(eighths(10));
+        assert_eq!(playback.position, 2);
+
+        playback.set_speed(-1.);
+        assert_eq!(playback.speed, 0.);
+        playback.update(eighths(10));
+        assert_eq!(playback.position, 2);
+        assert!(playback.playing);
+    }
+}
@@ -0,0 +1,41 @@
+/// Counters describing the rendering work done during a single frame.
+///
+/// Retrieved with [`GraphicsContext::frame_stats`](super::GraphicsContext::frame_stats), which returns the
+/// statistics of the last *completed* frame (i.e. the one most recently passed to
+/// [`GraphicsContext::end_frame`](super::GraphicsContext::end_frame)).
+///
+/// These are meant as a first stop when a scene is slower than expected; for example a high
+/// `pipeline_switches` or `bind_group_misses` count usually means draws with different shaders,
+/// blend modes or images are interleaved and could be reordered or batched.
+/// The internal pass that copies the final image to the window is not included.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FrameStats {
+    /// Number of [`Canvas`](super::Canvas)es finished.
+    pub canvases: usize,
+    /// Number of render passes begun.
+    pub render_passes: usize,
+    /// Number of draw calls issued.
+    pub draw_calls: usize,
+    /// Number of times a different render pipeline was bound.
+    pub pipeline_switches: usize,
+    /// Number of bind groups that were found in the bind group cache.
+    pub bind_group_hits: usize,
+    /// Number of bind groups that had to be created because they weren't cached.
+    pub bind_group_misses: usize,
+    /// Number of vertices submitted. For indexed meshes this counts indices, once per instance.
+    pub vertices: usize,
+    /// Number of instances submitted. A non-instanced draw counts as one instance.
+    pub instances: usize,
+    /// Number of draws and [`InstanceArray`](super::InstanceArray) instances skipped by culling
+    /// (see [`Canvas::set_culling`](super::Canvas::set_culling)).
+    pub culled: usize,
+    /// Number of text glyphs queued for drawing.
+    pub glyphs: usize,
+    /// Number of bytes uploaded for drawing: uniforms, text vertices, instance arrays and batches,
+    /// and [`DynamicTexture`](super::DynamicTexture) writes.
+    pub bytes_written: u64,
+    /// Number of GPU textures alive at the end of the frame.
+    pub textures_alive: usize,
+    /// Number of GPU buffers alive at the end of the frame.
+    pub buffers_alive: usize,
+}
@@ -0,0 +1,112 @@
+pub use wgpu::{CompareFunction, StencilOperation};
+
+/// Describes how a draw tests and changes the stencil buffer of a [`Canvas`](super::Canvas).
+///
+/// The stencil buffer holds a small counter per pixel, starting at 0 for every canvas. Masks
+/// are drawn with [`StencilMode::mask`], which raises the counter wherever they cover and
+/// leaves the colors alone, and other draws are then limited to where the counter is at
+/// least ([`StencilMode::inside`]) or below ([`StencilMode::outside`]) a level. Nesting a
+/// mask inside another one raises the counter to the next level.
+///
+/// Masks cover every pixel of the drawn geometry, regardless of the alpha of the image or
+/// color, so shapes should be drawn as meshes or with a shader that discards transparent
+/// pixels.
+///
+/// The canvas only gets a stencil buffer if one of its draws uses a mode other than
+/// [`StencilMode::DISABLED`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StencilMode {
+    /// Comparison between `reference` and the stored value, as `reference <compare> stored`,
+    /// that must pass for a pixel to be drawn.
+    pub compare: CompareFunction,
+    /// What happens to the stored value of the pixels that pass the comparison.
+    pub pass_op: StencilOperation,
+    /// The value compared against, and written by [`StencilOperation::Replace`].
+    pub reference: u32,
+    /// Whether passing pixels are also drawn to the color image.
+    pub write_color: bool,
+}
+
+impl StencilMode {
+    /// Ignores the stencil buffer; this is the default.
+    pub const DISABLED: Self = StencilMode {
+        compare: CompareFunction::Always,
+        pass_op: StencilOperation::Keep,
+        reference: 0,
+        write_color: true,
+    };
+
+    /// Draws a mask inside the pixels at `level`, raising them to `level + 1`.
+    ///
+    /// The first mask is drawn at level 0, and a mask nested inside it at level 1.
+    pub const fn mask(level: u32) -> Self {
+        StencilMode {
+            compare: CompareFunction::Equal,
+            pass_op: StencilOperation::IncrementClamp,
+            reference: level,
+            write_color: false,
+        }
+    }
+
+    /// Removes a mask from the pixels at `level`, lowering them to `level - 1`.
+    pub const fn unmask(level: u32) -> Self {
+        StencilMode {
+            compare: CompareFunction::Equal,
+            pass_op: StencilOperation::DecrementClamp,
+            reference: level,
+            write_color: false,
+        }
+    }
+
+    /// Resets the covered pixels to level 0, erasing every mask there.
+    pub const fn erase() -> Self {
+        StencilMode {
+            compare: CompareFunction::Always,
+            pass_op: StencilOperation::Replace,
+            reference: 0,
+            write_color: false,
+        }
+    }
+
+    /// Draws only where at least `level` masks overlap.
+    pub const fn inside(level: u32) -> Self {
+        StencilMode {
+            compare: CompareFunction::LessEqual,
+            pass_op: StencilOperation::Keep,
+            reference: level,
+            write_color: true,
+        }
+    }
+
+    /// Draws only where fewer than `level` masks overlap; `outside(1)` draws wherever there's
+    /// no mask at all.
+    pub const fn outside(level: u32) -> Self {
+        StencilMode {
+            compare: CompareFunction::Greater,
+            pass_op: StencilOperation::Keep,
+            reference: level,
+            write_color: true,
+        }
+    }
+
+    pub(crate) fn state(&self) -> wgpu::StencilState {
+        let face = wgpu::StencilFaceState {
+            compare: self.compare,
+            fail_op: StencilOperation::Keep,
+            depth_fail_op: StencilOperation::Keep,
+            pass_op: self.pass_op,
+        };
+        wgpu::StencilState {
+            front: face,
+            back: face,
+            read_mask: !0,
+            write_mask: !0,
+        }
+    }
+}
+
+impl Default for StencilMode {
+    fn default() -> Self {
+        Self::DISABLED
+    }
+}
@@ -16,6 +16,7 @@ impl FontData {
     /// Loads font data from a given path in the filesystem.
     #[allow(unused_results)]
     pub fn from_path(path: impl AsRef<Path>) -> GameResult<Self> {
+        trace_scope!("FontData::from_path", path = %path.as_ref().display());
         let mut bytes = vec![];
         File::open(path)?.read_to_end(&mut bytes)?;
         Ok(FontData {
@@ -25,6 +26,7 @@ impl FontData {
 
     /// Loads font data from owned bytes.
     pub fn from_vec(data: Vec<u8>) -> GameResult<Self> {
+        trace_scope!("FontData::from_vec", bytes = data.len());
         Ok(FontData {
             font: ab_glyph::FontArc::try_from_vec(data)?,
         })
@@ -239,6 +241,20 @@ impl Text {
             .unwrap_or_else(|| mint::Vector2::<f32> { x: 0., y: 0. }))
     }
 
+    /// Returns the glyph boundaries relative to the draw position, which can start left of or
+    /// above it depending on the layout.
+    pub(crate) fn bounds_raw(
+        &self,
+        text: &TextRenderer,
+        fonts: &HashMap<String, FontId>,
+    ) -> GameResult<Option<Rect>> {
+        Ok(text
+            .glyph_brush
+            .borrow_mut()
+            .glyph_bounds(self.as_section(fonts, DrawParam::default())?)
+            .map(|rect| Rect::new(rect.min.x, rect.min.y, rect.width(), rect.height())))
+    }
+
     pub(crate) fn as_section<'a>(
         &'a self,
         fonts: &HashMap<String, FontId>,
@@ -269,7 +285,7 @@ impl Text {
                             .ok_or_else(|| GameError::FontSelectError(font.clone()))?,
                         extra: Extra {
                             color: text.color.unwrap_or(param.color).into(),
-                            transform: param.transform.to_bare_matrix().into(),
+                            transform: param.transform.to_matrix_at_depth(param.depth).into(),
                         },
                     })
                 })
@@ -0,0 +1,987 @@
+//! Loading and drawing of maps made with the [Tiled](https://www.mapeditor.org/) editor.
+//!
+//! Both the XML (`.tmx`) and JSON (`.tmj`) formats are supported, with external tilesets
+//! (`.tsx`/`.tsj`), all tile data encodings and compressions except zstd, flipped and rotated
+//! tiles, object layers, custom properties and animated tiles. Maps must be orthogonal and
+//! finite, and every tileset must use a single image.
+//!
+//! Tile layers are drawn through [`InstanceArray`]s, one per tileset in every 16x16 chunk of
+//! the layer. Changing a tile or advancing a tile animation only rebuilds the chunks affected.
+//!
+//! ```rust,no_run
+//! # use ggez::graphics::{self, tilemap::{Layer, TileMap}};
+//! # fn t(ctx: &mut ggez::Context) -> ggez::GameResult {
+//! let mut map = TileMap::from_path(ctx, "resources/level1.tmx")?;
+//!
+//! // gameplay code reads the object layers
+//! for layer in map.layers() {
+//!     if let Layer::Objects(objects) = layer {
+//!         for object in &objects.objects {
+//!             println!("{} at {:?}", object.name, object.rect());
+//!         }
+//!     }
+//! }
+//!
+//! // once per frame
+//! map.update(ctx, ctx.time.delta());
+//! let mut canvas = graphics::Canvas::from_frame(ctx, None);
+//! canvas.draw(&map, graphics::DrawParam::default());
+//! canvas.finish(ctx)?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod tmj;
+mod tmx;
+
+use super::{
+    context::GraphicsContext, image::Image, Canvas, Color, DrawParam, Drawable, InstanceArray, Rect,
+};
+use crate::{context::Has, GameError, GameResult};
+use std::{
+    collections::HashMap,
+    f32::consts::FRAC_PI_2,
+    io::Read,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Width and height of the chunks tile layers are drawn in, in tiles.
+const CHUNK_SIZE: u32 = 16;
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+/// Only used by hexagonal maps, but still has to be masked out.
+const ROTATED_HEXAGONAL_120: u32 = 0x1000_0000;
+
+/// Custom properties of a map, layer, tileset, tile or object.
+pub type Properties = HashMap<String, PropertyValue>;
+
+/// The value of a custom property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    /// A `bool` property.
+    Bool(bool),
+    /// An `int` property.
+    Int(i64),
+    /// A `float` property.
+    Float(f64),
+    /// A `string` property.
+    String(String),
+    /// A `color` property.
+    Color(Color),
+    /// A `file` property, holding the path as written in the map.
+    File(String),
+    /// An `object` property, holding the ID of the object it refers to.
+    Object(u32),
+    /// A property of a custom class, holding its members.
+    Class(Properties),
+}
+
+impl PropertyValue {
+    /// Parses a property of a type other than `class` from its textual value.
+    fn parse(ty: &str, value: &str) -> GameResult<Self> {
+        let invalid = || GameError::ResourceLoadError(format!("invalid {ty} property `{value}`"));
+        Ok(match ty {
+            "bool" => PropertyValue::Bool(value == "true"),
+            "int" => PropertyValue::Int(value.parse().map_err(|_| invalid())?),
+            "float" => PropertyValue::Float(value.parse().map_err(|_| invalid())?),
+            "color" => PropertyValue::Color(parse_color(value).ok_or_else(invalid)?),
+            "file" => PropertyValue::File(value.to_owned()),
+            "object" => PropertyValue::Object(value.parse().map_err(|_| invalid())?),
+            _ => PropertyValue::String(value.to_owned()),
+        })
+    }
+}
+
+/// Parses Tiled's `#AARRGGBB` or `#RRGGBB` colors. An empty string is transparent.
+fn parse_color(value: &str) -> Option<Color> {
+    let hex = value.trim_start_matches('#');
+    if hex.is_empty() {
+        return Some(Color::new(0., 0., 0., 0.));
+    }
+    let argb = u32::from_str_radix(hex, 16).ok()?;
+    match hex.len() {
+        6 => Some(Color::from_rgb_u32(argb)),
+        8 => Some(Color::from_rgba_u32(argb.rotate_left(8))),
+        _ => None,
+    }
+}
+
+/// One frame of an animated tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationFrame {
+    /// ID of the tile shown, within the same tileset.
+    pub tile: u32,
+    /// How long the tile is shown.
+    pub duration: Duration,
+}
+
+/// Extra data attached to a tile of a tileset.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TileInfo {
+    /// The class (formerly "type") of the tile.
+    pub class: String,
+    /// Custom properties of the tile.
+    pub properties: Properties,
+    /// Frames of the tile's animation; empty if it isn't animated.
+    pub animation: Vec<AnimationFrame>,
+}
+
+/// A tileset, a grid of tiles cut from a single image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tileset {
+    /// Name of the tileset.
+    pub name: String,
+    /// The global tile ID of the first tile in the set.
+    pub first_gid: u32,
+    /// Width of a tile, in pixels.
+    pub tile_width: u32,
+    /// Height of a tile, in pixels.
+    pub tile_height: u32,
+    /// Space between tiles in the image, in pixels.
+    pub spacing: u32,
+    /// Space around the tiles in the image, in pixels.
+    pub margin: u32,
+    /// Number of tiles in the set.
+    pub tile_count: u32,
+    /// Number of tile columns in the image.
+    pub columns: u32,
+    /// Offset applied when drawing tiles of this set, in pixels.
+    pub offset: mint::Vector2<f32>,
+    /// Path of the image, relative to the working directory when loaded with
+    /// [`TileMap::from_path`].
+    pub image: PathBuf,
+    /// Extra data of the tiles that have any, keyed by tile ID.
+    pub tiles: HashMap<u32, TileInfo>,
+    /// Custom properties of the tileset.
+    pub properties: Properties,
+}
+
+impl Tileset {
+    /// Returns the position and size of a tile in the tileset's image, in pixels.
+    pub fn tile_rect(&self, id: u32) -> Rect {
+        let columns = self.columns.max(1);
+        let (column, row) = (id % columns, id / columns);
+        Rect::new(
+            (self.margin + column * (self.tile_width + self.spacing)) as f32,
+            (self.margin + row * (self.tile_height + self.spacing)) as f32,
+            self.tile_width as f32,
+            self.tile_height as f32,
+        )
+    }
+
+    /// Returns the tile shown at `time` into the tile's animation, which is the tile itself
+    /// if it isn't animated.
+    pub fn animated_tile(&self, id: u32, time: Duration) -> u32 {
+        let Some(info) = self
+            .tiles
+            .get(&id)
+            .filter(|info| !info.animation.is_empty())
+        else {
+            return id;
+        };
+        let total: Duration = info.animation.iter().map(|frame| frame.duration).sum();
+        if total.is_zero() {
+            return id;
+        }
+        let mut t = Duration::from_nanos((time.as_nanos() % total.as_nanos()) as u64);
+        for frame in &info.animation {
+            if t < frame.duration {
+                return frame.tile;
+            }
+            t -= frame.duration;
+        }
+        id
+    }
+
+    fn is_animated(&self, id: u32) -> bool {
+        self.tiles
+            .get(&id)
+            .is_some_and(|info| !info.animation.is_empty())
+    }
+}
+
+/// A tile placed on a [`TileLayer`] or used by a tile object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tile {
+    /// Index of the tileset in [`TileMap::tilesets`].
+    pub tileset: usize,
+    /// ID of the tile within its tileset.
+    pub id: u32,
+    /// Whether the tile is mirrored left to right.
+    pub flip_horizontal: bool,
+    /// Whether the tile is mirrored top to bottom.
+    pub flip_vertical: bool,
+    /// Whether the tile's x and y axes are swapped, which combined with the other flips
+    /// rotates it by 90 degree steps. Applied before the other flips.
+    pub flip_diagonal: bool,
+}
+
+impl Tile {
+    /// Decodes a global tile ID with its flip flags. Returns `None` for empty cells.
+    fn from_gid(gid: u32, tilesets: &[Tileset]) -> GameResult<Option<Self>> {
+        let id = gid
+            & !(FLIPPED_HORIZONTALLY
+                | FLIPPED_VERTICALLY
+                | FLIPPED_DIAGONALLY
+                | ROTATED_HEXAGONAL_120);
+        if id == 0 {
+            return Ok(None);
+        }
+        let tileset = tilesets
+            .iter()
+            .rposition(|tileset| tileset.first_gid <= id)
+            .ok_or_else(|| {
+                GameError::ResourceLoadError(format!("tile {id} doesn't belong to any tileset"))
+            })?;
+        Ok(Some(Tile {
+            tileset,
+            id: id - tilesets[tileset].first_gid,
+            flip_horizontal: gid & FLIPPED_HORIZONTALLY != 0,
+            flip_vertical: gid & FLIPPED_VERTICALLY != 0,
+            flip_diagonal: gid & FLIPPED_DIAGONALLY != 0,
+        }))
+    }
+
+    /// Returns the rotation and scale that flip the tile around its center.
+    fn orientation(&self) -> (f32, mint::Vector2<f32>) {
+        let sx = if self.flip_horizontal { -1. } else { 1. };
+        let sy = if self.flip_vertical { -1. } else { 1. };
+        if self.flip_diagonal {
+            // swapping the axes is a quarter turn of the tile mirrored top to bottom
+            (FRAC_PI_2, mint::Vector2 { x: sy, y: -sx })
+        } else {
+            (0., mint::Vector2 { x: sx, y: sy })
+        }
+    }
+}
+
+/// A layer made of a grid of tiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileLayer {
+    /// Name of the layer.
+    pub name: String,
+    /// Width of the layer, in tiles.
+    pub width: u32,
+    /// Height of the layer, in tiles.
+    pub height: u32,
+    /// Opacity of the layer, including that of the groups it's in.
+    pub opacity: f32,
+    /// Whether the layer is shown, taking the groups it's in into account.
+    pub visible: bool,
+    /// Offset of the layer, including that of the groups it's in, in pixels.
+    pub offset: mint::Vector2<f32>,
+    /// Custom properties of the layer.
+    pub properties: Properties,
+    tiles: Vec<Option<Tile>>,
+}
+
+impl TileLayer {
+    /// Returns the tile at a position of the grid, if there is one.
+    pub fn tile(&self, x: u32, y: u32) -> Option<Tile> {
+        if x < self.width && y < self.height {
+            self.tiles[(y * self.width + x) as usize]
+        } else {
+            None
+        }
+    }
+}
+
+/// The shape of a [`MapObject`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectShape {
+    /// A rectangle, which is also the shape of tile objects.
+    Rect,
+    /// An ellipse inside the object's rectangle.
+    Ellipse,
+    /// A single point.
+    Point,
+    /// A closed polygon, with points relative to the object's position.
+    Polygon(Vec<mint::Point2<f32>>),
+    /// An open line, with points relative to the object's position.
+    Polyline(Vec<mint::Point2<f32>>),
+}
+
+/// An object placed on an [`ObjectLayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapObject {
+    /// Unique ID of the object.
+    pub id: u32,
+    /// Name of the object.
+    pub name: String,
+    /// The class (formerly "type") of the object.
+    pub class: String,
+    /// Position of the object, in pixels.
+    ///
+    /// This is the top left corner, except for tile objects, where it's the bottom left.
+    pub position: mint::Point2<f32>,
+    /// Size of the object, in pixels.
+    pub size: mint::Vector2<f32>,
+    /// Rotation around `position`, in degrees clockwise.
+    pub rotation: f32,
+    /// Whether the object is shown.
+    pub visible: bool,
+    /// Shape of the object.
+    pub shape: ObjectShape,
+    /// The tile shown by tile objects.
+    pub tile: Option<Tile>,
+    /// Custom properties of the object.
+    pub properties: Properties,
+}
+
+impl MapObject {
+    /// Returns the outline of the object, with rotation applied.
+    ///
+    /// Rectangles, ellipses and tile objects give the corners of their rectangle;
+    /// polygons and polylines their points.
+    pub fn points(&self) -> Vec<mint::Point2<f32>> {
+        let (w, h) = (self.size.x, self.size.y);
+        // tile objects are anchored at their bottom left corner
+        let top = if self.tile.is_some() { -h } else { 0. };
+        let local = match &self.shape {
+            ObjectShape::Polygon(points) | ObjectShape::Polyline(points) => points.clone(),
+            ObjectShape::Point => vec![mint::Point2 { x: 0., y: 0. }],
+            ObjectShape::Rect | ObjectShape::Ellipse => {
+                [(0., top), (w, top), (w, top + h), (0., top + h)]
+                    .into_iter()
+                    .map(|(x, y)| mint::Point2 { x, y })
+                    .collect()
+            }
+        };
+
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        local
+            .into_iter()
+            .map(|p| mint::Point2 {
+                x: self.position.x + p.x * cos - p.y * sin,
+                y: self.position.y + p.x * sin + p.y * cos,
+            })
+            .collect()
+    }
+
+    /// Returns the axis aligned bounding box of the object, with rotation applied.
+    pub fn rect(&self) -> Rect {
+        let points = self.points();
+        let (mut min, mut max) = (points[0], points[0]);
+        for p in &points[1..] {
+            min = mint::Point2 {
+                x: min.x.min(p.x),
+                y: min.y.min(p.y),
+            };
+            max = mint::Point2 {
+                x: max.x.max(p.x),
+                y: max.y.max(p.y),
+            };
+        }
+        Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+}
+
+/// A layer of free-form objects, such as spawn points or collision shapes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectLayer {
+    /// Name of the layer.
+    pub name: String,
+    /// Opacity of the layer, including that of the groups it's in.
+    pub opacity: f32,
+    /// Whether the layer is shown, taking the groups it's in into account.
+    pub visible: bool,
+    /// Offset of the layer, including that of the groups it's in, in pixels.
+    pub offset: mint::Vector2<f32>,
+    /// Custom properties of the layer.
+    pub properties: Properties,
+    /// The objects on the layer.
+    pub objects: Vec<MapObject>,
+}
+
+/// A layer of a [`TileMap`]. Layers inside groups are flattened into the map's list of layers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Layer {
+    /// A grid of tiles.
+    Tiles(TileLayer),
+    /// Free-form objects.
+    Objects(ObjectLayer),
+}
+
+impl Layer {
+    /// Returns the name of the layer.
+    pub fn name(&self) -> &str {
+        match self {
+            Layer::Tiles(layer) => &layer.name,
+            Layer::Objects(layer) => &layer.name,
+        }
+    }
+}
+
+/// Opacity, visibility and offset inherited from the groups a layer is in.
+#[derive(Debug, Clone, Copy)]
+struct Inherited {
+    opacity: f32,
+    visible: bool,
+    offset: mint::Vector2<f32>,
+}
+
+impl Default for Inherited {
+    fn default() -> Self {
+        Inherited {
+            opacity: 1.,
+            visible: true,
+            offset: mint::Vector2 { x: 0., y: 0. },
+        }
+    }
+}
+
+impl Inherited {
+    fn apply(self, opacity: f32, visible: bool, offset: mint::Vector2<f32>) -> Self {
+        Inherited {
+            opacity: self.opacity * opacity,
+            visible: self.visible && visible,
+            offset: mint::Vector2 {
+                x: self.offset.x + offset.x,
+                y: self.offset.y + offset.y,
+            },
+        }
+    }
+}
+
+/// A parsed map, before its tileset images are loaded.
+#[derive(Debug)]
+struct MapData {
+    width: u32,
+    height: u32,
+    tile_width: u32,
+    tile_height: u32,
+    properties: Properties,
+    tilesets: Vec<Tileset>,
+    layers: Vec<Layer>,
+}
+
+/// Checks the map attributes the renderer depends on.
+fn check_map(orientation: &str, infinite: bool) -> GameResult {
+    if orientation != "orthogonal" {
+        return Err(GameError::ResourceLoadError(format!(
+            "{orientation} maps aren't supported"
+        )));
+    }
+    if infinite {
+        return Err(GameError::ResourceLoadError(String::from(
+            "infinite maps aren't supported",
+        )));
+    }
+    Ok(())
+}
+
+/// Decodes the contents of a tile layer's data into global tile IDs.
+fn decode_gids(data: &str, encoding: &str, compression: &str) -> GameResult<Vec<u32>> {
+    let invalid = || GameError::ResourceLoadError(String::from("invalid tile layer data"));
+    if encoding == "csv" {
+        return data
+            .split(',')
+            .map(|gid| gid.trim().parse().map_err(|_| invalid()))
+            .collect();
+    }
+    if encoding != "base64" {
+        return Err(GameError::ResourceLoadError(format!(
+            "unknown tile layer encoding `{encoding}`"
+        )));
+    }
+
+    let bytes = decode_base64(data.trim()).ok_or_else(invalid)?;
+    let mut raw = Vec::new();
+    match compression {
+        "" => raw = bytes,
+        "zlib" => {
+            let _ = flate2::read::ZlibDecoder::new(bytes.as_slice()).read_to_end(&mut raw)?;
+        }
+        "gzip" => {
+            let _ = flate2::read::GzDecoder::new(bytes.as_slice()).read_to_end(&mut raw)?;
+        }
+        other => {
+            return Err(GameError::ResourceLoadError(format!(
+                "unsupported tile layer compression `{other}`"
+            )))
+        }
+    }
+    if raw.len() % 4 != 0 {
+        return Err(invalid());
+    }
+    Ok(raw
+        .chunks_exact(4)
+        .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+        .collect())
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for c in text.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            c if c.is_ascii_whitespace() => continue,
+            _ => return None,
+        };
+        bits = bits << 6 | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Builds a tile layer from its global tile IDs.
+fn tile_layer(
+    name: String,
+    (width, height): (u32, u32),
+    gids: &[u32],
+    tilesets: &[Tileset],
+    inherited: Inherited,
+    properties: Properties,
+) -> GameResult<TileLayer> {
+    if gids.len() != (width * height) as usize {
+        return Err(GameError::ResourceLoadError(format!(
+            "layer `{name}` has {} tiles instead of {width}x{height}",
+            gids.len()
+        )));
+    }
+    Ok(TileLayer {
+        name,
+        width,
+        height,
+        opacity: inherited.opacity,
+        visible: inherited.visible,
+        offset: inherited.offset,
+        properties,
+        tiles: gids
+            .iter()
+            .map(|&gid| Tile::from_gid(gid, tilesets))
+            .collect::<GameResult<_>>()?,
+    })
+}
+
+/// The instance arrays drawing one chunk of a tile layer.
+#[derive(Debug)]
+struct Chunk {
+    x: u32,
+    y: u32,
+    /// One array per tileset, created once the chunk uses the tileset.
+    arrays: Vec<Option<InstanceArray>>,
+    dirty: bool,
+    animated: bool,
+}
+
+/// A map loaded from Tiled, ready to be drawn.
+///
+/// See the [module documentation](self) for what's supported.
+#[derive(Debug)]
+pub struct TileMap {
+    width: u32,
+    height: u32,
+    tile_width: u32,
+    tile_height: u32,
+    properties: Properties,
+    tilesets: Vec<Tileset>,
+    images: Vec<Image>,
+    layers: Vec<Layer>,
+    /// Chunks of every layer; empty for object layers.
+    chunks: Vec<Vec<Chunk>>,
+    time: Duration,
+}
+
+impl TileMap {
+    /// Loads a map from a `.tmx` or `.tmj` file, along with its external tilesets and images.
+    ///
+    /// The format is picked by the file extension; anything but `.tmj` and `.json`
+    /// is read as XML. Paths inside the map are resolved relative to its directory.
+    pub fn from_path(gfx: &impl Has<GraphicsContext>, path: impl AsRef<Path>) -> GameResult<Self> {
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let contents = std::fs::read(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("tmj" | "json") => Self::from_tmj(gfx, &contents, dir),
+            _ => Self::from_tmx(gfx, &contents, dir),
+        }
+    }
+
+    /// Loads a map from the contents of a `.tmx` file, resolving paths relative to `dir`.
+    pub fn from_tmx(
+        gfx: &impl Has<GraphicsContext>,
+        xml: &[u8],
+        dir: impl AsRef<Path>,
+    ) -> GameResult<Self> {
+        Self::new(gfx, tmx::parse_map(xml, dir.as_ref())?)
+    }
+
+    /// Loads a map from the contents of a `.tmj` file, resolving paths relative to `dir`.
+    pub fn from_tmj(
+        gfx: &impl Has<GraphicsContext>,
+        json: &[u8],
+        dir: impl AsRef<Path>,
+    ) -> GameResult<Self> {
+        Self::new(gfx, tmj::parse_map(json, dir.as_ref())?)
+    }
+
+    fn new(gfx: &impl Has<GraphicsContext>, data: MapData) -> GameResult<Self> {
+        let images = data
+            .tilesets
+            .iter()
+            .map(|tileset| Image::from_path(gfx, &tileset.image))
+            .collect::<GameResult<_>>()?;
+
+        let chunks = data
+            .layers
+            .iter()
+            .map(|layer| match layer {
+                Layer::Tiles(layer) => {
+                    let columns = layer.width.div_ceil(CHUNK_SIZE);
+                    let rows = layer.height.div_ceil(CHUNK_SIZE);
+                    (0..rows)
+                        .flat_map(|y| (0..columns).map(move |x| (x, y)))
+                        .map(|(x, y)| Chunk {
+                            x,
+                            y,
+                            arrays: Vec::new(),
+                            dirty: true,
+                            animated: false,
+                        })
+                        .collect()
+                }
+                Layer::Objects(_) => Vec::new(),
+            })
+            .collect();
+
+        let mut map = TileMap {
+            width: data.width,
+            height: data.height,
+            tile_width: data.tile_width,
+            tile_height: data.tile_height,
+            properties: data.properties,
+            tilesets: data.tilesets,
+            images,
+            layers: data.layers,
+            chunks,
+            time: Duration::ZERO,
+        };
+        map.rebuild(gfx);
+        Ok(map)
+    }
+
+    /// Returns the width of the map, in tiles.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the map, in tiles.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the width of the map's grid cells, in pixels.
+    #[inline]
+    pub fn tile_width(&self) -> u32 {
+        self.tile_width
+    }
+
+    /// Returns the height of the map's grid cells, in pixels.
+    #[inline]
+    pub fn tile_height(&self) -> u32 {
+        self.tile_height
+    }
+
+    /// Returns the custom properties of the map.
+    #[inline]
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    /// Returns the tilesets, in the order of their first global tile ID.
+    #[inline]
+    pub fn tilesets(&self) -> &[Tileset] {
+        &self.tilesets
+    }
+
+    /// Returns the image of the tileset at `index`.
+    pub fn tileset_image(&self, index: usize) -> Option<&Image> {
+        self.images.get(index)
+    }
+
+    /// Returns all layers, from bottom to top.
+    #[inline]
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Returns the index of the first layer with the given name.
+    pub fn layer_index(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|layer| layer.name() == name)
+    }
+
+    /// Returns the objects of all object layers.
+    pub fn objects(&self) -> impl Iterator<Item = &MapObject> {
+        self.layers.iter().flat_map(|layer| match layer {
+            Layer::Objects(layer) => layer.objects.as_slice(),
+            Layer::Tiles(_) => &[],
+        })
+    }
+
+    /// Returns the tile at a grid position of a tile layer.
+    pub fn tile(&self, layer: usize, x: u32, y: u32) -> Option<Tile> {
+        match self.layers.get(layer)? {
+            Layer::Tiles(layer) => layer.tile(x, y),
+            Layer::Objects(_) => None,
+        }
+    }
+
+    /// Replaces the tile at a grid position of a tile layer. The change is drawn after the
+    /// next [`TileMap::update`].
+    ///
+    /// Fails if there's no such tile layer or position, or the tile's tileset doesn't exist.
+    pub fn set_tile(&mut self, layer: usize, x: u32, y: u32, tile: Option<Tile>) -> GameResult {
+        if tile.is_some_and(|tile| tile.tileset >= self.tilesets.len()) {
+            return Err(GameError::CustomError(String::from("no such tileset")));
+        }
+        let Some(Layer::Tiles(tiles)) = self.layers.get_mut(layer) else {
+            return Err(GameError::CustomError(String::from("no such tile layer")));
+        };
+        if x >= tiles.width || y >= tiles.height {
+            return Err(GameError::CustomError(String::from(
+                "position is outside of the layer",
+            )));
+        }
+
+        tiles.tiles[(y * tiles.width + x) as usize] = tile;
+        let columns = tiles.width.div_ceil(CHUNK_SIZE);
+        self.chunks[layer][((y / CHUNK_SIZE) * columns + x / CHUNK_SIZE) as usize].dirty = true;
+        Ok(())
+    }
+
+    /// Advances tile animations by `dt` and rebuilds the chunks that changed since the last
+    /// update, whether through animations or [`TileMap::set_tile`].
+    pub fn update(&mut self, gfx: &impl Has<GraphicsContext>, dt: Duration) {
+        let before = self.time;
+        self.time += dt;
+
+        let frame_changed = self.tilesets.iter().any(|tileset| {
+            tileset.tiles.keys().any(|&id| {
+                tileset.animated_tile(id, before) != tileset.animated_tile(id, self.time)
+            })
+        });
+        if frame_changed {
+            for chunk in self.chunks.iter_mut().flatten() {
+                chunk.dirty |= chunk.animated;
+            }
+        }
+
+        self.rebuild(gfx);
+    }
+
+    fn rebuild(&mut self, gfx: &impl Has<GraphicsContext>) {
+        // taken out so the chunks can be changed while the rest of the map is read
+        let mut all_chunks = std::mem::take(&mut self.chunks);
+        for (layer, chunks) in self.layers.iter().zip(&mut all_chunks) {
+            let Layer::Tiles(layer) = layer else {
+                continue;
+            };
+            for chunk in chunks.iter_mut().filter(|chunk| chunk.dirty) {
+                let mut params = vec![Vec::new(); self.tilesets.len()];
+                chunk.animated = false;
+
+                let (x0, y0) = (chunk.x * CHUNK_SIZE, chunk.y * CHUNK_SIZE);
+                for y in y0..(y0 + CHUNK_SIZE).min(layer.height) {
+                    for x in x0..(x0 + CHUNK_SIZE).min(layer.width) {
+                        let Some(tile) = layer.tile(x, y) else {
+                            continue;
+                        };
+                        let tileset = &self.tilesets[tile.tileset];
+                        chunk.animated |= tileset.is_animated(tile.id);
+                        params[tile.tileset].push(self.tile_param(layer, tile, x, y));
+                    }
+                }
+
+                chunk.arrays.resize_with(self.tilesets.len(), || None);
+                for ((array, params), image) in
+                    chunk.arrays.iter_mut().zip(params).zip(&self.images)
+                {
+                    match array {
+                        Some(array) => array.set(params),
+                        None if !params.is_empty() => {
+                            let mut new = InstanceArray::new(gfx, image.clone());
+                            new.set(params);
+                            *array = Some(new);
+                        }
+                        None => {}
+                    }
+                }
+                chunk.dirty = false;
+            }
+        }
+        self.chunks = all_chunks;
+    }
+
+    fn tile_param(&self, layer: &TileLayer, tile: Tile, x: u32, y: u32) -> DrawParam {
+        let tileset = &self.tilesets[tile.tileset];
+        let image = &self.images[tile.tileset];
+        let src = tileset.tile_rect(tileset.animated_tile(tile.id, self.time));
+        let (iw, ih) = (image.width() as f32, image.height() as f32);
+
+        // tiles larger than the grid stick out at the top, as in Tiled
+        let (w, h) = (src.w, src.h);
+        let left = (x * self.tile_width) as f32 + layer.offset.x + tileset.offset.x;
+        let top = ((y + 1) * self.tile_height) as f32 - h + layer.offset.y + tileset.offset.y;
+        let (rotation, scale) = tile.orientation();
+
+        DrawParam::default()
+            .src(Rect::new(src.x / iw, src.y / ih, w / iw, h / ih))
+            .dest([left + w / 2., top + h / 2.])
+            .offset([0.5, 0.5])
+            .rotation(rotation)
+            .scale(scale)
+            .color(Color::new(1., 1., 1., layer.opacity))
+    }
+
+    /// Draws a single layer. Object layers and hidden layers draw nothing.
+    pub fn draw_layer(&self, canvas: &mut Canvas, layer: usize, param: impl Into<DrawParam>) {
+        let visible = matches!(self.layers.get(layer), Some(Layer::Tiles(tiles)) if tiles.visible);
+        if !visible {
+            return;
+        }
+        let param = param.into();
+        for array in self.chunks[layer]
+            .iter()
+            .flat_map(|chunk| chunk.arrays.iter().flatten())
+        {
+            canvas.draw(array, param);
+        }
+    }
+}
+
+impl Drawable for TileMap {
+    fn draw(&self, canvas: &mut Canvas, param: impl Into<DrawParam>) {
+        let param = param.into();
+        for layer in 0..self.layers.len() {
+            self.draw_layer(canvas, layer, param);
+        }
+    }
+
+    fn dimensions(&self, _gfx: &impl Has<GraphicsContext>) -> Option<Rect> {
+        Some(Rect::new(
+            0.,
+            0.,
+            (self.width * self.tile_width) as f32,
+            (self.height * self.tile_height) as f32,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tileset(first_gid: u32) -> Tileset {
+        Tileset {
+            name: String::new(),
+            first_gid,
+            tile_width: 16,
+            tile_height: 16,
+            spacing: 2,
+            margin: 1,
+            tile_count: 8,
+            columns: 4,
+            offset: mint::Vector2 { x: 0., y: 0. },
+            image: PathBuf::new(),
+            tiles: HashMap::new(),
+            properties: Properties::new(),
+        }
+    }
+
+    #[test]
+    fn gids_and_flags() {
+        let tilesets = [tileset(1), tileset(9)];
+        assert_eq!(Tile::from_gid(0, &tilesets).unwrap(), None);
+
+        let tile = Tile::from_gid(10 | FLIPPED_HORIZONTALLY | FLIPPED_DIAGONALLY, &tilesets)
+            .unwrap()
+            .unwrap();
+        assert_eq!((tile.tileset, tile.id), (1, 1));
+        assert!(tile.flip_horizontal && tile.flip_diagonal && !tile.flip_vertical);
+
+        // the diagonal flip maps the tile's x axis onto its y axis and vice versa
+        let (rotation, scale) = tile.orientation();
+        let x_axis = (rotation.cos() * scale.x, rotation.sin() * scale.x);
+        let y_axis = (-rotation.sin() * scale.y, rotation.cos() * scale.y);
+        assert!(x_axis.0.abs() < 1e-6 && x_axis.1 == 1.);
+        assert!(y_axis.0 == -1. && y_axis.1.abs() < 1e-6);
+
+        assert_eq!(tilesets[0].tile_rect(5), Rect::new(19., 19., 16., 16.));
+    }
+
+    #[test]
+    fn tile_animations() {
+        let mut tileset = tileset(1);
+        let ms = Duration::from_millis;
+        let _ = tileset.tiles.insert(
+            0,
+            TileInfo {
+                animation: vec![
+                    AnimationFrame {
+                        tile: 2,
+                        duration: ms(100),
+                    },
+                    AnimationFrame {
+                        tile: 3,
+                        duration: ms(50),
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+        assert_eq!(tileset.animated_tile(0, ms(0)), 2);
+        assert_eq!(tileset.animated_tile(0, ms(120)), 3);
+        assert_eq!(tileset.animated_tile(0, ms(160)), 2);
+        assert_eq!(tileset.animated_tile(1, ms(120)), 1);
+    }
+
+    #[test]
+    fn layer_data() {
+        assert_eq!(decode_gids("1, 2,\n3", "csv", "").unwrap(), [1, 2, 3]);
+        // [1, 0x80000002] as little endian bytes
+        assert_eq!(
+            decode_gids("AQAAAAIAAIA=", "base64", "").unwrap(),
+            [1, 0x8000_0002]
+        );
+        assert!(decode_gids("", "base64", "zstd").is_err());
+        assert_eq!(
+            parse_color("#80ff0000"),
+            Some(Color::from_rgba(255, 0, 0, 128))
+        );
+    }
+
+    #[test]
+    fn object_outlines() {
+        let object = MapObject {
+            id: 1,
+            name: String::new(),
+            class: String::new(),
+            position: mint::Point2 { x: 10., y: 20. },
+            size: mint::Vector2 { x: 4., y: 2. },
+            rotation: 90.,
+            visible: true,
+            shape: ObjectShape::Rect,
+            tile: None,
+            properties: Properties::new(),
+        };
+        let rect = object.rect();
+        assert!((rect.x - 8.).abs() < 1e-5 && (rect.y - 20.).abs() < 1e-5);
+        assert!((rect.w - 2.).abs() < 1e-5 && (rect.h - 4.).abs() < 1e-5);
+    }
+}
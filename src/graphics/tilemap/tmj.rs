@@ -0,0 +1,447 @@
+//! The JSON formats, `.tmj` for maps and `.tsj` for tilesets.
+
+use super::{
+    check_map, decode_gids, tile_layer, tmx, AnimationFrame, Inherited, Layer, MapData, MapObject,
+    ObjectLayer, ObjectShape, Properties, PropertyValue, Tile, TileInfo, Tileset,
+};
+use crate::{GameError, GameResult};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::HashMap, path::Path, time::Duration};
+
+#[derive(Deserialize)]
+struct JsonMap {
+    orientation: String,
+    width: u32,
+    height: u32,
+    tilewidth: u32,
+    tileheight: u32,
+    #[serde(default)]
+    infinite: bool,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+    #[serde(default)]
+    tilesets: Vec<JsonTileset>,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+}
+
+#[derive(Deserialize)]
+struct JsonProperty {
+    name: String,
+    #[serde(default = "string_type", rename = "type")]
+    ty: String,
+    value: Value,
+}
+
+fn string_type() -> String {
+    String::from("string")
+}
+
+#[derive(Deserialize)]
+struct JsonTileset {
+    #[serde(default)]
+    firstgid: u32,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    tilewidth: u32,
+    #[serde(default)]
+    tileheight: u32,
+    #[serde(default)]
+    spacing: u32,
+    #[serde(default)]
+    margin: u32,
+    #[serde(default)]
+    tilecount: u32,
+    #[serde(default)]
+    columns: u32,
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(default)]
+    tileoffset: Option<JsonPoint>,
+    #[serde(default)]
+    tiles: Vec<JsonTile>,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+#[derive(Deserialize)]
+struct JsonTile {
+    id: u32,
+    // "type" was renamed to "class" in Tiled 1.9
+    #[serde(default, alias = "type")]
+    class: String,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+    #[serde(default)]
+    animation: Vec<JsonFrame>,
+}
+
+#[derive(Deserialize)]
+struct JsonFrame {
+    tileid: u32,
+    duration: u64,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+struct JsonPoint {
+    x: f32,
+    y: f32,
+}
+
+impl From<JsonPoint> for mint::Point2<f32> {
+    fn from(p: JsonPoint) -> Self {
+        mint::Point2 { x: p.x, y: p.y }
+    }
+}
+
+fn one() -> f32 {
+    1.
+}
+
+fn yes() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct JsonLayer {
+    #[serde(rename = "type")]
+    ty: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+    #[serde(default = "one")]
+    opacity: f32,
+    #[serde(default = "yes")]
+    visible: bool,
+    #[serde(default)]
+    offsetx: f32,
+    #[serde(default)]
+    offsety: f32,
+    #[serde(default)]
+    data: Option<Value>,
+    #[serde(default)]
+    chunks: Option<Value>,
+    #[serde(default)]
+    encoding: Option<String>,
+    #[serde(default)]
+    compression: String,
+    #[serde(default)]
+    objects: Vec<JsonObject>,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+#[derive(Deserialize)]
+struct JsonObject {
+    #[serde(default)]
+    id: u32,
+    #[serde(default)]
+    name: String,
+    #[serde(default, alias = "type")]
+    class: String,
+    #[serde(default)]
+    x: f32,
+    #[serde(default)]
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default = "yes")]
+    visible: bool,
+    #[serde(default)]
+    gid: u32,
+    #[serde(default)]
+    ellipse: bool,
+    #[serde(default)]
+    point: bool,
+    #[serde(default)]
+    polygon: Option<Vec<JsonPoint>>,
+    #[serde(default)]
+    polyline: Option<Vec<JsonPoint>>,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+pub(super) fn parse_map(json: &[u8], dir: &Path) -> GameResult<MapData> {
+    let map: JsonMap = serde_json::from_slice(json)?;
+    check_map(&map.orientation, map.infinite)?;
+
+    let mut tilesets = Vec::new();
+    for tileset in map.tilesets {
+        tilesets.push(match &tileset.source {
+            Some(source) => load_tileset(&dir.join(source), tileset.firstgid)?,
+            None => convert_tileset(tileset, dir)?,
+        });
+    }
+    tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+    let mut layers = Vec::new();
+    convert_layers(map.layers, &tilesets, Inherited::default(), &mut layers)?;
+
+    Ok(MapData {
+        width: map.width,
+        height: map.height,
+        tile_width: map.tilewidth,
+        tile_height: map.tileheight,
+        properties: convert_properties(map.properties)?,
+        tilesets,
+        layers,
+    })
+}
+
+/// Loads an external tileset, which can be in either format.
+fn load_tileset(path: &Path, first_gid: u32) -> GameResult<Tileset> {
+    if path.extension().is_some_and(|ext| ext == "tsx") {
+        return tmx::load_tileset(path, first_gid);
+    }
+    let mut tileset: JsonTileset = serde_json::from_slice(&std::fs::read(path)?)?;
+    tileset.firstgid = first_gid;
+    convert_tileset(tileset, path.parent().unwrap_or_else(|| Path::new("")))
+}
+
+fn convert_tileset(tileset: JsonTileset, dir: &Path) -> GameResult<Tileset> {
+    let image = tileset.image.ok_or_else(|| {
+        GameError::ResourceLoadError(format!(
+            "tileset `{}` has no single image, which isn't supported",
+            tileset.name
+        ))
+    })?;
+
+    let mut tiles = HashMap::new();
+    for tile in tileset.tiles {
+        let _ = tiles.insert(
+            tile.id,
+            TileInfo {
+                class: tile.class,
+                properties: convert_properties(tile.properties)?,
+                animation: tile
+                    .animation
+                    .iter()
+                    .map(|frame| AnimationFrame {
+                        tile: frame.tileid,
+                        duration: Duration::from_millis(frame.duration),
+                    })
+                    .collect(),
+            },
+        );
+    }
+
+    Ok(Tileset {
+        name: tileset.name,
+        first_gid: tileset.firstgid,
+        tile_width: tileset.tilewidth,
+        tile_height: tileset.tileheight,
+        spacing: tileset.spacing,
+        margin: tileset.margin,
+        tile_count: tileset.tilecount,
+        columns: tileset.columns,
+        offset: tileset
+            .tileoffset
+            .map_or(mint::Vector2 { x: 0., y: 0. }, |p| mint::Vector2 {
+                x: p.x,
+                y: p.y,
+            }),
+        image: dir.join(image),
+        tiles,
+        properties: convert_properties(tileset.properties)?,
+    })
+}
+
+fn convert_layers(
+    json: Vec<JsonLayer>,
+    tilesets: &[Tileset],
+    inherited: Inherited,
+    layers: &mut Vec<Layer>,
+) -> GameResult {
+    for layer in json {
+        let inherited = inherited.apply(
+            layer.opacity,
+            layer.visible,
+            mint::Vector2 {
+                x: layer.offsetx,
+                y: layer.offsety,
+            },
+        );
+        match layer.ty.as_str() {
+            "tilelayer" => {
+                if layer.chunks.is_some() {
+                    return Err(GameError::ResourceLoadError(String::from(
+                        "infinite maps aren't supported",
+                    )));
+                }
+                let invalid = || {
+                    GameError::ResourceLoadError(format!("invalid data in layer `{}`", layer.name))
+                };
+                let gids = match (&layer.data, &layer.encoding) {
+                    (Some(Value::String(data)), Some(encoding)) => {
+                        decode_gids(data, encoding, &layer.compression)?
+                    }
+                    (Some(Value::Array(gids)), _) => gids
+                        .iter()
+                        .map(|gid| gid.as_u64().map(|gid| gid as u32).ok_or_else(invalid))
+                        .collect::<GameResult<_>>()?,
+                    _ => return Err(invalid()),
+                };
+                layers.push(Layer::Tiles(tile_layer(
+                    layer.name,
+                    (layer.width, layer.height),
+                    &gids,
+                    tilesets,
+                    inherited,
+                    convert_properties(layer.properties)?,
+                )?));
+            }
+            "objectgroup" => {
+                let objects = layer
+                    .objects
+                    .into_iter()
+                    .map(|object| convert_object(object, tilesets))
+                    .collect::<GameResult<_>>()?;
+                layers.push(Layer::Objects(ObjectLayer {
+                    name: layer.name,
+                    opacity: inherited.opacity,
+                    visible: inherited.visible,
+                    offset: inherited.offset,
+                    properties: convert_properties(layer.properties)?,
+                    objects,
+                }));
+            }
+            "group" => convert_layers(layer.layers, tilesets, inherited, layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn convert_object(object: JsonObject, tilesets: &[Tileset]) -> GameResult<MapObject> {
+    let points = |points: Vec<JsonPoint>| points.into_iter().map(Into::into).collect();
+    let shape = if object.ellipse {
+        ObjectShape::Ellipse
+    } else if object.point {
+        ObjectShape::Point
+    } else if let Some(polygon) = object.polygon {
+        ObjectShape::Polygon(points(polygon))
+    } else if let Some(polyline) = object.polyline {
+        ObjectShape::Polyline(points(polyline))
+    } else {
+        ObjectShape::Rect
+    };
+
+    Ok(MapObject {
+        id: object.id,
+        name: object.name,
+        class: object.class,
+        position: mint::Point2 {
+            x: object.x,
+            y: object.y,
+        },
+        size: mint::Vector2 {
+            x: object.width,
+            y: object.height,
+        },
+        rotation: object.rotation,
+        visible: object.visible,
+        shape,
+        tile: Tile::from_gid(object.gid, tilesets)?,
+        properties: convert_properties(object.properties)?,
+    })
+}
+
+fn convert_properties(json: Vec<JsonProperty>) -> GameResult<Properties> {
+    json.into_iter()
+        .map(|property| {
+            let value = convert_value(&property.ty, property.value)?;
+            Ok((property.name, value))
+        })
+        .collect()
+}
+
+fn convert_value(ty: &str, value: Value) -> GameResult<PropertyValue> {
+    Ok(match (ty, value) {
+        (_, Value::Bool(b)) => PropertyValue::Bool(b),
+        ("int", Value::Number(n)) => PropertyValue::Int(n.as_i64().unwrap_or_default()),
+        ("object", Value::Number(n)) => {
+            PropertyValue::Object(n.as_u64().unwrap_or_default() as u32)
+        }
+        (_, Value::Number(n)) => PropertyValue::Float(n.as_f64().unwrap_or_default()),
+        // members of a class value don't carry their types, so they're guessed from the JSON
+        (_, Value::Object(members)) => PropertyValue::Class(
+            members
+                .into_iter()
+                .map(|(name, value)| {
+                    let ty = match &value {
+                        Value::Number(n) if n.is_i64() => "int",
+                        _ => "",
+                    };
+                    Ok((name, convert_value(ty, value)?))
+                })
+                .collect::<GameResult<_>>()?,
+        ),
+        (ty, Value::String(s)) => PropertyValue::parse(ty, &s)?,
+        (ty, _) => PropertyValue::parse(ty, "")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = r##"{
+        "orientation": "orthogonal", "width": 2, "height": 1, "tilewidth": 8, "tileheight": 8,
+        "infinite": false,
+        "properties": [{ "name": "tint", "type": "color", "value": "#ff00ff00" }],
+        "tilesets": [{
+            "firstgid": 1, "name": "a", "tilewidth": 8, "tileheight": 8, "tilecount": 2,
+            "columns": 2, "image": "a.png",
+            "tiles": [{ "id": 0, "animation": [{ "tileid": 1, "duration": 100 }] }]
+        }],
+        "layers": [
+            { "type": "tilelayer", "name": "ground", "width": 2, "height": 1,
+              "data": [1, 1073741826] },
+            { "type": "group", "name": "g", "visible": false, "layers": [
+                { "type": "objectgroup", "name": "triggers", "objects": [
+                    { "id": 4, "name": "door", "class": "exit", "x": 1, "y": 2,
+                      "polyline": [{ "x": 0, "y": 0 }, { "x": 4, "y": 0 }],
+                      "properties": [{ "name": "target", "type": "object", "value": 7 }] }
+                ] }
+            ] }
+        ]
+    }"##;
+
+    #[test]
+    fn parse_tmj() {
+        let map = parse_map(MAP.as_bytes(), Path::new("")).unwrap();
+        assert_eq!(
+            map.properties["tint"],
+            PropertyValue::Color(crate::graphics::Color::from_rgba(0, 255, 0, 255))
+        );
+        assert_eq!(map.tilesets[0].tiles[&0].animation[0].tile, 1);
+
+        let Layer::Tiles(tiles) = &map.layers[0] else {
+            panic!("expected a tile layer");
+        };
+        let flipped = tiles.tile(1, 0).unwrap();
+        assert!(flipped.flip_vertical && flipped.id == 1);
+
+        let Layer::Objects(objects) = &map.layers[1] else {
+            panic!("expected an object layer");
+        };
+        assert!(!objects.visible);
+        let door = &objects.objects[0];
+        assert_eq!(door.class, "exit");
+        assert_eq!(door.properties["target"], PropertyValue::Object(7));
+        assert!(matches!(&door.shape, ObjectShape::Polyline(points) if points.len() == 2));
+    }
+}
@@ -0,0 +1,406 @@
+//! The XML formats, `.tmx` for maps and `.tsx` for tilesets.
+
+use super::{
+    check_map, decode_gids, tile_layer, AnimationFrame, Inherited, Layer, MapData, MapObject,
+    ObjectLayer, ObjectShape, Properties, PropertyValue, Tile, TileInfo, Tileset,
+};
+use crate::{GameError, GameResult};
+use std::{collections::HashMap, path::Path, str::FromStr, time::Duration};
+use xml::reader::{EventReader, XmlEvent};
+
+/// An XML element with everything we need from it.
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: HashMap<String, String>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn parse(xml: &[u8]) -> GameResult<Self> {
+        let mut stack = vec![Element::default()];
+        for event in EventReader::new(xml) {
+            let event =
+                event.map_err(|e| GameError::ResourceLoadError(format!("XML parse error: {e}")))?;
+            match event {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => stack.push(Element {
+                    name: name.local_name,
+                    attributes: attributes
+                        .into_iter()
+                        .map(|a| (a.name.local_name, a.value))
+                        .collect(),
+                    ..Default::default()
+                }),
+                XmlEvent::EndElement { .. } => {
+                    let element = stack.pop().unwrap(/* the reader checks nesting */);
+                    stack
+                        .last_mut()
+                        .unwrap(/* the document root is never popped */)
+                        .children
+                        .push(element);
+                }
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&text);
+                    }
+                }
+                _ => {}
+            }
+        }
+        stack
+            .pop()
+            .and_then(|document| document.children.into_iter().next())
+            .ok_or_else(|| GameError::ResourceLoadError(String::from("empty XML document")))
+    }
+
+    fn attr(&self, name: &str) -> &str {
+        self.attributes.get(name).map_or("", String::as_str)
+    }
+
+    /// Parses an attribute, or returns `default` if it's missing.
+    fn parse_attr<T: FromStr>(&self, name: &str, default: T) -> GameResult<T> {
+        match self.attributes.get(name) {
+            None => Ok(default),
+            Some(value) => value.parse().map_err(|_| {
+                GameError::ResourceLoadError(format!(
+                    "invalid `{name}` attribute `{value}` on <{}>",
+                    self.name
+                ))
+            }),
+        }
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    fn properties(&self) -> GameResult<Properties> {
+        let mut properties = Properties::new();
+        for property in self
+            .child("properties")
+            .into_iter()
+            .flat_map(|properties| properties.children("property"))
+        {
+            let ty = property
+                .attributes
+                .get("type")
+                .map_or("string", String::as_str);
+            let value = if ty == "class" {
+                PropertyValue::Class(property.properties()?)
+            } else if let Some(value) = property.attributes.get("value") {
+                PropertyValue::parse(ty, value)?
+            } else {
+                // multi-line strings are stored as text
+                PropertyValue::parse(ty, &property.text)?
+            };
+            let _ = properties.insert(property.attr("name").to_owned(), value);
+        }
+        Ok(properties)
+    }
+
+    fn offset(&self) -> GameResult<mint::Vector2<f32>> {
+        Ok(mint::Vector2 {
+            x: self.parse_attr("offsetx", 0.)?,
+            y: self.parse_attr("offsety", 0.)?,
+        })
+    }
+}
+
+pub(super) fn parse_map(xml: &[u8], dir: &Path) -> GameResult<MapData> {
+    let map = Element::parse(xml)?;
+    if map.name != "map" {
+        return Err(GameError::ResourceLoadError(String::from(
+            "not a Tiled map",
+        )));
+    }
+    check_map(map.attr("orientation"), map.attr("infinite") == "1")?;
+
+    let mut tilesets = Vec::new();
+    for tileset in map.children("tileset") {
+        let first_gid = tileset.parse_attr("firstgid", 1)?;
+        tilesets.push(match tileset.attributes.get("source") {
+            Some(source) => load_tileset(&dir.join(source), first_gid)?,
+            None => parse_tileset(tileset, first_gid, dir)?,
+        });
+    }
+    tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+    let mut layers = Vec::new();
+    parse_layers(&map, &tilesets, Inherited::default(), &mut layers)?;
+
+    Ok(MapData {
+        width: map.parse_attr("width", 0)?,
+        height: map.parse_attr("height", 0)?,
+        tile_width: map.parse_attr("tilewidth", 0)?,
+        tile_height: map.parse_attr("tileheight", 0)?,
+        properties: map.properties()?,
+        tilesets,
+        layers,
+    })
+}
+
+/// Loads an external `.tsx` tileset.
+pub(super) fn load_tileset(path: &Path, first_gid: u32) -> GameResult<Tileset> {
+    let xml = std::fs::read(path)?;
+    let tileset = Element::parse(&xml)?;
+    parse_tileset(
+        &tileset,
+        first_gid,
+        path.parent().unwrap_or_else(|| Path::new("")),
+    )
+}
+
+fn parse_tileset(tileset: &Element, first_gid: u32, dir: &Path) -> GameResult<Tileset> {
+    let name = tileset.attr("name").to_owned();
+    let image = tileset.child("image").ok_or_else(|| {
+        GameError::ResourceLoadError(format!(
+            "tileset `{name}` has no single image, which isn't supported"
+        ))
+    })?;
+
+    let mut tiles = HashMap::new();
+    for tile in tileset.children("tile") {
+        let animation = tile
+            .child("animation")
+            .into_iter()
+            .flat_map(|animation| animation.children("frame"))
+            .map(|frame| {
+                Ok(AnimationFrame {
+                    tile: frame.parse_attr("tileid", 0)?,
+                    duration: Duration::from_millis(frame.parse_attr("duration", 0)?),
+                })
+            })
+            .collect::<GameResult<_>>()?;
+        // "type" was renamed to "class" in Tiled 1.9
+        let class = match tile.attributes.get("class") {
+            Some(class) => class,
+            None => tile.attr("type"),
+        };
+        let _ = tiles.insert(
+            tile.parse_attr("id", 0)?,
+            TileInfo {
+                class: class.to_owned(),
+                properties: tile.properties()?,
+                animation,
+            },
+        );
+    }
+
+    let offset = match tileset.child("tileoffset") {
+        Some(offset) => mint::Vector2 {
+            x: offset.parse_attr("x", 0.)?,
+            y: offset.parse_attr("y", 0.)?,
+        },
+        None => mint::Vector2 { x: 0., y: 0. },
+    };
+
+    Ok(Tileset {
+        first_gid,
+        tile_width: tileset.parse_attr("tilewidth", 0)?,
+        tile_height: tileset.parse_attr("tileheight", 0)?,
+        spacing: tileset.parse_attr("spacing", 0)?,
+        margin: tileset.parse_attr("margin", 0)?,
+        tile_count: tileset.parse_attr("tilecount", 0)?,
+        columns: tileset.parse_attr("columns", 0)?,
+        offset,
+        image: dir.join(image.attr("source")),
+        tiles,
+        properties: tileset.properties()?,
+        name,
+    })
+}
+
+fn parse_layers(
+    parent: &Element,
+    tilesets: &[Tileset],
+    inherited: Inherited,
+    layers: &mut Vec<Layer>,
+) -> GameResult {
+    for element in &parent.children {
+        let inherited = inherited.apply(
+            element.parse_attr("opacity", 1.)?,
+            element.attr("visible") != "0",
+            element.offset()?,
+        );
+        let name = element.attr("name").to_owned();
+        match element.name.as_str() {
+            "layer" => {
+                let size = (
+                    element.parse_attr("width", 0)?,
+                    element.parse_attr("height", 0)?,
+                );
+                let data = element.child("data").ok_or_else(|| {
+                    GameError::ResourceLoadError(format!("layer `{name}` has no data"))
+                })?;
+                if data.child("chunk").is_some() {
+                    return Err(GameError::ResourceLoadError(String::from(
+                        "infinite maps aren't supported",
+                    )));
+                }
+                let gids = if data.attributes.contains_key("encoding") {
+                    decode_gids(&data.text, data.attr("encoding"), data.attr("compression"))?
+                } else {
+                    data.children("tile")
+                        .map(|tile| tile.parse_attr("gid", 0))
+                        .collect::<GameResult<_>>()?
+                };
+                layers.push(Layer::Tiles(tile_layer(
+                    name,
+                    size,
+                    &gids,
+                    tilesets,
+                    inherited,
+                    element.properties()?,
+                )?));
+            }
+            "objectgroup" => {
+                let objects = element
+                    .children("object")
+                    .map(|object| parse_object(object, tilesets))
+                    .collect::<GameResult<_>>()?;
+                layers.push(Layer::Objects(ObjectLayer {
+                    name,
+                    opacity: inherited.opacity,
+                    visible: inherited.visible,
+                    offset: inherited.offset,
+                    properties: element.properties()?,
+                    objects,
+                }));
+            }
+            "group" => parse_layers(element, tilesets, inherited, layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_object(object: &Element, tilesets: &[Tileset]) -> GameResult<MapObject> {
+    let points = |element: &Element| {
+        element
+            .attr("points")
+            .split_whitespace()
+            .map(|point| {
+                let (x, y) = point.split_once(',')?;
+                Some(mint::Point2 {
+                    x: x.parse().ok()?,
+                    y: y.parse().ok()?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| GameError::ResourceLoadError(String::from("invalid object points")))
+    };
+
+    let shape = if object.child("ellipse").is_some() {
+        ObjectShape::Ellipse
+    } else if object.child("point").is_some() {
+        ObjectShape::Point
+    } else if let Some(polygon) = object.child("polygon") {
+        ObjectShape::Polygon(points(polygon)?)
+    } else if let Some(polyline) = object.child("polyline") {
+        ObjectShape::Polyline(points(polyline)?)
+    } else {
+        ObjectShape::Rect
+    };
+    let class = match object.attributes.get("class") {
+        Some(class) => class,
+        None => object.attr("type"),
+    };
+
+    Ok(MapObject {
+        id: object.parse_attr("id", 0)?,
+        name: object.attr("name").to_owned(),
+        class: class.to_owned(),
+        position: mint::Point2 {
+            x: object.parse_attr("x", 0.)?,
+            y: object.parse_attr("y", 0.)?,
+        },
+        size: mint::Vector2 {
+            x: object.parse_attr("width", 0.)?,
+            y: object.parse_attr("height", 0.)?,
+        },
+        rotation: object.parse_attr("rotation", 0.)?,
+        visible: object.attr("visible") != "0",
+        shape,
+        tile: Tile::from_gid(object.parse_attr("gid", 0)?, tilesets)?,
+        properties: object.properties()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="3" height="2"
+     tilewidth="16" tileheight="16" infinite="0">
+ <properties>
+  <property name="music" type="file" value="level1.ogg"/>
+  <property name="gravity" type="float" value="9.5"/>
+ </properties>
+ <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="16" tilecount="4" columns="2">
+  <image source="terrain.png" width="32" height="32"/>
+  <tile id="1" type="water">
+   <animation><frame tileid="1" duration="200"/><frame tileid="3" duration="200"/></animation>
+  </tile>
+ </tileset>
+ <group name="ground" offsetx="4" opacity="0.5">
+  <layer id="1" name="tiles" width="3" height="2" offsetx="1">
+   <data encoding="csv">1,2,0,
+2147483652,0,1</data>
+  </layer>
+ </group>
+ <objectgroup id="2" name="spawns">
+  <object id="1" name="player" type="spawn" x="8" y="24" width="4" height="4">
+   <properties><property name="lives" type="int" value="3"/></properties>
+  </object>
+  <object id="2" name="wall" x="0" y="0"><polygon points="0,0 16,0 16,8"/></object>
+  <object id="3" x="16" y="16" width="16" height="16" gid="2"/>
+ </objectgroup>
+</map>"##;
+
+    #[test]
+    fn parse_tmx() {
+        let map = parse_map(MAP.as_bytes(), Path::new("maps")).unwrap();
+        assert_eq!((map.width, map.height, map.tile_width), (3, 2, 16));
+        assert_eq!(map.properties["gravity"], PropertyValue::Float(9.5));
+        assert_eq!(
+            map.properties["music"],
+            PropertyValue::File("level1.ogg".into())
+        );
+
+        let tileset = &map.tilesets[0];
+        assert_eq!(tileset.image, Path::new("maps/terrain.png"));
+        assert_eq!(tileset.tiles[&1].class, "water");
+        assert_eq!(tileset.animated_tile(1, Duration::from_millis(250)), 3);
+
+        let Layer::Tiles(tiles) = &map.layers[0] else {
+            panic!("expected a tile layer");
+        };
+        assert_eq!(tiles.opacity, 0.5);
+        assert_eq!(tiles.offset, mint::Vector2 { x: 5., y: 0. });
+        assert_eq!(tiles.tile(1, 0).map(|tile| tile.id), Some(1));
+        assert_eq!(tiles.tile(2, 0), None);
+        let flipped = tiles.tile(0, 1).unwrap();
+        assert!(flipped.flip_horizontal && flipped.id == 3);
+
+        let Layer::Objects(objects) = &map.layers[1] else {
+            panic!("expected an object layer");
+        };
+        let player = &objects.objects[0];
+        assert_eq!(
+            (player.class.as_str(), player.rect()),
+            ("spawn", crate::graphics::Rect::new(8., 24., 4., 4.))
+        );
+        assert_eq!(player.properties["lives"], PropertyValue::Int(3));
+        assert_eq!(objects.objects[1].points().len(), 3);
+        // tile objects are anchored at the bottom
+        assert_eq!(objects.objects[2].rect().y, 0.);
+    }
+}
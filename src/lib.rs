@@ -188,8 +188,23 @@
 #![warn(missing_copy_implementations)]
 #![allow(clippy::needless_doctest_main)]
 
+#[cfg(not(feature = "tracing"))]
 #[macro_use]
 extern crate log;
+// with the `tracing` feature, ggez's log messages are emitted as tracing events instead
+#[cfg(feature = "tracing")]
+#[macro_use]
+extern crate tracing;
+
+/// Enters a `tracing` span that lasts until the end of the enclosing scope.
+///
+/// Expands to nothing unless the `tracing` feature is enabled, so the fields are never evaluated.
+macro_rules! trace_scope {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($($args)*).entered();
+    };
+}
 
 pub use glam;
 pub use mint;
@@ -79,6 +79,21 @@ where
     fn latest(&self) -> T {
         self.contents[self.head]
     }
+
+    /// Returns the contents of the buffer ordered from the oldest
+    /// to the most recent item.
+    fn ordered(&self) -> impl Iterator<Item = T> + '_ {
+        let contents = self.contents();
+        let split = if self.samples > self.size {
+            (self.head + 1) % contents.len()
+        } else {
+            0
+        };
+        contents[split..]
+            .iter()
+            .chain(contents[..split].iter())
+            .copied()
+    }
 }
 
 /// A structure that contains our time-tracking state.
@@ -128,6 +143,14 @@ impl TimeContext {
         }
     }
 
+    /// Returns the lengths of the last 200 frames, ordered from
+    /// the oldest to the most recent one.
+    ///
+    /// Useful for drawing frame time graphs.
+    pub fn frame_durations(&self) -> impl Iterator<Item = time::Duration> + '_ {
+        self.frame_durations.ordered()
+    }
+
     /// Gets the FPS of the game, averaged over the last
     /// 200 frames.
     pub fn fps(&self) -> f64 {